@@ -1,14 +1,7 @@
 use futures::StreamExt;
-use mpvipc_async::{Event, Mpv, MpvDataType, MpvError, MpvExt, Property, parse_property};
-
-fn seconds_to_hms(total: f64) -> String {
-    let total = total as u64;
-    let seconds = total % 60;
-    let total = total / 60;
-    let minutes = total % 60;
-    let hours = total / 60;
-    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
-}
+use mpvipc_async::{
+    Event, Mpv, MpvDataType, MpvError, MpvExt, Property, format_duration, parse_property,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), MpvError> {
@@ -32,10 +25,10 @@ async fn main() -> Result<(), MpvError> {
                         println!("Pause: {}", value);
                     }
                     Property::PlaybackTime(Some(value)) => {
-                        println!("Playback time: {}", seconds_to_hms(value));
+                        println!("Playback time: {}", format_duration(value));
                     }
                     Property::Duration(Some(value)) => {
-                        println!("Duration: {}", seconds_to_hms(value));
+                        println!("Duration: {}", format_duration(value));
                     }
                     Property::Metadata(Some(value)) => {
                         println!("File tags:");
@@ -0,0 +1,361 @@
+//! Builder for configuring a [`Mpv`] connection before it is established.
+
+use std::{
+    error, io,
+    path::Path,
+    sync::{Arc, Mutex, atomic::AtomicU64},
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    net::UnixStream,
+    sync::{broadcast, mpsc},
+};
+use tokio_util::codec::{Decoder, Encoder, LinesCodec};
+
+use crate::{
+    Mpv, MpvError,
+    capabilities::MpvCapabilities,
+    ipc::{MpvIpc, MpvIpcEventReplay, MpvIpcHeartbeat, MpvIpcObservedProperties, MpvIpcRateLimit},
+    message_parser::MAX_VALUE_DEPTH,
+    streams::FIRST_STREAM_OBSERVE_ID,
+};
+
+/// How often to check whether the socket file exists in [`MpvBuilder::connect_when_available`].
+const SOCKET_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A codec that frames the JSON messages exchanged with mpv.
+///
+/// mpv's IPC protocol is newline-delimited JSON, which is handled by [`LinesCodec`].
+/// Any other codec that decodes and encodes [`String`]s can be used with
+/// [`MpvBuilder::connect_socket_with_codec`], for example to talk to a proxy
+/// that frames messages differently. Each frame must contain a single JSON message.
+///
+/// This trait is implemented for all types that satisfy its bounds.
+pub trait MpvCodec:
+    Decoder<Item = String, Error: error::Error + Send>
+    + Encoder<String, Error: error::Error + Send>
+    + Send
+    + Unpin
+    + 'static
+{
+}
+
+impl<C> MpvCodec for C where
+    C: Decoder<Item = String, Error: error::Error + Send>
+        + Encoder<String, Error: error::Error + Send>
+        + Send
+        + Unpin
+        + 'static
+{
+}
+
+/// A builder for [`Mpv`] instances with non-default settings.
+///
+/// [`Mpv::connect`] and [`Mpv::connect_socket`] are shorthands for
+/// connecting with a default [`MpvBuilder`].
+///
+/// # Example
+/// ```
+/// use mpvipc_async::{Mpv, MpvError};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), MpvError> {
+///     let mpv = Mpv::builder()
+///         .event_replay_depth(10)
+///         .connect("/tmp/mpvsocket")
+///         .await?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MpvBuilder {
+    event_replay_depth: Option<usize>,
+    heartbeat: Option<(Duration, Duration)>,
+    rate_limit: Option<(usize, Duration)>,
+    default_osd: bool,
+    command_timeout: Option<Duration>,
+    max_value_depth: Option<usize>,
+}
+
+impl MpvBuilder {
+    /// Create a new builder with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the `depth` most recent events from mpv in a replay buffer.
+    ///
+    /// Every stream created with [`Mpv::get_event_stream`] will start by yielding
+    /// the buffered events, before continuing with live events. This makes it possible
+    /// to subscribe shortly after connecting without missing early events, like an
+    /// initial [`Event::FileLoaded`](crate::Event::FileLoaded).
+    ///
+    /// Note that the buffered events are replayed to *every* new stream, not only the first one.
+    ///
+    /// The buffer holds at most `depth` raw JSON events at a time, so the memory cost
+    /// is roughly `depth` times the size of an average event. Most events are tiny,
+    /// but property changes for large properties like `playlist` or `metadata` can be
+    /// a few kilobytes each. A depth of `0` disables the replay buffer, which is the default.
+    pub fn event_replay_depth(mut self, depth: usize) -> Self {
+        self.event_replay_depth = Some(depth).filter(|depth| *depth > 0);
+        self
+    }
+
+    /// Check every `interval` that mpv still answers, by requesting the `mpv-version` property.
+    ///
+    /// If mpv does not reply within `timeout`, or the request fails, the connection is
+    /// considered dead and closed, so that [`Mpv::is_connected`] returns `false` and
+    /// further commands fail. This detects half-open sockets, e.g. behind a proxy,
+    /// which would otherwise go unnoticed until the next command hangs.
+    /// The time of the last successful heartbeat is available with [`Mpv::last_heartbeat`].
+    ///
+    /// Heartbeats are disabled by default.
+    pub fn heartbeat(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.heartbeat = Some((interval, timeout));
+        self
+    }
+
+    /// Send at most `max_commands` commands to mpv per `interval`.
+    ///
+    /// This protects slow mpv instances from floods of commands, e.g. from a UI that sets
+    /// the volume for every pixel a slider is dragged. Commands over the limit are queued,
+    /// and sent in order as soon as the limit allows.
+    ///
+    /// While queued, [`Mpv::set_property`] commands are coalesced: a queued `set_property`
+    /// is replaced by a newer one for the same property, which is safe because setting a
+    /// property is idempotent. The replaced call returns `Ok(())` right away, and only the
+    /// newest value is sent to mpv. This also applies to the [`MpvExt`](crate::MpvExt) setters
+    /// built on [`Mpv::set_property`]. Coalescing never moves a property change past any other
+    /// kind of queued command.
+    ///
+    /// All other commands, like seeks, playlist edits, getters and observers, are never
+    /// coalesced or dropped, only delayed, since skipping them would change the outcome.
+    /// This includes the `osd-msg` commands sent when [`MpvBuilder::default_osd`] is enabled.
+    /// Heartbeats are not counted against the limit.
+    ///
+    /// `max_commands` is at least `1`. There is no rate limit by default.
+    pub fn rate_limit(mut self, max_commands: usize, interval: Duration) -> Self {
+        self.rate_limit = Some((max_commands, interval));
+        self
+    }
+
+    /// Show the changes made by high-level commands on mpv's OSD.
+    ///
+    /// When enabled, [`MpvExt::seek`](crate::MpvExt::seek),
+    /// [`MpvExt::seek_with_precision`](crate::MpvExt::seek_with_precision),
+    /// [`MpvExt::set_volume`](crate::MpvExt::set_volume),
+    /// [`MpvExt::set_volume_preset`](crate::MpvExt::set_volume_preset) and
+    /// [`MpvExt::set_speed`](crate::MpvExt::set_speed) are sent with the `osd-msg`
+    /// command prefix, so that mpv shows the new position, volume or speed the same way
+    /// it does for key bindings. Everything else, including [`Mpv::run_command`],
+    /// [`Mpv::run_command_raw`] and [`Mpv::set_property`], is sent unchanged.
+    /// There is no separate per-command prefix API, so to use another prefix for a single
+    /// command, pass it as the command to [`Mpv::run_command_raw`], e.g.
+    /// `mpv.run_command_raw("no-osd", &["seek", "10"])`.
+    ///
+    /// This is disabled by default, which leaves the OSD behaviour to mpv.
+    pub fn default_osd(mut self, enabled: bool) -> Self {
+        self.default_osd = enabled;
+        self
+    }
+
+    /// Give up on [`Mpv::run_command`] and [`Mpv::get_property`] calls that mpv does not
+    /// reply to within `timeout`, returning [`MpvError::Timeout`].
+    ///
+    /// Use [`Mpv::run_command_timeout`] and [`Mpv::get_property_timeout`] for single calls
+    /// that are expected to take longer, like the `subprocess` command. Note that mpv still
+    /// runs a command that timed out, and that commands are answered in order, so the
+    /// following calls wait until mpv is done with it. There is no timeout by default.
+    pub fn command_timeout(mut self, timeout: Duration) -> Self {
+        self.command_timeout = Some(timeout);
+        self
+    }
+
+    /// Give up on property values in events that are nested more than `depth` arrays or
+    /// objects deep, yielding an error for the event instead.
+    ///
+    /// mpv's own values are only nested a few levels deep, but data from untrusted network
+    /// streams, like `metadata`, can end up in events. The limit protects against stack
+    /// overflows while parsing them. The default is 64.
+    pub fn max_value_depth(mut self, depth: usize) -> Self {
+        self.max_value_depth = Some(depth);
+        self
+    }
+
+    /// Connect to a unix socket, hosted by mpv, at the given path.
+    pub async fn connect(self, socket_path: &str) -> Result<Mpv, MpvError> {
+        log::debug!("Connecting to mpv socket at {}", socket_path);
+
+        let socket = match UnixStream::connect(socket_path).await {
+            Ok(stream) => Ok(stream),
+            Err(err) => Err(MpvError::MpvSocketConnectionError(err.to_string())),
+        }?;
+
+        let mut mpv = self.connect_socket(socket).await?;
+        mpv.socket_path = Some(socket_path.to_owned());
+        Ok(mpv)
+    }
+
+    /// Connect to a unix socket in the abstract namespace, hosted by mpv, with the given name.
+    ///
+    /// Abstract sockets have no file on disk, and are identified by their name alone.
+    /// The name is given without the leading null byte. This is only available on Linux,
+    /// since other platforms do not have an abstract socket namespace.
+    #[cfg(target_os = "linux")]
+    pub async fn connect_abstract(self, name: &str) -> Result<Mpv, MpvError> {
+        use std::os::linux::net::SocketAddrExt;
+
+        log::debug!("Connecting to abstract mpv socket @{}", name);
+
+        let socket = std::os::unix::net::SocketAddr::from_abstract_name(name)
+            .and_then(|address| std::os::unix::net::UnixStream::connect_addr(&address))
+            .and_then(|stream| {
+                stream.set_nonblocking(true)?;
+                UnixStream::from_std(stream)
+            })
+            .map_err(|err| MpvError::MpvSocketConnectionError(err.to_string()))?;
+
+        let mut mpv = self.connect_socket(socket).await?;
+        mpv.socket_path = Some(format!("@{}", name));
+        Ok(mpv)
+    }
+
+    /// Wait for the unix socket at the given path to appear, and then connect to it.
+    ///
+    /// This is useful when mpv was just spawned as a child process, and might not
+    /// have created its socket yet. The path is polled every 10 milliseconds.
+    /// If the socket does not appear within `timeout`, [`MpvError::Timeout`] is returned.
+    pub async fn connect_when_available(
+        self,
+        socket_path: &str,
+        timeout: Duration,
+    ) -> Result<Mpv, MpvError> {
+        let path = Path::new(socket_path);
+
+        tokio::time::timeout(timeout, async {
+            while !path.exists() {
+                tokio::time::sleep(SOCKET_POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .map_err(|_| {
+            MpvError::Timeout(format!(
+                "mpv socket at {:?} did not appear within {:?}",
+                path, timeout
+            ))
+        })?;
+
+        self.connect(socket_path).await
+    }
+
+    /// Connect to a unix socket at the given path, retrying while mpv is not accepting connections yet.
+    ///
+    /// Connecting is retried up to `attempts` times in total, waiting `delay` between each attempt,
+    /// as long as it fails because the socket does not exist or refuses the connection.
+    /// Unlike [`MpvBuilder::connect_when_available`], this also handles the case where the socket
+    /// file exists before mpv has started listening on it. If every attempt fails,
+    /// the error from the last attempt is returned.
+    pub async fn connect_with_retry(
+        self,
+        socket_path: &str,
+        attempts: usize,
+        delay: Duration,
+    ) -> Result<Mpv, MpvError> {
+        let mut attempt = 1;
+        let socket = loop {
+            match UnixStream::connect(socket_path).await {
+                Ok(stream) => break stream,
+                Err(err)
+                    if attempt < attempts
+                        && matches!(
+                            err.kind(),
+                            io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+                        ) =>
+                {
+                    log::debug!(
+                        "Failed to connect to mpv socket at {} (attempt {}/{}): {}",
+                        socket_path,
+                        attempt,
+                        attempts,
+                        err
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) => return Err(MpvError::MpvSocketConnectionError(err.to_string())),
+            }
+        };
+
+        let mut mpv = self.connect_socket(socket).await?;
+        mpv.socket_path = Some(socket_path.to_owned());
+        Ok(mpv)
+    }
+
+    /// Connect to an existing [`UnixStream`].
+    pub async fn connect_socket(self, socket: UnixStream) -> Result<Mpv, MpvError> {
+        self.connect_socket_with_codec(socket, LinesCodec::new())
+            .await
+    }
+
+    /// Connect to an existing [`UnixStream`], framing messages with a custom codec.
+    ///
+    /// This is only needed when the other end of the socket is not mpv itself, but
+    /// something that frames messages differently. See [`MpvCodec`] for details.
+    pub async fn connect_socket_with_codec<C: MpvCodec>(
+        self,
+        socket: UnixStream,
+        codec: C,
+    ) -> Result<Mpv, MpvError> {
+        let (com_tx, com_rx) = mpsc::channel(100);
+        let (ev_tx, _) = broadcast::channel(100);
+        let event_replay = self
+            .event_replay_depth
+            .map(|depth| Arc::new(Mutex::new(MpvIpcEventReplay::new(depth))));
+        let lossless_event_channels = Arc::new(Mutex::new(Vec::new()));
+        let observed_properties = Arc::new(Mutex::new(MpvIpcObservedProperties::default()));
+        let last_heartbeat = Arc::new(Mutex::new(None::<Instant>));
+        let mut ipc = MpvIpc::new(
+            socket,
+            codec,
+            com_rx,
+            ev_tx.clone(),
+            event_replay.clone(),
+            lossless_event_channels.clone(),
+            observed_properties.clone(),
+        );
+        if let Some((interval, timeout)) = self.heartbeat {
+            ipc = ipc.with_heartbeat(MpvIpcHeartbeat::new(
+                interval,
+                timeout,
+                last_heartbeat.clone(),
+            ));
+        }
+        if let Some((max_commands, interval)) = self.rate_limit {
+            ipc = ipc.with_rate_limit(MpvIpcRateLimit::new(max_commands, interval));
+        }
+        let max_value_depth = self.max_value_depth.unwrap_or(MAX_VALUE_DEPTH);
+        ipc = ipc.with_max_value_depth(max_value_depth);
+
+        let last_event = ipc.last_event();
+
+        log::debug!("Starting IPC handler");
+        tokio::spawn(ipc.run());
+
+        Ok(Mpv {
+            command_sender: com_tx,
+            broadcast_channel: ev_tx,
+            event_replay,
+            lossless_event_channels,
+            observed_properties,
+            capabilities: Arc::new(MpvCapabilities::default()),
+            next_observe_id: Arc::new(AtomicU64::new(FIRST_STREAM_OBSERVE_ID)),
+            last_heartbeat,
+            last_event,
+            socket_path: None,
+            default_osd: self.default_osd,
+            command_timeout: self.command_timeout,
+            max_value_depth,
+        })
+    }
+}
@@ -0,0 +1,223 @@
+//! Detection of what the connected mpv instance supports, and metadata about its options.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::OnceCell;
+
+use crate::{Mpv, MpvError};
+
+/// Lazily fetched information about what the connected mpv instance supports.
+///
+/// This is shared between all clones of a [`Mpv`] instance, and is only fetched once.
+#[derive(Debug, Default)]
+pub(crate) struct MpvCapabilities {
+    commands: OnceCell<HashSet<String>>,
+    properties: OnceCell<HashSet<String>>,
+}
+
+/// Metadata about an mpv option, see [`Mpv::get_option_info`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionInfo {
+    /// The default value, formatted as a string. Flags are formatted as `"yes"` or `"no"`.
+    pub default: Option<String>,
+    /// The lowest allowed value, for numeric options with a lower bound.
+    pub min: Option<f64>,
+    /// The highest allowed value, for numeric options with an upper bound.
+    pub max: Option<f64>,
+    /// The allowed values, for options of type `Choice` and `Flag`.
+    pub choices: Vec<String>,
+    /// The name of the option type, e.g. `"Flag"`, `"Double"` or `"Choice"`.
+    pub type_name: String,
+}
+
+impl Mpv {
+    /// Check whether the connected mpv instance supports the given input command.
+    ///
+    /// The list of commands is read from the `command-list` property the first time
+    /// this is called, and cached for the lifetime of the connection.
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     if mpv.supports_command("overlay-add").await? {
+    ///         println!("Overlays are available");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn supports_command(&self, command: &str) -> Result<bool, MpvError> {
+        let commands = self
+            .capabilities
+            .commands
+            .get_or_try_init(|| async {
+                let value = self.get_property_value("command-list").await?;
+                parse_command_list(value)
+            })
+            .await?;
+
+        Ok(commands.contains(command))
+    }
+
+    /// Check whether the connected mpv instance has the given top-level property.
+    ///
+    /// The list of properties is read from the `property-list` property the first time
+    /// this is called, and cached for the lifetime of the connection. This only knows about
+    /// top-level properties, so sub-properties like `current-tracks/video` are not found.
+    pub async fn supports_property(&self, property: &str) -> Result<bool, MpvError> {
+        let properties = self
+            .capabilities
+            .properties
+            .get_or_try_init(|| async {
+                let value = self.get_property_value("property-list").await?;
+                parse_property_list(value)
+            })
+            .await?;
+
+        Ok(properties.contains(property))
+    }
+
+    /// Read the metadata of an option, like its type, default value and range.
+    ///
+    /// This reads the `option-info/<name>` property. Which fields are present depends
+    /// on the option type, so fields that mpv does not provide are left empty.
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     let volume = mpv.get_option_info("volume").await?;
+    ///     println!("Volume can be set between {:?} and {:?}", volume.min, volume.max);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_option_info(&self, name: &str) -> Result<OptionInfo, MpvError> {
+        let value = self
+            .get_property_value(&format!("option-info/{}", name))
+            .await?;
+        parse_option_info(value)
+    }
+
+    /// Return [`MpvError::Unsupported`] if the connected mpv instance does not support `command`.
+    pub(crate) async fn require_command(&self, command: &str) -> Result<(), MpvError> {
+        if self.supports_command(command).await? {
+            Ok(())
+        } else {
+            Err(MpvError::Unsupported(format!(
+                "mpv does not support the '{}' command",
+                command
+            )))
+        }
+    }
+
+    /// Return [`MpvError::Unsupported`] if the connected mpv instance does not have `property`.
+    pub(crate) async fn require_property(&self, property: &str) -> Result<(), MpvError> {
+        if self.supports_property(property).await? {
+            Ok(())
+        } else {
+            Err(MpvError::Unsupported(format!(
+                "mpv does not have the '{}' property",
+                property
+            )))
+        }
+    }
+}
+
+/// Parse the value of an `option-info/<name>` property.
+fn parse_option_info(value: Option<Value>) -> Result<OptionInfo, MpvError> {
+    let value = value.ok_or(MpvError::MissingMpvData)?;
+    let info = value
+        .as_object()
+        .ok_or(MpvError::ValueContainsUnexpectedType {
+            expected_type: "object".to_owned(),
+            received: value.clone(),
+        })?;
+
+    let default = info.get("default-value").and_then(|default| match default {
+        Value::String(s) => Some(s.to_owned()),
+        Value::Bool(true) => Some("yes".to_owned()),
+        Value::Bool(false) => Some("no".to_owned()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    });
+
+    let choices = info
+        .get("choices")
+        .and_then(Value::as_array)
+        .map(|choices| {
+            choices
+                .iter()
+                .filter_map(|choice| choice.as_str().map(str::to_owned))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(OptionInfo {
+        default,
+        min: info.get("min").and_then(Value::as_f64),
+        max: info.get("max").and_then(Value::as_f64),
+        choices,
+        type_name: info
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned(),
+    })
+}
+
+/// Extract the command names from the value of the `command-list` property.
+fn parse_command_list(value: Option<Value>) -> Result<HashSet<String>, MpvError> {
+    let value = value.ok_or(MpvError::MissingMpvData)?;
+    let commands = value
+        .as_array()
+        .ok_or(MpvError::ValueContainsUnexpectedType {
+            expected_type: "Array".to_owned(),
+            received: value.clone(),
+        })?;
+
+    commands
+        .iter()
+        .map(|command| {
+            command
+                .get("name")
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+                .ok_or(MpvError::ValueContainsUnexpectedType {
+                    expected_type: "object with a 'name' string".to_owned(),
+                    received: command.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Extract the property names from the value of the `property-list` property.
+fn parse_property_list(value: Option<Value>) -> Result<HashSet<String>, MpvError> {
+    let value = value.ok_or(MpvError::MissingMpvData)?;
+    let properties = value
+        .as_array()
+        .ok_or(MpvError::ValueContainsUnexpectedType {
+            expected_type: "Array".to_owned(),
+            received: value.clone(),
+        })?;
+
+    properties
+        .iter()
+        .map(|property| {
+            property
+                .as_str()
+                .map(str::to_owned)
+                .ok_or(MpvError::ValueContainsUnexpectedType {
+                    expected_type: "String".to_owned(),
+                    received: property.clone(),
+                })
+        })
+        .collect()
+}
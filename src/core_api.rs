@@ -1,17 +1,25 @@
 //! The core API for interacting with [`Mpv`].
 
 use futures::StreamExt;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::{collections::HashMap, fmt};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, atomic::AtomicU64},
+    time::{Duration, Instant},
+};
 use tokio::{
     net::UnixStream,
     sync::{broadcast, mpsc, oneshot},
 };
 
 use crate::{
-    Event, MpvError,
-    ipc::{MpvIpc, MpvIpcCommand, MpvIpcEvent, MpvIpcResponse},
+    Event, EventLogMessageLevel, MpvBuilder, MpvError, Property,
+    capabilities::MpvCapabilities,
+    ipc::{
+        MpvIpcCommand, MpvIpcEvent, MpvIpcEventReplay, MpvIpcObservedProperties, MpvIpcResponse,
+    },
     message_parser::TypeHandler,
 };
 
@@ -27,18 +35,104 @@ use crate::{
 /// the upstream list of commands.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MpvCommand {
+    /// Add `value` to a number property, e.g. `-5.0` to lower the `volume` by 5.
+    ///
+    /// Unlike reading the property and setting a new value, this is a single command,
+    /// so it does not race against other clients changing the property in the meantime.
+    /// mpv clamps the result to the range the property allows.
+    Add { property: String, value: f64 },
+
+    /// Apply a profile from mpv's config, or restore the options it changed.
+    /// See [`ProfileMode`].
+    ApplyProfile { name: String, mode: ProfileMode },
+
+    /// Cycle through the values of a property, in the given direction.
+    /// For boolean properties like `pause`, this toggles the value.
+    Cycle {
+        property: String,
+        direction: CycleDirection,
+    },
+
+    /// Cycle through a given list of values for a property.
+    /// If `reverse` is set, the values are cycled through backwards.
+    CycleValues {
+        property: String,
+        values: Vec<String>,
+        reverse: bool,
+    },
+
+    /// Define a named input section, replacing a section with the same name.
+    ///
+    /// `contents` uses the same syntax as `input.conf`: one binding per line, made of a key
+    /// name and the command it runs, e.g. `"SPACE cycle pause\nq quit"`.
+    /// The section has no effect until it is enabled with [`MpvCommand::EnableSection`].
+    DefineSection {
+        name: String,
+        contents: String,
+        flags: DefineSectionFlags,
+    },
+
+    /// Discard all decoded and demuxed data, and resume playing from the current position.
+    ///
+    /// This is useful when reconnecting a live stream, to avoid showing stale frames.
+    DropBuffers,
+
+    /// Enable an input section defined with [`MpvCommand::DefineSection`],
+    /// putting it on top of the currently enabled sections.
+    EnableSection {
+        name: String,
+        flags: EnableSectionFlags,
+    },
+
+    /// Step back one frame, and pause.
+    ///
+    /// This is much slower than [`MpvCommand::FrameStep`], since mpv has to seek back
+    /// and decode from the previous keyframe.
+    FrameBackStep,
+
+    /// Step forward one frame, and pause.
+    FrameStep,
+
     /// Load the given file or URL and play it.
     LoadFile {
         file: String,
         option: PlaylistAddOptions,
     },
 
+    /// Load the given file or URL and play it, with a set of per-file options.
+    ///
+    /// The options are applied only while this file is playing,
+    /// for example `start=30` to start playback 30 seconds in.
+    ///
+    /// See [`FileOptions`] for building the options.
+    ///
+    /// This requires mpv `v0.38.0` or newer, as it uses the `index` argument of `loadfile`.
+    /// To keep the command well-formed, option values containing `,`, `=` or `%` are quoted.
+    LoadFileWithOptions {
+        file: String,
+        option: PlaylistAddOptions,
+        options: HashMap<String, String>,
+    },
+
     /// Load the given playlist file or URL.
     LoadList {
         file: String,
         option: PlaylistAddOptions,
     },
 
+    /// Load a Lua or JavaScript script into mpv, as if it was passed with `--script`.
+    ///
+    /// Scripts run inside the mpv process without any sandbox, with the same permissions as mpv
+    /// itself. They can read and write files, run programs and send commands to mpv, so
+    /// only load scripts from trusted locations, and never paths received from untrusted clients.
+    /// The path is resolved by mpv, relative to its working directory.
+    LoadScript { path: String },
+
+    /// Multiply a number property by `value`, e.g. `2.0` to double the `speed`.
+    ///
+    /// mpv clamps the result to the range the property allows.
+    Multiply { property: String, value: f64 },
+
     /// Clear the playlist, except for the currently playing file.
     PlaylistClear,
 
@@ -48,16 +142,83 @@ pub enum MpvCommand {
     /// the entry will have after moving.)
     PlaylistMove { from: usize, to: usize },
 
+    /// Add an OSD overlay, drawing a raw image on top of the video.
+    ///
+    /// The image is read from `file`, starting at byte `offset`. `file` is either a path
+    /// to a file (typically a memory mapped file in `/dev/shm`), or `&<fd>` to read from
+    /// an already open file descriptor in the mpv process. The file must contain the
+    /// image as `w` x `h` pixels with `stride` bytes per row. The only `fmt` supported
+    /// by mpv is `"bgra"`: 4 bytes per pixel, with premultiplied alpha.
+    ///
+    /// mpv reads the file only when the command is run, so the overlay must be re-added
+    /// with the same `id` to show updated contents. `id` must be in the range `0..=63`.
+    ///
+    /// The arguments of this command have changed between mpv releases. Before running it,
+    /// the command is checked with [`Mpv::supports_command`], returning
+    /// [`MpvError::Unsupported`] if it is not available.
+    OverlayAdd {
+        id: usize,
+        x: i64,
+        y: i64,
+        file: String,
+        offset: usize,
+        fmt: String,
+        w: usize,
+        h: usize,
+        stride: usize,
+    },
+
+    /// Add or update a text overlay on the OSD, or remove it with [`OsdOverlayFormat::None`].
+    ///
+    /// With [`OsdOverlayFormat::AssEvents`], `data` contains ASS event lines, separated by
+    /// newlines, e.g. `{\pos(100,50)}Hello`. Only the text part of the `Dialogue:` lines
+    /// is given, including override tags. The ASS coordinates are relative to a virtual
+    /// canvas of `res_x` x `res_y`, with `(0, 0)` in the top left corner, which is scaled
+    /// to the OSD size. Setting `res_y` to `0` uses mpv's default of `720`, and setting
+    /// `res_x` to `0` derives the width from `res_y` and the aspect ratio of the window.
+    ///
+    /// Overlays with a higher `z` are drawn on top of ones with a lower `z`. Overlays are
+    /// only visible to the client that added them, and are removed when it disconnects.
+    ///
+    /// This uses the object form of `osd-overlay`, which has changed between mpv releases.
+    /// Before running it, the command is checked with [`Mpv::supports_command`], returning
+    /// [`MpvError::Unsupported`] if it is not available.
+    OsdOverlay {
+        id: u64,
+        format: OsdOverlayFormat,
+        data: String,
+        res_x: u32,
+        res_y: u32,
+        z: i64,
+    },
+
+    /// Remove an overlay added with [`MpvCommand::OverlayAdd`].
+    ///
+    /// Like [`MpvCommand::OverlayAdd`], this is checked with [`Mpv::supports_command`] first.
+    OverlayRemove { id: usize },
+
     /// Observe a property. This will start triggering [`Event::PropertyChange`] events
     /// in the event stream whenever the specific property changes.
     /// You can use [`Mpv::get_event_stream`] to get the stream.
     Observe { id: u64, property: String },
 
+    /// Observe a property, but only to be notified that it changed, not of its new value.
+    /// The [`Event::PropertyChange`] events for it always have `data: None`,
+    /// meaning "it changed, go re-read it if you care".
+    ///
+    /// Note that mpv's JSON IPC has no way of observing without values, so mpv still
+    /// sends them. They are only removed before the events reach the event stream.
+    /// This saves parsing the values, e.g. for large properties like `playlist`,
+    /// but not serializing and sending them.
+    ObserveNone { id: u64, property: String },
+
     /// Skip to the next entry in the playlist.
-    PlaylistNext,
+    /// See [`PlaylistJumpMode`] for what happens at the end of the playlist.
+    PlaylistNext(PlaylistJumpMode),
 
     /// Skip to the previous entry in the playlist.
-    PlaylistPrev,
+    /// See [`PlaylistJumpMode`] for what happens at the start of the playlist.
+    PlaylistPrev(PlaylistJumpMode),
 
     /// Remove an entry from the playlist by its position in the playlist.
     PlaylistRemove(usize),
@@ -68,6 +229,28 @@ pub enum MpvCommand {
     /// Exit the player
     Quit,
 
+    /// Start sending [`Event::LogMessage`] events for log messages at or above the given level,
+    /// or stop sending them if the level is `None`.
+    ///
+    /// This only affects the current connection. See also [`Mpv::log_stream`].
+    RequestLogMessages(Option<EventLogMessageLevel>),
+
+    /// Take a screenshot, and save it in mpv's screenshot directory.
+    ///
+    /// The directory and file name are chosen by mpv's `screenshot-directory` and
+    /// `screenshot-template` options. Use [`MpvCommand::ScreenshotToFile`] to choose the file.
+    Screenshot { flag: ScreenshotFlag },
+
+    /// Take a screenshot, and save it to `filename`.
+    ///
+    /// The image format is derived from the file extension, e.g. `.png` or `.jpg`.
+    /// A relative `filename` is resolved against mpv's working directory, not this process'.
+    /// An existing file is overwritten.
+    ScreenshotToFile {
+        filename: String,
+        flag: ScreenshotFlag,
+    },
+
     /// Send a message to all clients, and pass it the following list of arguments.
     /// What this message means, how many arguments it takes, and what the arguments
     /// mean is fully up to the receiver and the sender.
@@ -77,7 +260,19 @@ pub enum MpvCommand {
     ScriptMessageTo { target: String, args: Vec<String> },
 
     /// Change the playback position.
-    Seek { seconds: f64, option: SeekOptions },
+    ///
+    /// Use [`SeekPrecision::Default`] to let mpv decide between keyframe and exact seeking.
+    Seek {
+        seconds: f64,
+        option: SeekMode,
+        precision: SeekPrecision,
+    },
+
+    /// Seek to the start of a nearby subtitle line.
+    ///
+    /// A positive `skip` seeks forward by that many lines, and a negative `skip` seeks back,
+    /// e.g. `-1` to jump to the start of the previous line.
+    SubSeek { skip: i64 },
 
     /// Stop the current playback, and clear the playlist.
     /// This esentially resets the entire player state without exiting mpv.
@@ -88,6 +283,96 @@ pub enum MpvCommand {
     Unobserve(u64),
 }
 
+impl MpvCommand {
+    /// The names of the mpv input commands that [`MpvCommand`] can run, e.g. `"loadfile"`.
+    ///
+    /// Any other command has to be run with [`Mpv::run_command_raw`]. The names match
+    /// the ones in mpv's `command-list` property, which can be checked with
+    /// [`Mpv::supports_command`]. [`MpvCommand::Observe`], [`MpvCommand::ObserveNone`] and
+    /// [`MpvCommand::Unobserve`] are not included, as they use the IPC specific
+    /// `observe_property` and `unobserve_property` commands, which are not input commands.
+    pub fn all_command_names() -> &'static [&'static str] {
+        &[
+            "add",
+            "apply-profile",
+            "cycle",
+            "cycle-values",
+            "define-section",
+            "drop-buffers",
+            "enable-section",
+            "frame-back-step",
+            "frame-step",
+            "loadfile",
+            "loadlist",
+            "load-script",
+            "multiply",
+            "osd-overlay",
+            "overlay-add",
+            "overlay-remove",
+            "playlist-clear",
+            "playlist-move",
+            "playlist-next",
+            "playlist-prev",
+            "playlist-remove",
+            "playlist-shuffle",
+            "quit",
+            "request_log_messages",
+            "screenshot",
+            "screenshot-to-file",
+            "script-message",
+            "script-message-to",
+            "seek",
+            "stop",
+            "sub-seek",
+        ]
+    }
+
+    /// The name of the mpv input command this runs, or `None` for the IPC specific commands.
+    ///
+    /// This has to be kept in sync with [`Mpv::run_command`], and is checked against
+    /// [`MpvCommand::all_command_names`] in the tests.
+    #[cfg(test)]
+    fn command_name(&self) -> Option<&'static str> {
+        match self {
+            MpvCommand::Add { .. } => Some("add"),
+            MpvCommand::ApplyProfile { .. } => Some("apply-profile"),
+            MpvCommand::Cycle { .. } => Some("cycle"),
+            MpvCommand::CycleValues { .. } => Some("cycle-values"),
+            MpvCommand::DefineSection { .. } => Some("define-section"),
+            MpvCommand::DropBuffers => Some("drop-buffers"),
+            MpvCommand::EnableSection { .. } => Some("enable-section"),
+            MpvCommand::FrameBackStep => Some("frame-back-step"),
+            MpvCommand::FrameStep => Some("frame-step"),
+            MpvCommand::LoadFile { .. } => Some("loadfile"),
+            MpvCommand::LoadFileWithOptions { .. } => Some("loadfile"),
+            MpvCommand::LoadList { .. } => Some("loadlist"),
+            MpvCommand::LoadScript { .. } => Some("load-script"),
+            MpvCommand::Multiply { .. } => Some("multiply"),
+            MpvCommand::PlaylistClear => Some("playlist-clear"),
+            MpvCommand::PlaylistMove { .. } => Some("playlist-move"),
+            MpvCommand::OsdOverlay { .. } => Some("osd-overlay"),
+            MpvCommand::OverlayAdd { .. } => Some("overlay-add"),
+            MpvCommand::OverlayRemove { .. } => Some("overlay-remove"),
+            MpvCommand::Observe { .. } => None,
+            MpvCommand::ObserveNone { .. } => None,
+            MpvCommand::PlaylistNext(_) => Some("playlist-next"),
+            MpvCommand::PlaylistPrev(_) => Some("playlist-prev"),
+            MpvCommand::PlaylistRemove(_) => Some("playlist-remove"),
+            MpvCommand::PlaylistShuffle => Some("playlist-shuffle"),
+            MpvCommand::Quit => Some("quit"),
+            MpvCommand::RequestLogMessages(_) => Some("request_log_messages"),
+            MpvCommand::Screenshot { .. } => Some("screenshot"),
+            MpvCommand::ScreenshotToFile { .. } => Some("screenshot-to-file"),
+            MpvCommand::ScriptMessage(_) => Some("script-message"),
+            MpvCommand::ScriptMessageTo { .. } => Some("script-message-to"),
+            MpvCommand::Seek { .. } => Some("seek"),
+            MpvCommand::SubSeek { .. } => Some("sub-seek"),
+            MpvCommand::Stop => Some("stop"),
+            MpvCommand::Unobserve(_) => None,
+        }
+    }
+}
+
 /// Helper trait to keep track of the string literals that mpv expects.
 pub(crate) trait IntoRawCommandPart {
     fn into_raw_command_part(self) -> String;
@@ -112,6 +397,21 @@ pub enum MpvDataType {
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Playlist(pub Vec<PlaylistEntry>);
 
+impl Playlist {
+    /// Find the first entry with the given filename.
+    ///
+    /// The filename is compared exactly as mpv reports it, i.e. as it was added to the playlist.
+    pub fn find_by_filename(&self, filename: &str) -> Option<&PlaylistEntry> {
+        self.0.iter().find(|entry| entry.filename == filename)
+    }
+
+    /// Find the position of the first entry with the given filename,
+    /// e.g. for setting `playlist-pos`. See [`Playlist::find_by_filename`].
+    pub fn position_of(&self, filename: &str) -> Option<usize> {
+        self.0.iter().position(|entry| entry.filename == filename)
+    }
+}
+
 /// A single entry in the mpv playlist.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PlaylistEntry {
@@ -121,7 +421,103 @@ pub struct PlaylistEntry {
     pub current: bool,
 }
 
-/// Options for [`MpvCommand::LoadFile`] and [`MpvCommand::LoadList`].
+/// The type of a [`Track`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrackType {
+    Video,
+    Audio,
+    Sub,
+
+    /// A catch-all enum variant in case `mpvipc-async` has not implemented the
+    /// returned track type yet.
+    Unimplemented(String),
+}
+
+/// A single entry in the mpv `track-list` property.
+///
+/// See <https://mpv.io/manual/master/#command-interface-track-list> for
+/// the meaning of each field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Track {
+    /// The id of the track, which is unique among tracks of the same type.
+    /// This is the value used for selecting it with properties like `aid`, `vid` and `sid`.
+    pub id: usize,
+    pub track_type: TrackType,
+    pub title: Option<String>,
+    pub lang: Option<String>,
+    pub codec: Option<String>,
+    pub selected: bool,
+    pub default: bool,
+    pub forced: bool,
+    pub external: bool,
+    pub external_filename: Option<String>,
+    /// Whether the track is a video track consisting of a single image.
+    pub image: bool,
+    /// Whether the track is a cover art image embedded in an audio file.
+    pub albumart: bool,
+}
+
+/// The size of the OSD, from the mpv `osd-dimensions` property.
+///
+/// When a video is shown, this is the size of the window, and the margins
+/// are the borders between the edges of the window and the video.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OsdDimensions {
+    pub width: usize,
+    pub height: usize,
+    /// The pixel aspect ratio of the OSD, usually `1.0`.
+    pub par: f64,
+    /// The display aspect ratio of the OSD.
+    pub aspect: f64,
+    pub margin_top: usize,
+    pub margin_bottom: usize,
+    pub margin_left: usize,
+    pub margin_right: usize,
+}
+
+/// The state of the demuxer cache, from the mpv `demuxer-cache-state` property.
+///
+/// This only contains the parts of the property that are useful for showing what
+/// has been buffered. All times are in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DemuxerCacheState {
+    /// The time ranges that can be seeked to without reading from the source again,
+    /// as `(start, end)` pairs.
+    pub seekable_ranges: Vec<(f64, f64)>,
+    /// The end of the cached data, after the current position.
+    pub cache_end: Option<f64>,
+    /// How much is cached after the current position.
+    pub cache_duration: Option<f64>,
+}
+
+/// Render timings of the video output, from the mpv `vo-passes` property.
+///
+/// mpv splits rendering into passes, like scaling or applying shaders, and times each of them.
+/// Only the passes of the GPU based video outputs are timed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RenderStats {
+    /// The passes run to render a new frame.
+    pub fresh: Vec<RenderPass>,
+    /// The passes run to redraw a frame that was already rendered, e.g. when the OSD changes.
+    pub redraw: Vec<RenderPass>,
+}
+
+/// The timing of a single render pass, see [`RenderStats`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderPass {
+    /// A human readable description of the pass, like `"scaling (luma)"`.
+    pub desc: String,
+    /// How long the pass took the last time it ran.
+    pub last: Duration,
+    /// The average time of the pass, over the recent samples.
+    pub avg: Duration,
+    /// The longest time of the pass, over the recent samples.
+    pub peak: Duration,
+    /// How many samples the average and peak are computed from.
+    pub count: usize,
+}
+
+/// Options for [`MpvCommand::LoadFile`], [`MpvCommand::LoadFileWithOptions`] and [`MpvCommand::LoadList`].
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum PlaylistAddOptions {
     Replace,
@@ -137,8 +533,97 @@ impl IntoRawCommandPart for PlaylistAddOptions {
     }
 }
 
-/// Options for [`MpvCommand::Seek`].
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// Per-file options for [`MpvCommand::LoadFileWithOptions`], built one option at a time.
+///
+/// The options only apply while the file is playing. Options without a method of their own
+/// can be given with [`FileOptions::set`], using the names of mpv's command line options.
+///
+/// # Example
+/// ```
+/// use mpvipc_async::FileOptions;
+///
+/// let options = FileOptions::new().start(30.0).aid(2).set("volume", "50");
+/// assert_eq!(options.options()["start"], "30");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileOptions {
+    options: HashMap<String, String>,
+}
+
+impl FileOptions {
+    /// Create an empty set of options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start playback at `seconds` into the file.
+    pub fn start(self, seconds: f64) -> Self {
+        self.set("start", seconds.to_string())
+    }
+
+    /// Select the audio track with the given id, from [`Track::id`](crate::Track::id).
+    pub fn aid(self, id: usize) -> Self {
+        self.set("aid", id.to_string())
+    }
+
+    /// Select the subtitle track with the given id, from [`Track::id`](crate::Track::id).
+    pub fn sid(self, id: usize) -> Self {
+        self.set("sid", id.to_string())
+    }
+
+    /// Select the video track with the given id, from [`Track::id`](crate::Track::id).
+    pub fn vid(self, id: usize) -> Self {
+        self.set("vid", id.to_string())
+    }
+
+    /// Show `title` instead of the title mpv derives from the file, by setting `force-media-title`.
+    pub fn title(self, title: impl Into<String>) -> Self {
+        self.set("force-media-title", title)
+    }
+
+    /// Set any option by name, e.g. `set("sid", "no")` to disable subtitles.
+    ///
+    /// This replaces an earlier value for the same option.
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+
+    /// The options, as they are passed to [`MpvCommand::LoadFileWithOptions`].
+    pub fn options(&self) -> &HashMap<String, String> {
+        &self.options
+    }
+}
+
+impl From<FileOptions> for HashMap<String, String> {
+    fn from(options: FileOptions) -> Self {
+        options.options
+    }
+}
+
+/// Direction for [`MpvCommand::Cycle`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CycleDirection {
+    /// Cycle to the next value. This is what mpv does when no direction is given.
+    #[default]
+    Up,
+    /// Cycle to the previous value.
+    Down,
+}
+
+impl IntoRawCommandPart for CycleDirection {
+    fn into_raw_command_part(self) -> String {
+        match self {
+            CycleDirection::Up => "up".to_string(),
+            CycleDirection::Down => "down".to_string(),
+        }
+    }
+}
+
+/// Options for [`MpvCommand::Seek`], as the flat combinations of a [`SeekReference`] and a [`SeekUnit`].
+///
+/// This converts into a [`SeekMode`], which is what the seek functions take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SeekOptions {
     Relative,
     Absolute,
@@ -147,22 +632,262 @@ pub enum SeekOptions {
 }
 
 impl IntoRawCommandPart for SeekOptions {
+    fn into_raw_command_part(self) -> String {
+        SeekMode::from(self).into_raw_command_part()
+    }
+}
+
+/// What the target of a seek is relative to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeekReference {
+    /// Seek relative to the current playback position.
+    #[default]
+    Relative,
+    /// Seek relative to the start of the file.
+    Absolute,
+}
+
+/// The unit of the target of a seek.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeekUnit {
+    /// The target is in seconds.
+    #[default]
+    Seconds,
+    /// The target is in percent of the file duration.
+    Percent,
+}
+
+/// How the target of [`MpvCommand::Seek`] is interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeekMode {
+    pub reference: SeekReference,
+    pub unit: SeekUnit,
+}
+
+impl SeekMode {
+    pub fn new(reference: SeekReference, unit: SeekUnit) -> Self {
+        SeekMode { reference, unit }
+    }
+}
+
+impl From<SeekOptions> for SeekMode {
+    fn from(option: SeekOptions) -> Self {
+        match option {
+            SeekOptions::Relative => SeekMode::new(SeekReference::Relative, SeekUnit::Seconds),
+            SeekOptions::Absolute => SeekMode::new(SeekReference::Absolute, SeekUnit::Seconds),
+            SeekOptions::RelativePercent => {
+                SeekMode::new(SeekReference::Relative, SeekUnit::Percent)
+            }
+            SeekOptions::AbsolutePercent => {
+                SeekMode::new(SeekReference::Absolute, SeekUnit::Percent)
+            }
+        }
+    }
+}
+
+impl IntoRawCommandPart for SeekMode {
+    fn into_raw_command_part(self) -> String {
+        let reference = match self.reference {
+            SeekReference::Relative => "relative",
+            SeekReference::Absolute => "absolute",
+        };
+        match self.unit {
+            SeekUnit::Seconds => reference.to_string(),
+            SeekUnit::Percent => format!("{}-percent", reference),
+        }
+    }
+}
+
+/// Precision of [`MpvCommand::Seek`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeekPrecision {
+    /// Use mpv's default, which depends on the seek type and the `hr-seek` option.
+    #[default]
+    Default,
+    /// Seek to the nearest keyframe. This is fast, but not frame accurate.
+    Keyframes,
+    /// Seek to the exact position. This is slower, but frame accurate.
+    Exact,
+}
+
+impl IntoRawCommandPart for SeekPrecision {
+    fn into_raw_command_part(self) -> String {
+        match self {
+            SeekPrecision::Default => "".to_string(),
+            SeekPrecision::Keyframes => "keyframes".to_string(),
+            SeekPrecision::Exact => "exact".to_string(),
+        }
+    }
+}
+
+/// Combine the flags of a `seek` command into a single argument, e.g. `absolute+exact`.
+pub(crate) fn seek_flags(option: SeekMode, precision: SeekPrecision) -> String {
+    match precision.into_raw_command_part() {
+        precision if precision.is_empty() => option.into_raw_command_part(),
+        precision => format!("{}+{}", option.into_raw_command_part(), precision),
+    }
+}
+
+/// Whether [`MpvCommand::ApplyProfile`] applies a profile, or undoes it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProfileMode {
+    /// Set the options of the profile.
+    #[default]
+    Apply,
+    /// Set the options of the profile back to the values they had before it was applied.
+    /// This only works for profiles with `profile-restore` set in the config.
+    Restore,
+}
+
+impl IntoRawCommandPart for ProfileMode {
+    fn into_raw_command_part(self) -> String {
+        match self {
+            ProfileMode::Apply => "apply".to_string(),
+            ProfileMode::Restore => "restore".to_string(),
+        }
+    }
+}
+
+/// How the bindings of [`MpvCommand::DefineSection`] interact with the user's own bindings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DefineSectionFlags {
+    /// Only use a binding if the user has not bound the key in `input.conf`.
+    #[default]
+    Default,
+    /// Always use the bindings of the section, even over the user's bindings.
+    Force,
+}
+
+impl IntoRawCommandPart for DefineSectionFlags {
+    fn into_raw_command_part(self) -> String {
+        match self {
+            DefineSectionFlags::Default => "default".to_string(),
+            DefineSectionFlags::Force => "force".to_string(),
+        }
+    }
+}
+
+/// How [`MpvCommand::EnableSection`] interacts with the other enabled sections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnableSectionFlags {
+    /// Keys that are not bound in the section fall through to the sections below it.
+    #[default]
+    Default,
+    /// Keys that are not bound in the section are ignored,
+    /// until the section is disabled again.
+    Exclusive,
+}
+
+impl IntoRawCommandPart for EnableSectionFlags {
+    fn into_raw_command_part(self) -> String {
+        match self {
+            EnableSectionFlags::Default => "default".to_string(),
+            EnableSectionFlags::Exclusive => "exclusive".to_string(),
+        }
+    }
+}
+
+/// The format of the `data` of a [`MpvCommand::OsdOverlay`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OsdOverlayFormat {
+    /// ASS event lines, separated by newlines.
+    #[default]
+    AssEvents,
+    /// No overlay. This removes the overlay with the given id.
+    None,
+}
+
+impl IntoRawCommandPart for OsdOverlayFormat {
+    fn into_raw_command_part(self) -> String {
+        match self {
+            OsdOverlayFormat::AssEvents => "ass-events".to_string(),
+            OsdOverlayFormat::None => "none".to_string(),
+        }
+    }
+}
+
+/// What [`MpvCommand::Screenshot`] and [`MpvCommand::ScreenshotToFile`] capture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScreenshotFlag {
+    /// The video with subtitles, at the video resolution. This is mpv's default.
+    #[default]
+    Subtitles,
+    /// Only the video, without subtitles or OSD, at the video resolution.
+    Video,
+    /// The window contents, as they are shown, including subtitles and OSD.
+    Window,
+}
+
+impl IntoRawCommandPart for ScreenshotFlag {
+    fn into_raw_command_part(self) -> String {
+        match self {
+            ScreenshotFlag::Subtitles => "subtitles".to_string(),
+            ScreenshotFlag::Video => "video".to_string(),
+            ScreenshotFlag::Window => "window".to_string(),
+        }
+    }
+}
+
+/// What [`MpvCommand::PlaylistNext`] and [`MpvCommand::PlaylistPrev`] do
+/// when there is no entry to skip to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlaylistJumpMode {
+    /// Do nothing at the ends of the playlist, and keep playing the current entry.
+    /// This is mpv's default.
+    #[default]
+    Weak,
+    /// Stop playing at the ends of the playlist. mpv quits unless it runs with `--idle`.
+    Force,
+}
+
+impl IntoRawCommandPart for PlaylistJumpMode {
     fn into_raw_command_part(self) -> String {
         match self {
-            SeekOptions::Relative => "relative".to_string(),
-            SeekOptions::Absolute => "absolute".to_string(),
-            SeekOptions::RelativePercent => "relative-percent".to_string(),
-            SeekOptions::AbsolutePercent => "absolute-percent".to_string(),
+            PlaylistJumpMode::Weak => "weak".to_string(),
+            PlaylistJumpMode::Force => "force".to_string(),
         }
     }
 }
 
+/// Format per-file options as the comma separated `key=value` list that `loadfile` expects.
+///
+/// The options are sorted by key to keep the output stable, and values that would
+/// otherwise break the list are quoted with mpv's `%<length>%<value>` syntax.
+fn format_file_options(options: &HashMap<String, String>) -> String {
+    let mut options: Vec<_> = options.iter().collect();
+    options.sort();
+    options
+        .into_iter()
+        .map(|(key, value)| {
+            if value.contains([',', '=', '%']) {
+                format!("{}=%{}%{}", key, value.len(), value)
+            } else {
+                format!("{}={}", key, value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Await `future`, or return [`MpvError::Timeout`] if it takes longer than `timeout`.
+async fn with_timeout<T>(
+    timeout: Duration,
+    future: impl Future<Output = Result<T, MpvError>>,
+) -> Result<T, MpvError> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| MpvError::Timeout(format!("mpv did not reply within {:?}", timeout)))?
+}
+
 /// A trait for specifying how to extract and parse a value returned through [`Mpv::get_property`].
 pub trait GetPropertyTypeHandler: Sized {
     // TODO: fix this
     #[allow(async_fn_in_trait)]
     async fn get_property_generic(instance: &Mpv, property: &str)
     -> Result<Option<Self>, MpvError>;
+
+    /// Parse a property value that was received some other way, e.g. in an [`Event::PropertyChange`].
+    fn parse_property_value(value: Value) -> Result<Self, MpvError>;
 }
 
 impl<T> GetPropertyTypeHandler for T
@@ -178,6 +903,10 @@ where
                 None => Ok(None),
             })
     }
+
+    fn parse_property_value(value: Value) -> Result<T, MpvError> {
+        T::get_value(value)
+    }
 }
 
 /// A trait for specifying how to serialize and set a value through [`Mpv::set_property`].
@@ -207,11 +936,11 @@ where
                 res_tx,
             ))
             .await
-            .map_err(|err| MpvError::InternalConnectionError(err.to_string()))?;
+            .map_err(|_| MpvError::Disconnected)?;
 
         match res_rx.await {
             Ok(MpvIpcResponse(response)) => response.map(|_| ()),
-            Err(err) => Err(MpvError::InternalConnectionError(err.to_string())),
+            Err(_) => Err(MpvError::Disconnected),
         }
     }
 }
@@ -226,8 +955,37 @@ where
 /// It only contains a message passing channel to the tokio task that handles the IPC communication with mpv.
 #[derive(Clone)]
 pub struct Mpv {
-    command_sender: mpsc::Sender<(MpvIpcCommand, oneshot::Sender<MpvIpcResponse>)>,
-    broadcast_channel: broadcast::Sender<MpvIpcEvent>,
+    pub(crate) command_sender: mpsc::Sender<(MpvIpcCommand, oneshot::Sender<MpvIpcResponse>)>,
+    pub(crate) broadcast_channel: broadcast::Sender<MpvIpcEvent>,
+    pub(crate) event_replay: Option<Arc<Mutex<MpvIpcEventReplay>>>,
+    pub(crate) lossless_event_channels: Arc<Mutex<Vec<mpsc::UnboundedSender<Event>>>>,
+    pub(crate) observed_properties: Arc<Mutex<MpvIpcObservedProperties>>,
+    pub(crate) capabilities: Arc<MpvCapabilities>,
+    pub(crate) next_observe_id: Arc<AtomicU64>,
+    pub(crate) last_heartbeat: Arc<Mutex<Option<Instant>>>,
+    pub(crate) last_event: Arc<Mutex<Option<Instant>>>,
+    pub(crate) socket_path: Option<String>,
+    pub(crate) default_osd: bool,
+    pub(crate) command_timeout: Option<Duration>,
+    pub(crate) max_value_depth: usize,
+}
+
+/// Information about a connection and the mpv instance behind it, see [`Mpv::diagnostics`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostics {
+    /// See [`Mpv::is_connected`].
+    pub is_connected: bool,
+    /// The path of the socket, or `None` if the connection was made from an existing socket.
+    /// Abstract sockets are shown as `@name`.
+    pub socket_path: Option<String>,
+    /// The `mpv-version` property, or `None` if it could not be read.
+    pub mpv_version: Option<String>,
+    /// The `ffmpeg-version` property, or `None` if it could not be read.
+    pub ffmpeg_version: Option<String>,
+    /// How many properties are observed on this connection.
+    pub observed_properties: usize,
+    /// How long ago the last event was received from mpv, or `None` if there was none yet.
+    pub since_last_event: Option<Duration>,
 }
 
 // TODO: Can we somehow provide a more useful Debug implementation?
@@ -240,15 +998,45 @@ impl fmt::Debug for Mpv {
 impl Mpv {
     /// Connect to a unix socket, hosted by mpv, at the given path.
     /// This is the inteded way of creating a new [`Mpv`] instance.
+    ///
+    /// See [`Mpv::builder`] if you need to configure the connection.
     pub async fn connect(socket_path: &str) -> Result<Mpv, MpvError> {
-        log::debug!("Connecting to mpv socket at {}", socket_path);
+        MpvBuilder::new().connect(socket_path).await
+    }
+
+    /// Wait for the unix socket at the given path to appear, and then connect to it.
+    ///
+    /// This is the standard way of connecting to an mpv instance that was just spawned
+    /// with `--input-ipc-server`, since mpv needs a moment to create the socket.
+    /// Returns [`MpvError::Timeout`] if the socket does not appear within `timeout`.
+    pub async fn connect_when_available(
+        socket_path: &str,
+        timeout: Duration,
+    ) -> Result<Mpv, MpvError> {
+        MpvBuilder::new()
+            .connect_when_available(socket_path, timeout)
+            .await
+    }
 
-        let socket = match UnixStream::connect(socket_path).await {
-            Ok(stream) => Ok(stream),
-            Err(err) => Err(MpvError::MpvSocketConnectionError(err.to_string())),
-        }?;
+    /// Connect to a unix socket at the given path, retrying while mpv is still starting up.
+    ///
+    /// See [`MpvBuilder::connect_with_retry`] for details.
+    pub async fn connect_with_retry(
+        socket_path: &str,
+        attempts: usize,
+        delay: Duration,
+    ) -> Result<Mpv, MpvError> {
+        MpvBuilder::new()
+            .connect_with_retry(socket_path, attempts, delay)
+            .await
+    }
 
-        Self::connect_socket(socket).await
+    /// Connect to a unix socket in the abstract namespace, hosted by mpv, with the given name.
+    ///
+    /// This is only available on Linux. See [`MpvBuilder::connect_abstract`] for details.
+    #[cfg(target_os = "linux")]
+    pub async fn connect_abstract(name: &str) -> Result<Mpv, MpvError> {
+        MpvBuilder::new().connect_abstract(name).await
     }
 
     /// Connect to an existing [`UnixStream`].
@@ -256,17 +1044,12 @@ impl Mpv {
     ///
     /// Internally, this is used for testing purposes.
     pub async fn connect_socket(socket: UnixStream) -> Result<Mpv, MpvError> {
-        let (com_tx, com_rx) = mpsc::channel(100);
-        let (ev_tx, _) = broadcast::channel(100);
-        let ipc = MpvIpc::new(socket, com_rx, ev_tx.clone());
-
-        log::debug!("Starting IPC handler");
-        tokio::spawn(ipc.run());
+        MpvBuilder::new().connect_socket(socket).await
+    }
 
-        Ok(Mpv {
-            command_sender: com_tx,
-            broadcast_channel: ev_tx,
-        })
+    /// Create a [`MpvBuilder`] for configuring a new connection.
+    pub fn builder() -> MpvBuilder {
+        MpvBuilder::new()
     }
 
     /// Disconnect from the mpv socket.
@@ -279,52 +1062,217 @@ impl Mpv {
         self.command_sender
             .send((MpvIpcCommand::Exit, res_tx))
             .await
-            .map_err(|err| MpvError::InternalConnectionError(err.to_string()))?;
+            .map_err(|_| MpvError::Disconnected)?;
 
         match res_rx.await {
             Ok(MpvIpcResponse(response)) => response.map(|_| ()),
-            Err(err) => Err(MpvError::InternalConnectionError(err.to_string())),
+            Err(_) => Err(MpvError::Disconnected),
         }
     }
 
-    /// Create a new stream, providing [`Event`]s from mpv.
+    /// Check whether the connection to mpv is still open.
     ///
-    /// This is intended to be used with [`MpvCommand::Observe`] and [`MpvCommand::Unobserve`]
-    /// (or [`MpvExt::observe_property`] and [`MpvExt::unobserve_property`] respectively).
-    pub async fn get_event_stream(&self) -> impl futures::Stream<Item = Result<Event, MpvError>> {
-        tokio_stream::wrappers::BroadcastStream::new(self.broadcast_channel.subscribe()).map(
-            |event| match event {
-                Ok(event) => crate::event_parser::parse_event(event),
-                Err(err) => Err(MpvError::InternalConnectionError(err.to_string())),
-            },
-        )
+    /// This returns `false` after [`Mpv::disconnect`], or after a failed heartbeat
+    /// (see [`MpvBuilder::heartbeat`]). Without heartbeats, a socket that was closed
+    /// by mpv is not detected here, even though commands sent over it will fail.
+    pub fn is_connected(&self) -> bool {
+        !self.command_sender.is_closed()
     }
 
-    /// Run a custom command.
-    /// This should only be used if the desired command is not implemented
-    /// with [`MpvCommand`].
-    pub async fn run_command_raw(
-        &self,
-        command: &str,
-        args: &[&str],
-    ) -> Result<Option<Value>, MpvError> {
-        let command_vec = Vec::from(
-            [command]
-                .iter()
-                .chain(args.iter())
-                .map(|s| s.to_string())
-                .collect::<Vec<String>>()
-                .as_slice(),
-        );
+    /// The time of the last successful heartbeat, or `None` if there has not been one yet.
+    ///
+    /// This is always `None` unless heartbeats are enabled with [`MpvBuilder::heartbeat`].
+    pub fn last_heartbeat(&self) -> Option<Instant> {
+        *self.last_heartbeat.lock().unwrap()
+    }
+
+    /// Collect information about the connection and the mpv instance, e.g. for bug reports.
+    ///
+    /// The versions are only read while connected, and are `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     let diagnostics = mpv.diagnostics().await?;
+    ///     println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn diagnostics(&self) -> Result<Diagnostics, MpvError> {
+        let is_connected = self.is_connected();
+        let (mpv_version, ffmpeg_version) = if is_connected {
+            (
+                self.get_property("mpv-version").await?,
+                self.get_property("ffmpeg-version").await?,
+            )
+        } else {
+            (None, None)
+        };
+
+        Ok(Diagnostics {
+            is_connected,
+            socket_path: self.socket_path.clone(),
+            mpv_version,
+            ffmpeg_version,
+            observed_properties: self.observed_properties.lock().unwrap().observed_count(),
+            since_last_event: self.last_event.lock().unwrap().map(|time| time.elapsed()),
+        })
+    }
+
+    /// Create a new stream, providing [`Event`]s from mpv.
+    ///
+    /// This is intended to be used with [`MpvCommand::Observe`] and [`MpvCommand::Unobserve`]
+    /// (or [`MpvExt::observe_property`] and [`MpvExt::unobserve_property`] respectively).
+    ///
+    /// If the connection was configured with [`MpvBuilder::event_replay_depth`],
+    /// the stream will start with the buffered recent events.
+    pub async fn get_event_stream(
+        &self,
+    ) -> impl futures::Stream<Item = Result<Event, MpvError>> + use<> {
+        let max_depth = self.max_value_depth;
+        self.ipc_event_stream().map(move |event| {
+            event.and_then(|event| crate::event_parser::parse_event(event, max_depth))
+        })
+    }
+
+    /// Same as [`Mpv::get_event_stream`], but every [`Event`] comes with the JSON object mpv sent.
+    ///
+    /// The JSON object includes fields that [`Event`] does not model, which makes it possible
+    /// to log or relay events exactly as they were received, e.g. in a proxy.
+    /// Values of properties observed with [`MpvCommand::ObserveNone`] are already removed.
+    pub async fn get_event_stream_with_raw(
+        &self,
+    ) -> impl futures::Stream<Item = Result<(Event, Value), MpvError>> + use<> {
+        let max_depth = self.max_value_depth;
+        self.ipc_event_stream().map(move |event| {
+            let event = event?;
+            let raw = event.0.clone();
+            crate::event_parser::parse_event(event, max_depth).map(|event| (event, raw))
+        })
+    }
+
+    /// Same as [`Mpv::get_event_stream`], but without the replayed events.
+    ///
+    /// Anything that waits for the outcome of a command has to use this, since a replayed
+    /// event from before the command would otherwise be mistaken for its outcome.
+    pub(crate) fn live_event_stream(
+        &self,
+    ) -> impl futures::Stream<Item = Result<Event, MpvError>> + use<> {
+        let max_depth = self.max_value_depth;
+        tokio_stream::wrappers::BroadcastStream::new(self.broadcast_channel.subscribe())
+            .map(|event| event.map_err(|err| MpvError::InternalConnectionError(err.to_string())))
+            .map(move |event| {
+                event.and_then(|event| crate::event_parser::parse_event(event, max_depth))
+            })
+    }
+
+    /// A stream of unparsed events, starting with the replayed events, if any.
+    fn ipc_event_stream(
+        &self,
+    ) -> impl futures::Stream<Item = Result<MpvIpcEvent, MpvError>> + use<> {
+        let (replayed_events, receiver) = match &self.event_replay {
+            Some(replay) => {
+                let replay = replay.lock().unwrap();
+                (replay.events(), self.broadcast_channel.subscribe())
+            }
+            None => (Vec::new(), self.broadcast_channel.subscribe()),
+        };
+
+        futures::stream::iter(replayed_events.into_iter().map(Ok))
+            .chain(tokio_stream::wrappers::BroadcastStream::new(receiver))
+            .map(|event| event.map_err(|err| MpvError::InternalConnectionError(err.to_string())))
+    }
+
+    /// Create a new channel, providing every [`Event`] from mpv.
+    ///
+    /// Unlike [`Mpv::get_event_stream`], which drops events if the consumer lags too far behind,
+    /// this channel never drops events. Events that can not be parsed are skipped.
+    ///
+    /// The channel is unbounded, so if the receiver is not consumed,
+    /// events will keep accumulating in memory for as long as the connection is alive.
+    /// Only use this for consumers that keep up with the event rate over time,
+    /// like loggers, and drop the receiver when it is no longer needed.
+    pub fn get_event_channel(&self) -> mpsc::UnboundedReceiver<Event> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.lossless_event_channels.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Get the latest value of every observed property.
+    ///
+    /// Values are cached as [`Event::PropertyChange`] events arrive for properties observed with
+    /// [`MpvCommand::Observe`], and removed again when the properties are unobserved.
+    /// The map is empty if nothing is observed, or no values have arrived yet.
+    ///
+    /// This is intended for debugging, e.g. logging all watched state when an error occurs.
+    pub fn dump_observed(&self) -> HashMap<String, Property> {
+        self.observed_properties.lock().unwrap().values()
+    }
+
+    /// Run a custom command.
+    /// This should only be used if the desired command is not implemented
+    /// with [`MpvCommand`].
+    pub async fn run_command_raw(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<Option<Value>, MpvError> {
+        let command_vec = Vec::from(
+            [command]
+                .iter()
+                .chain(args.iter())
+                .map(|s| s.to_string())
+                .collect::<Vec<String>>()
+                .as_slice(),
+        );
         let (res_tx, res_rx) = oneshot::channel();
         self.command_sender
             .send((MpvIpcCommand::Command(command_vec.clone()), res_tx))
             .await
-            .map_err(|err| MpvError::InternalConnectionError(err.to_string()))?;
+            .map_err(|_| MpvError::Disconnected)?;
+
+        match res_rx.await {
+            Ok(MpvIpcResponse(response)) => response,
+            Err(_) => Err(MpvError::Disconnected),
+        }
+    }
+
+    /// Run a command with named arguments, using the object form of mpv's JSON IPC commands.
+    pub(crate) async fn run_named_command(
+        &self,
+        command: &str,
+        args: Map<String, Value>,
+    ) -> Result<Option<Value>, MpvError> {
+        let mut named_command = Map::new();
+        named_command.insert("name".to_owned(), json!(command));
+        named_command.extend(args);
+
+        let (res_tx, res_rx) = oneshot::channel();
+        self.command_sender
+            .send((MpvIpcCommand::NamedCommand(named_command), res_tx))
+            .await
+            .map_err(|_| MpvError::Disconnected)?;
 
         match res_rx.await {
             Ok(MpvIpcResponse(response)) => response,
-            Err(err) => Err(MpvError::InternalConnectionError(err.to_string())),
+            Err(_) => Err(MpvError::Disconnected),
+        }
+    }
+
+    /// Run a custom command like [`Mpv::run_command_raw`], but with the
+    /// [`MpvBuilder::command_timeout`](crate::MpvBuilder::command_timeout) applied.
+    pub(crate) async fn run_command_raw_timed(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<Option<Value>, MpvError> {
+        match self.command_timeout {
+            Some(timeout) => with_timeout(timeout, self.run_command_raw(command, args)).await,
+            None => self.run_command_raw(command, args).await,
         }
     }
 
@@ -348,7 +1296,7 @@ impl Mpv {
     ///
     /// # Example
     /// ```
-    /// use mpvipc_async::{Mpv, MpvError};
+    /// use mpvipc_async::{Mpv, MpvCommand, MpvError, SeekOptions, SeekPrecision};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), MpvError> {
@@ -360,14 +1308,99 @@ impl Mpv {
     ///     //Run command 'seek' which in this case takes two arguments
     ///     mpv.run_command(MpvCommand::Seek {
     ///         seconds: 0f64,
-    ///         option: SeekOptions::Absolute,
+    ///         option: SeekOptions::Absolute.into(),
+    ///         precision: SeekPrecision::Default,
     ///     }).await?;
     ///     Ok(())
     /// }
     /// ```
     pub async fn run_command(&self, command: MpvCommand) -> Result<(), MpvError> {
+        match self.command_timeout {
+            Some(timeout) => self.run_command_timeout(command, timeout).await,
+            None => self.run_command_untimed(command).await,
+        }
+    }
+
+    /// Run a command like [`Mpv::run_command`], but with a different timeout than the one
+    /// set with [`MpvBuilder::command_timeout`](crate::MpvBuilder::command_timeout).
+    ///
+    /// Returns [`MpvError::Timeout`] if mpv does not reply within `timeout`.
+    pub async fn run_command_timeout(
+        &self,
+        command: MpvCommand,
+        timeout: Duration,
+    ) -> Result<(), MpvError> {
+        with_timeout(timeout, self.run_command_untimed(command)).await
+    }
+
+    async fn run_command_untimed(&self, command: MpvCommand) -> Result<(), MpvError> {
         log::trace!("Running command: {:?}", command);
         let result = match command {
+            MpvCommand::Add { property, value } => {
+                self.run_command_raw_ignore_value("add", &[&property, &value.to_string()])
+                    .await
+            }
+            MpvCommand::ApplyProfile { name, mode } => match mode {
+                ProfileMode::Apply => {
+                    self.run_command_raw_ignore_value("apply-profile", &[&name])
+                        .await
+                }
+                ProfileMode::Restore => {
+                    self.run_command_raw_ignore_value(
+                        "apply-profile",
+                        &[&name, mode.into_raw_command_part().as_str()],
+                    )
+                    .await
+                }
+            },
+            MpvCommand::Cycle {
+                property,
+                direction,
+            } => {
+                self.run_command_raw_ignore_value(
+                    "cycle",
+                    &[&property, direction.into_raw_command_part().as_str()],
+                )
+                .await
+            }
+            MpvCommand::CycleValues {
+                property,
+                values,
+                reverse,
+            } => {
+                let mut args: Vec<_> = Vec::with_capacity(values.len() + 2);
+                if reverse {
+                    args.push("!reverse");
+                }
+                args.push(property.as_str());
+                args.extend(values.iter().map(String::as_str));
+                self.run_command_raw_ignore_value("cycle-values", &args)
+                    .await
+            }
+            MpvCommand::DefineSection {
+                name,
+                contents,
+                flags,
+            } => {
+                self.run_command_raw_ignore_value(
+                    "define-section",
+                    &[&name, &contents, flags.into_raw_command_part().as_str()],
+                )
+                .await
+            }
+            MpvCommand::DropBuffers => self.run_command_raw_ignore_value("drop-buffers", &[]).await,
+            MpvCommand::EnableSection { name, flags } => {
+                self.run_command_raw_ignore_value(
+                    "enable-section",
+                    &[&name, flags.into_raw_command_part().as_str()],
+                )
+                .await
+            }
+            MpvCommand::FrameBackStep => {
+                self.run_command_raw_ignore_value("frame-back-step", &[])
+                    .await
+            }
+            MpvCommand::FrameStep => self.run_command_raw_ignore_value("frame-step", &[]).await,
             MpvCommand::LoadFile { file, option } => {
                 self.run_command_raw_ignore_value(
                     "loadfile",
@@ -375,6 +1408,22 @@ impl Mpv {
                 )
                 .await
             }
+            MpvCommand::LoadFileWithOptions {
+                file,
+                option,
+                options,
+            } => {
+                self.run_command_raw_ignore_value(
+                    "loadfile",
+                    &[
+                        file.as_ref(),
+                        option.into_raw_command_part().as_str(),
+                        "-1",
+                        &format_file_options(&options),
+                    ],
+                )
+                .await
+            }
             MpvCommand::LoadList { file, option } => {
                 self.run_command_raw_ignore_value(
                     "loadlist",
@@ -382,18 +1431,96 @@ impl Mpv {
                 )
                 .await
             }
+            MpvCommand::LoadScript { path } => {
+                self.run_command_raw_ignore_value("load-script", &[&path])
+                    .await
+            }
+            MpvCommand::Multiply { property, value } => {
+                self.run_command_raw_ignore_value("multiply", &[&property, &value.to_string()])
+                    .await
+            }
             MpvCommand::Observe { id, property } => {
                 let (res_tx, res_rx) = oneshot::channel();
                 self.command_sender
                     .send((MpvIpcCommand::ObserveProperty(id, property), res_tx))
                     .await
-                    .map_err(|err| MpvError::InternalConnectionError(err.to_string()))?;
+                    .map_err(|_| MpvError::Disconnected)?;
 
                 match res_rx.await {
                     Ok(MpvIpcResponse(response)) => response.map(|_| ()),
-                    Err(err) => Err(MpvError::InternalConnectionError(err.to_string())),
+                    Err(_) => Err(MpvError::Disconnected),
                 }
             }
+            MpvCommand::ObserveNone { id, property } => {
+                let (res_tx, res_rx) = oneshot::channel();
+                self.command_sender
+                    .send((
+                        MpvIpcCommand::ObservePropertyNotifyOnly(id, property),
+                        res_tx,
+                    ))
+                    .await
+                    .map_err(|_| MpvError::Disconnected)?;
+
+                match res_rx.await {
+                    Ok(MpvIpcResponse(response)) => response.map(|_| ()),
+                    Err(_) => Err(MpvError::Disconnected),
+                }
+            }
+            MpvCommand::OsdOverlay {
+                id,
+                format,
+                data,
+                res_x,
+                res_y,
+                z,
+            } => {
+                self.require_command("osd-overlay").await?;
+                let args = Map::from_iter([
+                    ("id".to_owned(), json!(id)),
+                    ("format".to_owned(), json!(format.into_raw_command_part())),
+                    ("data".to_owned(), json!(data)),
+                    ("res_x".to_owned(), json!(res_x)),
+                    ("res_y".to_owned(), json!(res_y)),
+                    ("z".to_owned(), json!(z)),
+                ]);
+                self.run_named_command("osd-overlay", args)
+                    .await
+                    .map(|_| ())
+            }
+            MpvCommand::OverlayAdd {
+                id,
+                x,
+                y,
+                file,
+                offset,
+                fmt,
+                w,
+                h,
+                stride,
+            } => {
+                self.require_command("overlay-add").await?;
+                let args = Map::from_iter([
+                    ("id".to_owned(), json!(id)),
+                    ("x".to_owned(), json!(x)),
+                    ("y".to_owned(), json!(y)),
+                    ("file".to_owned(), json!(file)),
+                    ("offset".to_owned(), json!(offset)),
+                    ("fmt".to_owned(), json!(fmt)),
+                    ("w".to_owned(), json!(w)),
+                    ("h".to_owned(), json!(h)),
+                    ("stride".to_owned(), json!(stride)),
+                ]);
+                self.run_named_command("overlay-add", args)
+                    .await
+                    .map(|_| ())
+            }
+            MpvCommand::OverlayRemove { id } => {
+                self.require_command("overlay-remove").await?;
+                let args = Map::from_iter([("id".to_owned(), json!(id))]);
+                self.run_named_command("overlay-remove", args)
+                    .await
+                    .map(|_| ())
+            }
             MpvCommand::PlaylistClear => {
                 self.run_command_raw_ignore_value("playlist-clear", &[])
                     .await
@@ -405,13 +1532,19 @@ impl Mpv {
                 )
                 .await
             }
-            MpvCommand::PlaylistNext => {
-                self.run_command_raw_ignore_value("playlist-next", &[])
-                    .await
+            MpvCommand::PlaylistNext(mode) => {
+                self.run_command_raw_ignore_value(
+                    "playlist-next",
+                    &[mode.into_raw_command_part().as_str()],
+                )
+                .await
             }
-            MpvCommand::PlaylistPrev => {
-                self.run_command_raw_ignore_value("playlist-prev", &[])
-                    .await
+            MpvCommand::PlaylistPrev(mode) => {
+                self.run_command_raw_ignore_value(
+                    "playlist-prev",
+                    &[mode.into_raw_command_part().as_str()],
+                )
+                .await
             }
             MpvCommand::PlaylistRemove(id) => {
                 self.run_command_raw_ignore_value("playlist-remove", &[&id.to_string()])
@@ -422,6 +1555,27 @@ impl Mpv {
                     .await
             }
             MpvCommand::Quit => self.run_command_raw_ignore_value("quit", &[]).await,
+            MpvCommand::RequestLogMessages(level) => {
+                let level = level
+                    .map(IntoRawCommandPart::into_raw_command_part)
+                    .unwrap_or("no".to_string());
+                self.run_command_raw_ignore_value("request_log_messages", &[&level])
+                    .await
+            }
+            MpvCommand::Screenshot { flag } => {
+                self.run_command_raw_ignore_value(
+                    "screenshot",
+                    &[flag.into_raw_command_part().as_str()],
+                )
+                .await
+            }
+            MpvCommand::ScreenshotToFile { filename, flag } => {
+                self.run_command_raw_ignore_value(
+                    "screenshot-to-file",
+                    &[&filename, flag.into_raw_command_part().as_str()],
+                )
+                .await
+            }
             MpvCommand::ScriptMessage(args) => {
                 let str_args: Vec<_> = args.iter().map(String::as_str).collect();
                 self.run_command_raw_ignore_value("script-message", &str_args)
@@ -434,15 +1588,18 @@ impl Mpv {
                 self.run_command_raw_ignore_value("script-message-to", &cmd_args)
                     .await
             }
-            MpvCommand::Seek { seconds, option } => {
-                self.run_command_raw_ignore_value(
-                    "seek",
-                    &[
-                        &seconds.to_string(),
-                        option.into_raw_command_part().as_str(),
-                    ],
-                )
-                .await
+            MpvCommand::Seek {
+                seconds,
+                option,
+                precision,
+            } => {
+                let flags = seek_flags(option, precision);
+                self.run_command_raw_ignore_value("seek", &[&seconds.to_string(), &flags])
+                    .await
+            }
+            MpvCommand::SubSeek { skip } => {
+                self.run_command_raw_ignore_value("sub-seek", &[&skip.to_string()])
+                    .await
             }
             MpvCommand::Stop => self.run_command_raw_ignore_value("stop", &[]).await,
             MpvCommand::Unobserve(id) => {
@@ -450,11 +1607,11 @@ impl Mpv {
                 self.command_sender
                     .send((MpvIpcCommand::UnobserveProperty(id), res_tx))
                     .await
-                    .map_err(|err| MpvError::InternalConnectionError(err.to_string()))?;
+                    .map_err(|_| MpvError::Disconnected)?;
 
                 match res_rx.await {
                     Ok(MpvIpcResponse(response)) => response.map(|_| ()),
-                    Err(err) => Err(MpvError::InternalConnectionError(err.to_string())),
+                    Err(_) => Err(MpvError::Disconnected),
                 }
             }
         };
@@ -494,7 +1651,113 @@ impl Mpv {
         &self,
         property: &str,
     ) -> Result<Option<T>, MpvError> {
-        T::get_property_generic(self, property).await
+        match self.command_timeout {
+            Some(timeout) => self.get_property_timeout(property, timeout).await,
+            None => T::get_property_generic(self, property).await,
+        }
+    }
+
+    /// Retrieve a property like [`Mpv::get_property`], but with a different timeout than the
+    /// one set with [`MpvBuilder::command_timeout`](crate::MpvBuilder::command_timeout).
+    ///
+    /// Returns [`MpvError::Timeout`] if mpv does not reply within `timeout`.
+    pub async fn get_property_timeout<T: GetPropertyTypeHandler>(
+        &self,
+        property: &str,
+        timeout: Duration,
+    ) -> Result<Option<T>, MpvError> {
+        with_timeout(timeout, T::get_property_generic(self, property)).await
+    }
+
+    /// # Description
+    ///
+    /// Retrieves the property value from mpv, together with the time it was received.
+    ///
+    /// This is the same as [`Mpv::get_property`], but also returns the [`Instant`] at which
+    /// the reply was parsed. Like [`Mpv::get_property`], the value is `None` if the property
+    /// is unavailable, and the [`MpvBuilder::command_timeout`](crate::MpvBuilder::command_timeout)
+    /// applies. Note that this is not the time mpv computed the value, which
+    /// is slightly earlier, depending on the latency of the connection.
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     let (volume, received) = mpv.get_property_timed::<f64>("volume").await?;
+    ///     println!("Volume {:?}, updated {:?} ago", volume, received.elapsed());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_property_timed<T: GetPropertyTypeHandler>(
+        &self,
+        property: &str,
+    ) -> Result<(Option<T>, Instant), MpvError> {
+        let value = self.get_property(property).await?;
+        Ok((value, Instant::now()))
+    }
+
+    /// # Description
+    ///
+    /// Retrieves a field of the Nth entry of a list property, like `track-list/0/lang`.
+    ///
+    /// This is a shorthand for [`Mpv::get_property`] with the path `<base>/<index>/<sub>`,
+    /// and supports the same types.
+    ///
+    /// ## Input arguments
+    ///
+    /// - **base** defines the list property, e.g. `track-list` or `chapter-list`
+    /// - **index** defines the position of the entry in the list
+    /// - **sub** defines the field of the entry that should be retrieved
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     let lang: Option<String> = mpv.get_indexed("track-list", 0, "lang").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_indexed<T: GetPropertyTypeHandler>(
+        &self,
+        base: &str,
+        index: usize,
+        sub: &str,
+    ) -> Result<Option<T>, MpvError> {
+        self.get_property(&format!("{}/{}/{}", base, index, sub))
+            .await
+    }
+
+    /// # Description
+    ///
+    /// Checks whether mpv is idle, i.e. connected but without a file loaded.
+    ///
+    /// This reads the `idle-active` property, which is only `true` when mpv was started
+    /// with `--idle` and nothing is playing. While idle, properties that describe the
+    /// current file, like `path`, `duration` and `time-pos`, are unavailable.
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     if mpv.is_idle().await? {
+    ///         println!("Nothing is loaded yet");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn is_idle(&self) -> Result<bool, MpvError> {
+        self.get_property("idle-active")
+            .await?
+            .ok_or(MpvError::MissingMpvData)
     }
 
     /// # Description
@@ -523,11 +1786,143 @@ impl Mpv {
         self.command_sender
             .send((MpvIpcCommand::GetProperty(property.to_owned()), res_tx))
             .await
-            .map_err(|err| MpvError::InternalConnectionError(err.to_string()))?;
+            .map_err(|_| MpvError::Disconnected)?;
 
         match res_rx.await {
             Ok(MpvIpcResponse(response)) => response,
-            Err(err) => Err(MpvError::InternalConnectionError(err.to_string())),
+            Err(_) => Err(MpvError::Disconnected),
+        }
+    }
+
+    /// Read a property, and deserialize its value into a caller-provided type.
+    ///
+    /// This is meant for complex properties like `track-list`, `chapter-list` or
+    /// `video-params`, where only some of the fields are needed. An unavailable property is
+    /// deserialized from `null`, so use `Option<T>` to accept that.
+    /// Values that do not fit `T` return [`MpvError::JsonParseError`].
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct MyTrack {
+    ///     id: usize,
+    ///     #[serde(rename = "type")]
+    ///     kind: String,
+    ///     lang: Option<String>,
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     let tracks: Vec<MyTrack> = mpv.get_property_as("track-list").await?;
+    ///     for track in tracks {
+    ///         println!("{} {} {:?}", track.id, track.kind, track.lang);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_property_as<T: DeserializeOwned>(
+        &self,
+        property: &str,
+    ) -> Result<T, MpvError> {
+        let value = self.get_property_value(property).await?;
+        Ok(serde_json::from_value(value.unwrap_or(Value::Null))?)
+    }
+
+    /// Like [`Mpv::get_property_value`], but also return the `request_id` that mpv echoed
+    /// back in its reply. This is intended for testing that replies are matched to the
+    /// right requests, and is not part of the stable API.
+    #[cfg(feature = "test-util")]
+    pub async fn get_property_value_with_meta(
+        &self,
+        property: &str,
+    ) -> Result<(Option<Value>, u64), MpvError> {
+        let (res_tx, res_rx) = oneshot::channel();
+        self.command_sender
+            .send((
+                MpvIpcCommand::GetPropertyWithMeta(property.to_owned()),
+                res_tx,
+            ))
+            .await
+            .map_err(|_| MpvError::Disconnected)?;
+
+        match res_rx.await {
+            Ok(MpvIpcResponse(response)) => {
+                let response = response?.unwrap_or(Value::Null);
+                let data = Some(response["data"].clone()).filter(|data| !data.is_null());
+                let request_id = response["request_id"].as_u64().unwrap_or_default();
+                Ok((data, request_id))
+            }
+            Err(_) => Err(MpvError::Disconnected),
+        }
+    }
+
+    /// Retrieve several property values from mpv at once.
+    ///
+    /// All the requests are sent to mpv before waiting for the replies, which saves
+    /// a round trip per property compared to calling [`Mpv::get_property_value`] in a loop.
+    /// The values are returned in the same order as the properties, with `None` for
+    /// unavailable properties. If any of the requests fail, the first error is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     let values = mpv.get_properties(&["volume", "speed", "pause"]).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_properties(
+        &self,
+        properties: &[&str],
+    ) -> Result<Vec<Option<Value>>, MpvError> {
+        let properties = properties.iter().map(|p| p.to_string()).collect();
+        let (res_tx, res_rx) = oneshot::channel();
+        self.command_sender
+            .send((MpvIpcCommand::GetProperties(properties), res_tx))
+            .await
+            .map_err(|_| MpvError::Disconnected)?;
+
+        match res_rx.await {
+            Ok(MpvIpcResponse(response)) => match response? {
+                Some(Value::Array(values)) => Ok(values
+                    .into_iter()
+                    .map(|value| Some(value).filter(|value| !value.is_null()))
+                    .collect()),
+                value => Err(MpvError::ValueContainsUnexpectedType {
+                    expected_type: "Array".to_string(),
+                    received: value.unwrap_or(Value::Null),
+                }),
+            },
+            Err(_) => Err(MpvError::Disconnected),
+        }
+    }
+
+    /// Set several properties at once, in the given order.
+    ///
+    /// Like [`Mpv::get_properties`], all the requests are sent before waiting for the replies.
+    /// mpv handles each request separately, so if one of them fails, the others are still applied.
+    /// In that case, the first error is returned.
+    pub async fn set_properties(&self, properties: &[(&str, Value)]) -> Result<(), MpvError> {
+        let properties = properties
+            .iter()
+            .map(|(property, value)| (property.to_string(), value.to_owned()))
+            .collect();
+        let (res_tx, res_rx) = oneshot::channel();
+        self.command_sender
+            .send((MpvIpcCommand::SetProperties(properties), res_tx))
+            .await
+            .map_err(|_| MpvError::Disconnected)?;
+
+        match res_rx.await {
+            Ok(MpvIpcResponse(response)) => response.map(|_| ()),
+            Err(_) => Err(MpvError::Disconnected),
         }
     }
 
@@ -561,4 +1956,210 @@ impl Mpv {
     {
         T::set_property_generic(self, property, value.clone()).await
     }
+
+    /// # Description
+    ///
+    /// Sets the mpv property _`<property>`_ to an already parsed [`MpvDataType`] value.
+    ///
+    /// This is useful when the value was read from somewhere else, for example
+    /// when copying a property from one mpv instance to another.
+    ///
+    /// ## Input arguments
+    ///
+    /// - **property** defines the mpv property that should be set
+    /// - **value** defines the value of the given mpv property _`<property>`_
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvDataType, MpvError};
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     let other_mpv = Mpv::connect("/tmp/other_mpvsocket").await?;
+    ///     if let Some(volume) = mpv.get_property::<MpvDataType>("volume").await? {
+    ///         other_mpv.set_property_data("volume", volume).await?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn set_property_data(
+        &self,
+        property: &str,
+        value: MpvDataType,
+    ) -> Result<(), MpvError> {
+        self.set_property(property, Value::from(value)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playlist_find_by_filename() {
+        let entry = |id: usize, filename: &str| PlaylistEntry {
+            id,
+            filename: filename.to_string(),
+            title: None,
+            current: false,
+        };
+        let playlist = Playlist(vec![
+            entry(1, "a.mkv"),
+            entry(2, "b.mkv"),
+            entry(3, "a.mkv"),
+        ]);
+
+        assert_eq!(playlist.find_by_filename("b.mkv"), Some(&entry(2, "b.mkv")));
+        assert_eq!(playlist.find_by_filename("a.mkv").map(|e| e.id), Some(1));
+        assert_eq!(playlist.find_by_filename("c.mkv"), None);
+
+        assert_eq!(playlist.position_of("a.mkv"), Some(0));
+        assert_eq!(playlist.position_of("b.mkv"), Some(1));
+        assert_eq!(playlist.position_of("c.mkv"), None);
+        assert_eq!(Playlist::default().position_of("a.mkv"), None);
+    }
+
+    #[test]
+    fn test_file_options() {
+        let options = FileOptions::new()
+            .start(90.5)
+            .aid(2)
+            .sid(3)
+            .vid(1)
+            .title("Episode 1")
+            .set("volume", "50")
+            .set("sid", "no");
+
+        assert_eq!(
+            HashMap::from(options),
+            HashMap::from([
+                ("start".to_string(), "90.5".to_string()),
+                ("aid".to_string(), "2".to_string()),
+                ("sid".to_string(), "no".to_string()),
+                ("vid".to_string(), "1".to_string()),
+                ("force-media-title".to_string(), "Episode 1".to_string()),
+                ("volume".to_string(), "50".to_string()),
+            ])
+        );
+        assert!(FileOptions::new().options().is_empty());
+    }
+
+    #[test]
+    fn test_all_command_names() {
+        let string = String::new;
+        let commands = [
+            MpvCommand::Add {
+                property: string(),
+                value: 1.0,
+            },
+            MpvCommand::ApplyProfile {
+                name: string(),
+                mode: ProfileMode::Apply,
+            },
+            MpvCommand::Cycle {
+                property: string(),
+                direction: CycleDirection::Up,
+            },
+            MpvCommand::CycleValues {
+                property: string(),
+                values: Vec::new(),
+                reverse: false,
+            },
+            MpvCommand::DefineSection {
+                name: string(),
+                contents: string(),
+                flags: DefineSectionFlags::Default,
+            },
+            MpvCommand::DropBuffers,
+            MpvCommand::EnableSection {
+                name: string(),
+                flags: EnableSectionFlags::Default,
+            },
+            MpvCommand::FrameBackStep,
+            MpvCommand::FrameStep,
+            MpvCommand::LoadFile {
+                file: string(),
+                option: PlaylistAddOptions::Append,
+            },
+            MpvCommand::LoadFileWithOptions {
+                file: string(),
+                option: PlaylistAddOptions::Append,
+                options: HashMap::new(),
+            },
+            MpvCommand::LoadList {
+                file: string(),
+                option: PlaylistAddOptions::Append,
+            },
+            MpvCommand::LoadScript { path: string() },
+            MpvCommand::Multiply {
+                property: string(),
+                value: 1.0,
+            },
+            MpvCommand::PlaylistClear,
+            MpvCommand::PlaylistMove { from: 0, to: 1 },
+            MpvCommand::OsdOverlay {
+                id: 0,
+                format: OsdOverlayFormat::AssEvents,
+                data: string(),
+                res_x: 0,
+                res_y: 0,
+                z: 0,
+            },
+            MpvCommand::OverlayAdd {
+                id: 0,
+                x: 0,
+                y: 0,
+                file: string(),
+                offset: 0,
+                fmt: string(),
+                w: 0,
+                h: 0,
+                stride: 0,
+            },
+            MpvCommand::OverlayRemove { id: 0 },
+            MpvCommand::Observe {
+                id: 0,
+                property: string(),
+            },
+            MpvCommand::ObserveNone {
+                id: 0,
+                property: string(),
+            },
+            MpvCommand::PlaylistNext(PlaylistJumpMode::Weak),
+            MpvCommand::PlaylistPrev(PlaylistJumpMode::Weak),
+            MpvCommand::PlaylistRemove(0),
+            MpvCommand::PlaylistShuffle,
+            MpvCommand::Quit,
+            MpvCommand::RequestLogMessages(None),
+            MpvCommand::Screenshot {
+                flag: ScreenshotFlag::Subtitles,
+            },
+            MpvCommand::ScreenshotToFile {
+                filename: string(),
+                flag: ScreenshotFlag::Subtitles,
+            },
+            MpvCommand::ScriptMessage(Vec::new()),
+            MpvCommand::ScriptMessageTo {
+                target: string(),
+                args: Vec::new(),
+            },
+            MpvCommand::Seek {
+                seconds: 0.0,
+                option: SeekOptions::Absolute.into(),
+                precision: SeekPrecision::Default,
+            },
+            MpvCommand::SubSeek { skip: 1 },
+            MpvCommand::Stop,
+            MpvCommand::Unobserve(0),
+        ];
+
+        let mut names: Vec<_> = commands
+            .iter()
+            .filter_map(MpvCommand::command_name)
+            .collect();
+        names.sort();
+        names.dedup();
+        let mut all_names = MpvCommand::all_command_names().to_vec();
+        all_names.sort();
+        assert_eq!(names, all_names);
+    }
 }
@@ -20,6 +20,14 @@ pub enum MpvError {
     #[error("Internal connection error: {0}")]
     InternalConnectionError(String),
 
+    /// The task that talks to mpv has stopped, so commands can no longer be sent.
+    ///
+    /// This happens after [`Mpv::disconnect`](crate::Mpv::disconnect) or a failed heartbeat,
+    /// on every clone of the [`Mpv`](crate::Mpv) instance. Event streams created earlier are
+    /// not closed, and still yield any events that were buffered before the connection stopped.
+    #[error("The connection to mpv has been closed")]
+    Disconnected,
+
     #[error("JsonParseError: {0}")]
     JsonParseError(#[from] serde_json::Error),
 
@@ -39,6 +47,20 @@ pub enum MpvError {
         received: MpvDataType,
     },
 
+    /// The reply to a command was not a JSON object, e.g. a bare string from a misbehaving proxy.
+    ///
+    /// Only this command fails, the connection stays usable.
+    #[error(
+        "Mpv sent an unexpected reply:\nExpected an object, received {received:#?}\nCommand: {command:#?}"
+    )]
+    UnexpectedReply {
+        command: Vec<Value>,
+        received: Value,
+    },
+
+    #[error("Mpv sent null, expected {expected_type}")]
+    UnexpectedNull { expected_type: String },
+
     #[error("Missing expected 'data' field in mpv message")]
     MissingMpvData,
 
@@ -48,6 +70,12 @@ pub enum MpvError {
         map: Map<String, Value>,
     },
 
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Unsupported by mpv: {0}")]
+    Unsupported(String),
+
     #[error("Unexpected property: {0:?}")]
     UnexpectedProperty(Property),
 
@@ -55,6 +83,53 @@ pub enum MpvError {
     Other(String),
 }
 
+/// A parsed version of the error message mpv returns when a command fails.
+///
+/// See [`MpvError::command_error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MpvCommandError {
+    /// The property does not exist.
+    PropertyNotFound,
+
+    /// The property exists, but has no value right now, e.g. `duration` when no file is loaded.
+    PropertyUnavailable,
+
+    /// One of the arguments of the command was invalid.
+    InvalidParameter,
+
+    /// The property can not be read or written with the given type.
+    UnsupportedFormat,
+
+    /// Any other error message.
+    Other(String),
+}
+
+impl From<&str> for MpvCommandError {
+    fn from(message: &str) -> Self {
+        match message {
+            "property not found" => MpvCommandError::PropertyNotFound,
+            "property unavailable" => MpvCommandError::PropertyUnavailable,
+            "invalid parameter" => MpvCommandError::InvalidParameter,
+            "unsupported format for accessing property" => MpvCommandError::UnsupportedFormat,
+            other => MpvCommandError::Other(other.to_owned()),
+        }
+    }
+}
+
+impl MpvError {
+    /// If this error was returned by mpv in response to a command,
+    /// get a parsed version of mpv's error message.
+    ///
+    /// Note that `"property unavailable"` is usually not returned as an error,
+    /// since [`Mpv::get_property`](crate::Mpv::get_property) returns `Ok(None)` in that case.
+    pub fn command_error(&self) -> Option<MpvCommandError> {
+        match self {
+            MpvError::MpvError { message, .. } => Some(MpvCommandError::from(message.as_str())),
+            _ => None,
+        }
+    }
+}
+
 impl PartialEq for MpvError {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -70,6 +145,8 @@ impl PartialEq for MpvError {
             ) => l_command == r_command && l_message == r_message,
             (Self::MpvSocketConnectionError(l0), Self::MpvSocketConnectionError(r0)) => l0 == r0,
             (Self::InternalConnectionError(l0), Self::InternalConnectionError(r0)) => l0 == r0,
+            (Self::Timeout(l0), Self::Timeout(r0)) => l0 == r0,
+            (Self::Unsupported(l0), Self::Unsupported(r0)) => l0 == r0,
             (Self::JsonParseError(l0), Self::JsonParseError(r0)) => {
                 l0.to_string() == r0.to_string()
             }
@@ -93,6 +170,24 @@ impl PartialEq for MpvError {
                     received: r_received,
                 },
             ) => l_expected_type == r_expected_type && l_received == r_received,
+            (
+                Self::UnexpectedReply {
+                    command: l_command,
+                    received: l_received,
+                },
+                Self::UnexpectedReply {
+                    command: r_command,
+                    received: r_received,
+                },
+            ) => l_command == r_command && l_received == r_received,
+            (
+                Self::UnexpectedNull {
+                    expected_type: l_expected_type,
+                },
+                Self::UnexpectedNull {
+                    expected_type: r_expected_type,
+                },
+            ) => l_expected_type == r_expected_type,
             (
                 Self::MissingKeyInObject {
                     key: l_key,
@@ -108,3 +203,31 @@ impl PartialEq for MpvError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_error() {
+        let error = MpvError::MpvError {
+            command: vec![Value::from("get_property"), Value::from("nonexistent")],
+            message: "property not found".to_owned(),
+        };
+        assert_eq!(
+            error.command_error(),
+            Some(MpvCommandError::PropertyNotFound)
+        );
+
+        let error = MpvError::MpvError {
+            command: vec![Value::from("loadfile")],
+            message: "error running command".to_owned(),
+        };
+        assert_eq!(
+            error.command_error(),
+            Some(MpvCommandError::Other("error running command".to_owned()))
+        );
+
+        assert_eq!(MpvError::MissingMpvData.command_error(), None);
+    }
+}
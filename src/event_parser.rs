@@ -5,7 +5,10 @@ use std::str::FromStr;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
-use crate::{MpvDataType, MpvError, ipc::MpvIpcEvent, message_parser::json_to_value};
+use crate::{
+    IntoRawCommandPart, MpvDataType, MpvError, ipc::MpvIpcEvent,
+    message_parser::json_to_value_with_max_depth,
+};
 
 /// Reason behind the `MPV_EVENT_END_FILE` event.
 ///
@@ -86,7 +89,7 @@ impl FromStr for EventLogMessageLevel {
             "warn" => Ok(EventLogMessageLevel::Warn),
             "error" => Ok(EventLogMessageLevel::Error),
             "fatal" => Ok(EventLogMessageLevel::Fatal),
-            "verbose" => Ok(EventLogMessageLevel::Verbose),
+            "v" | "verbose" => Ok(EventLogMessageLevel::Verbose),
             "debug" => Ok(EventLogMessageLevel::Debug),
             "trace" => Ok(EventLogMessageLevel::Trace),
             level => Ok(EventLogMessageLevel::Unimplemented(level.to_string())),
@@ -94,6 +97,21 @@ impl FromStr for EventLogMessageLevel {
     }
 }
 
+impl IntoRawCommandPart for EventLogMessageLevel {
+    fn into_raw_command_part(self) -> String {
+        match self {
+            EventLogMessageLevel::Info => "info".to_string(),
+            EventLogMessageLevel::Warn => "warn".to_string(),
+            EventLogMessageLevel::Error => "error".to_string(),
+            EventLogMessageLevel::Fatal => "fatal".to_string(),
+            EventLogMessageLevel::Verbose => "v".to_string(),
+            EventLogMessageLevel::Debug => "debug".to_string(),
+            EventLogMessageLevel::Trace => "trace".to_string(),
+            EventLogMessageLevel::Unimplemented(level) => level,
+        }
+    }
+}
+
 /// All possible events that can be sent by mpv.
 ///
 /// Not all event types are guaranteed to be implemented.
@@ -177,6 +195,45 @@ pub enum Event {
     Unimplemented(Map<String, Value>),
 }
 
+impl Event {
+    /// A stable name for the kind of event, without any of its data.
+    ///
+    /// This is the event name used by mpv, like `"property-change"` or `"end-file"`,
+    /// which makes it suitable for logging and metrics labels.
+    /// All [`Event::Unimplemented`] events share the name `"unimplemented"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::StartFile { .. } => "start-file",
+            Event::EndFile { .. } => "end-file",
+            Event::FileLoaded => "file-loaded",
+            Event::Seek => "seek",
+            Event::PlaybackRestart => "playback-restart",
+            Event::Shutdown => "shutdown",
+            Event::LogMessage { .. } => "log-message",
+            Event::Hook { .. } => "hook",
+            Event::GetPropertyReply => "get-property-reply",
+            Event::SetPropertyReply => "set-property-reply",
+            Event::CommandReply { .. } => "command-reply",
+            Event::ClientMessage { .. } => "client-message",
+            Event::VideoReconfig => "video-reconfig",
+            Event::AudioReconfig => "audio-reconfig",
+            Event::PropertyChange { .. } => "property-change",
+            Event::EventQueueOverflow => "event-queue-overflow",
+            Event::None => "none",
+            Event::Idle => "idle",
+            Event::Tick => "tick",
+            Event::TracksChanged => "tracks-changed",
+            Event::TrackSwitched => "track-switched",
+            Event::Pause => "pause",
+            Event::Unpause => "unpause",
+            Event::MetadataUpdate => "metadata-update",
+            Event::ChapterChange => "chapter-change",
+            Event::ScriptInputDispatch => "script-input-dispatch",
+            Event::Unimplemented(_) => "unimplemented",
+        }
+    }
+}
+
 macro_rules! get_key_as {
     ($as_type:ident, $key:expr, $event:ident) => {{
         let tmp = $event.get($key).ok_or(MpvError::MissingKeyInObject {
@@ -218,7 +275,10 @@ macro_rules! get_optional_key_as {
 //       If you need this, please open an issue or a PR.
 
 /// Parse a highlevel [`Event`] objects from json.
-pub(crate) fn parse_event(raw_event: MpvIpcEvent) -> Result<Event, MpvError> {
+///
+/// Property values nested more than `max_depth` arrays or objects deep are rejected,
+/// see [`MAX_VALUE_DEPTH`](crate::message_parser::MAX_VALUE_DEPTH).
+pub(crate) fn parse_event(raw_event: MpvIpcEvent, max_depth: usize) -> Result<Event, MpvError> {
     let MpvIpcEvent(event) = raw_event;
 
     event
@@ -251,7 +311,7 @@ pub(crate) fn parse_event(raw_event: MpvIpcEvent) -> Result<Event, MpvError> {
                 "client-message" => parse_client_message(event),
                 "video-reconfig" => Ok(Event::VideoReconfig),
                 "audio-reconfig" => Ok(Event::AudioReconfig),
-                "property-change" => parse_property_change(event),
+                "property-change" => parse_property_change(event, max_depth),
                 "tick" => Ok(Event::Tick),
                 "idle" => Ok(Event::Idle),
                 "tracks-changed" => Ok(Event::TracksChanged),
@@ -325,10 +385,13 @@ fn parse_client_message(event: &Map<String, Value>) -> Result<Event, MpvError> {
     Ok(Event::ClientMessage { args })
 }
 
-fn parse_property_change(event: &Map<String, Value>) -> Result<Event, MpvError> {
+fn parse_property_change(event: &Map<String, Value>, max_depth: usize) -> Result<Event, MpvError> {
     let id = get_optional_key_as!(as_u64, "id", event);
     let property_name = get_key_as!(as_str, "name", event);
-    let data = event.get("data").map(json_to_value).transpose()?;
+    let data = event
+        .get("data")
+        .map(|data| json_to_value_with_max_depth(data, max_depth))
+        .transpose()?;
 
     Ok(Event::PropertyChange {
         id,
@@ -340,9 +403,80 @@ fn parse_property_change(event: &Map<String, Value>) -> Result<Event, MpvError>
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ipc::MpvIpcEvent;
+    use crate::{ipc::MpvIpcEvent, message_parser::MAX_VALUE_DEPTH};
     use serde_json::json;
 
+    #[test]
+    fn test_event_kind() {
+        let events = vec![
+            (
+                Event::StartFile {
+                    playlist_entry_id: 1,
+                },
+                "start-file",
+            ),
+            (
+                Event::EndFile {
+                    reason: EventEndFileReason::Eof,
+                    playlist_entry_id: 1,
+                    file_error: None,
+                    playlist_insert_id: None,
+                    playlist_insert_num_entries: None,
+                },
+                "end-file",
+            ),
+            (Event::FileLoaded, "file-loaded"),
+            (Event::Seek, "seek"),
+            (Event::PlaybackRestart, "playback-restart"),
+            (Event::Shutdown, "shutdown"),
+            (
+                Event::LogMessage {
+                    prefix: "cplayer".to_string(),
+                    level: EventLogMessageLevel::Info,
+                    text: "Playing: video.mkv".to_string(),
+                },
+                "log-message",
+            ),
+            (Event::Hook { hook_id: 1 }, "hook"),
+            (Event::GetPropertyReply, "get-property-reply"),
+            (Event::SetPropertyReply, "set-property-reply"),
+            (
+                Event::CommandReply {
+                    result: "ok".to_string(),
+                },
+                "command-reply",
+            ),
+            (Event::ClientMessage { args: vec![] }, "client-message"),
+            (Event::VideoReconfig, "video-reconfig"),
+            (Event::AudioReconfig, "audio-reconfig"),
+            (
+                Event::PropertyChange {
+                    id: Some(1),
+                    name: "volume".to_string(),
+                    data: Some(MpvDataType::Double(64.0)),
+                },
+                "property-change",
+            ),
+            (Event::EventQueueOverflow, "event-queue-overflow"),
+            (Event::None, "none"),
+            (Event::Idle, "idle"),
+            (Event::Tick, "tick"),
+            (Event::TracksChanged, "tracks-changed"),
+            (Event::TrackSwitched, "track-switched"),
+            (Event::Pause, "pause"),
+            (Event::Unpause, "unpause"),
+            (Event::MetadataUpdate, "metadata-update"),
+            (Event::ChapterChange, "chapter-change"),
+            (Event::ScriptInputDispatch, "script-input-dispatch"),
+            (Event::Unimplemented(Map::new()), "unimplemented"),
+        ];
+
+        for (event, kind) in events {
+            assert!(!event.kind().is_empty());
+            assert_eq!(event.kind(), kind);
+        }
+    }
+
     #[test]
     fn test_parse_simple_events() {
         let simple_events = vec![
@@ -364,7 +498,7 @@ mod tests {
 
         for (raw_event_json, expected_event) in simple_events {
             let raw_event = MpvIpcEvent(raw_event_json);
-            let event = parse_event(raw_event).unwrap();
+            let event = parse_event(raw_event, MAX_VALUE_DEPTH).unwrap();
             assert_eq!(event, expected_event);
         }
     }
@@ -376,7 +510,7 @@ mod tests {
             "playlist_entry_id": 1
         }));
 
-        let event = parse_event(raw_event).unwrap();
+        let event = parse_event(raw_event, MAX_VALUE_DEPTH).unwrap();
 
         assert_eq!(
             event,
@@ -396,7 +530,7 @@ mod tests {
             "playlist_insert_id": 3,
             "playlist_insert_num_entries": 5
         }));
-        let event = parse_event(raw_event).unwrap();
+        let event = parse_event(raw_event, MAX_VALUE_DEPTH).unwrap();
         assert_eq!(
             event,
             Event::EndFile {
@@ -414,7 +548,7 @@ mod tests {
             "playlist_entry_id": 4,
             "file_error": "File not found",
         }));
-        let event_with_error = parse_event(raw_event_with_error).unwrap();
+        let event_with_error = parse_event(raw_event_with_error, MAX_VALUE_DEPTH).unwrap();
         assert_eq!(
             event_with_error,
             Event::EndFile {
@@ -431,7 +565,7 @@ mod tests {
             "reason": "unknown-reason",
             "playlist_entry_id": 5
         }));
-        let event_unimplemented = parse_event(raw_event_unimplemented).unwrap();
+        let event_unimplemented = parse_event(raw_event_unimplemented, MAX_VALUE_DEPTH).unwrap();
         assert_eq!(
             event_unimplemented,
             Event::EndFile {
@@ -452,7 +586,7 @@ mod tests {
             "level": "info",
             "text": "This is a log message"
         }));
-        let event = parse_event(raw_event).unwrap();
+        let event = parse_event(raw_event, MAX_VALUE_DEPTH).unwrap();
         assert_eq!(
             event,
             Event::LogMessage {
@@ -469,7 +603,7 @@ mod tests {
             "event": "hook",
             "hook_id": 42
         }));
-        let event = parse_event(raw_event).unwrap();
+        let event = parse_event(raw_event, MAX_VALUE_DEPTH).unwrap();
         assert_eq!(event, Event::Hook { hook_id: 42 });
     }
 
@@ -479,7 +613,7 @@ mod tests {
             "event": "client-message",
             "args": ["arg1", "arg2", "arg3"]
         }));
-        let event = parse_event(raw_event).unwrap();
+        let event = parse_event(raw_event, MAX_VALUE_DEPTH).unwrap();
         assert_eq!(
             event,
             Event::ClientMessage {
@@ -496,7 +630,7 @@ mod tests {
             "name": "pause",
             "data": true
         }));
-        let event = parse_event(raw_event).unwrap();
+        let event = parse_event(raw_event, MAX_VALUE_DEPTH).unwrap();
         assert_eq!(
             event,
             Event::PropertyChange {
@@ -513,7 +647,7 @@ mod tests {
             "event": "some-unimplemented-event",
             "some_key": "some_value"
         }));
-        let event = parse_event(raw_event).unwrap();
+        let event = parse_event(raw_event, MAX_VALUE_DEPTH).unwrap();
         assert_eq!(
             event,
             Event::Unimplemented(
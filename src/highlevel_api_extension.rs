@@ -1,11 +1,22 @@
 //! High-level API extension for [`Mpv`].
 
 use crate::{
-    IntoRawCommandPart, LoopProperty, Mpv, MpvCommand, MpvDataType, MpvError, Playlist,
-    PlaylistAddOptions, Property, SeekOptions, parse_property,
+    CycleDirection, Event, EventEndFileReason, FileOptions, GetPropertyTypeHandler,
+    IntoRawCommandPart, LoopProperty, Mpv, MpvCommand, MpvDataType, MpvError, OsdDimensions,
+    OsdOverlayFormat, Playlist, PlaylistAddOptions, PlaylistJumpMode, ProfileMode, Property,
+    RenderStats, SeekMode, SeekOptions, SeekPrecision, Track, TrackType, format_duration,
+    message_parser::json_to_value, parse_property, parse_timestamp, seek_flags,
 };
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::{collections::HashMap, sync::atomic::Ordering, time::Duration};
+
+/// The lowest playback speed that [`MpvExt::set_speed`] will set.
+const MIN_SPEED: f64 = 0.01;
+
+/// The highest playback speed that [`MpvExt::set_speed`] will set.
+const MAX_SPEED: f64 = 100.0;
 
 /// Generic high-level command for changing a number property.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +51,62 @@ pub enum PlaylistAddTypeOptions {
     Playlist,
 }
 
+/// Fixed volume levels for [`MpvExt::set_volume_preset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VolumePreset {
+    /// Volume `0`. This sets the volume, and does not touch the `mute` property.
+    Mute,
+    /// Volume `25`.
+    Quarter,
+    /// Volume `50`.
+    Half,
+    /// Volume `100`, which is the unamplified volume.
+    Full,
+    /// The highest volume allowed by the `volume-max` option, `130` by default.
+    Max,
+}
+
+/// The selected track of each type, see [`MpvExt::get_current_tracks`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurrentTracks {
+    pub video: Option<Track>,
+    pub audio: Option<Track>,
+    pub sub: Option<Track>,
+}
+
+/// The size of the video window, see [`MpvExt::get_window_geometry`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// The size of the window, and the margins around the video.
+    pub osd: OsdDimensions,
+    /// The size of the window relative to the video size, from the `window-scale` property.
+    pub scale: f64,
+}
+
+/// What kind of media the current file is, see [`MpvExt::media_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MediaKind {
+    /// A still image, like a picture opened in mpv.
+    Image,
+    /// Audio without a video track, or with only embedded cover art.
+    AudioOnly,
+    /// A video with moving pictures.
+    Video,
+    /// No file is loaded, or the file has neither audio nor video.
+    None,
+}
+
+/// Why playback is or is not progressing, see [`MpvExt::pause_reason`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PauseReason {
+    /// Playback was paused, by the user or another client.
+    UserPaused,
+    /// Playback is stalled without being paused, e.g. waiting for the cache to fill up.
+    Buffering,
+    /// Playback is progressing.
+    Playing,
+}
+
 /// A set of typesafe high-level functions to interact with [`Mpv`].
 // TODO: fix this
 #[allow(async_fn_in_trait)]
@@ -47,11 +114,38 @@ pub trait MpvExt {
     // COMMANDS
 
     /// Seek to a specific position in the current video.
-    async fn seek(&self, seconds: f64, option: SeekOptions) -> Result<(), MpvError>;
+    ///
+    /// `option` is either a [`SeekMode`], combining a [`SeekReference`](crate::SeekReference)
+    /// and a [`SeekUnit`](crate::SeekUnit), or one of the flat [`SeekOptions`].
+    async fn seek(&self, seconds: f64, option: impl Into<SeekMode>) -> Result<(), MpvError>;
+
+    /// Seek to a specific position in the current video, with keyframe or exact precision.
+    ///
+    /// Exact seeking is needed for frame accurate tools, while keyframe seeking is faster for scrubbing.
+    async fn seek_with_precision(
+        &self,
+        seconds: f64,
+        option: impl Into<SeekMode>,
+        precision: SeekPrecision,
+    ) -> Result<(), MpvError>;
+
+    /// Seek to a human readable position, like `"00:01:30"`, `"1:30.5"` or `"10%"`.
+    ///
+    /// See [`parse_timestamp`] for the accepted formats. Malformed timestamps are rejected
+    /// with [`MpvError::Other`] without sending anything to mpv.
+    async fn seek_to_timestamp(&self, timestamp: &str) -> Result<(), MpvError>;
 
     /// Shuffle the current playlist.
     async fn playlist_shuffle(&self) -> Result<(), MpvError>;
 
+    /// Shuffle the current playlist into an order that only depends on `seed` and the playlist length.
+    ///
+    /// mpv's own shuffle can not be seeded, so the order is computed here, and applied
+    /// with one `playlist-move` command per entry that is not in place yet. This takes up to
+    /// one command per entry, and is not atomic: other clients can see the playlist while it is
+    /// partially shuffled, and changes made to the playlist in the meantime can mix up the result.
+    async fn playlist_shuffle_seeded(&self, seed: u64) -> Result<(), MpvError>;
+
     /// Remove an entry from the playlist.
     async fn playlist_remove_id(&self, id: usize) -> Result<(), MpvError>;
 
@@ -69,9 +163,27 @@ pub trait MpvExt {
     /// will result in a final position that is one less than the `to` parameter.
     async fn playlist_move_id(&self, from: usize, to: usize) -> Result<(), MpvError>;
 
+    /// Move the playlist entry at `index` one step up, swapping it with the entry before it.
+    ///
+    /// Returns [`MpvError::Other`] for the first entry, which can not be moved further up.
+    async fn playlist_move_up(&self, index: usize) -> Result<(), MpvError>;
+
+    /// Move the playlist entry at `index` one step down, swapping it with the entry after it.
+    ///
+    /// This takes care of the off-by-one of [`MpvExt::playlist_move_id`] when moving down.
+    /// Returns [`MpvError::Other`] for the last entry, which can not be moved further down.
+    async fn playlist_move_down(&self, index: usize) -> Result<(), MpvError>;
+
     /// Remove all entries from the playlist.
     async fn playlist_clear(&self) -> Result<(), MpvError>;
 
+    /// Replace the whole playlist with the entries of `playlist`, e.g. one read with [`MpvExt::get_playlist`].
+    ///
+    /// The current playlist is replaced by the first entry, which starts playing, and the others
+    /// are appended in order. An empty playlist stops playback. Only the filenames are used:
+    /// mpv derives the titles and ids of the new entries itself, so these are not restored.
+    async fn set_playlist(&self, playlist: &Playlist) -> Result<(), MpvError>;
+
     /// Add a file or playlist to the playlist.
     async fn playlist_add(
         &self,
@@ -80,16 +192,39 @@ pub trait MpvExt {
         option: PlaylistAddOptions,
     ) -> Result<(), MpvError>;
 
+    /// Append a file to the playlist, with per-file options that only apply while it is playing.
+    ///
+    /// This requires mpv `v0.38.0` or newer, see [`MpvCommand::LoadFileWithOptions`].
+    async fn playlist_add_with_options(
+        &self,
+        file: &str,
+        options: FileOptions,
+    ) -> Result<(), MpvError>;
+
     /// Start the current video from the beginning.
     async fn restart(&self) -> Result<(), MpvError>;
 
     /// Play the previous entry in the playlist.
     async fn prev(&self) -> Result<(), MpvError>;
 
+    /// Play the previous entry in the playlist, choosing what happens at the start of it.
+    async fn prev_with_mode(&self, mode: PlaylistJumpMode) -> Result<(), MpvError>;
+
+    /// Reload the current file, and continue playback from the current position.
+    ///
+    /// This is useful for applying settings that only take effect when a file is loaded.
+    /// The function returns once mpv has restarted playback of the reloaded file.
+    /// Returns an error if no file is currently loaded.
+    async fn reload_current(&self) -> Result<(), MpvError>;
+
     /// Notify mpv to send events whenever a property changes.
     /// See [`Mpv::get_event_stream`] and [`Property`](crate::Property) for more information.
     async fn observe_property(&self, id: u64, property: &str) -> Result<(), MpvError>;
 
+    /// Notify mpv to send events whenever a property changes, but without its new value.
+    /// See [`MpvCommand::ObserveNone`] for more information.
+    async fn observe_property_notify_only(&self, id: u64, property: &str) -> Result<(), MpvError>;
+
     /// Stop observing a property.
     /// See [`Mpv::get_event_stream`] and [`Property`](crate::Property) for more information.
     async fn unobserve_property(&self, id: u64) -> Result<(), MpvError>;
@@ -97,6 +232,9 @@ pub trait MpvExt {
     /// Skip to the next entry in the playlist.
     async fn next(&self) -> Result<(), MpvError>;
 
+    /// Skip to the next entry in the playlist, choosing what happens at the end of it.
+    async fn next_with_mode(&self, mode: PlaylistJumpMode) -> Result<(), MpvError>;
+
     /// Stop mpv completely, and kill the process.
     ///
     /// Note that this is different than forcefully killing the process using
@@ -108,34 +246,150 @@ pub trait MpvExt {
     /// removing the pointer to the current video.
     async fn stop(&self) -> Result<(), MpvError>;
 
+    /// Discard all buffered data, for example after reconnecting a live stream.
+    async fn drop_buffers(&self) -> Result<(), MpvError>;
+
+    /// Step forward exactly one frame, and pause.
+    async fn frame_step(&self) -> Result<(), MpvError>;
+
+    /// Step back exactly one frame, and pause. See [`MpvCommand::FrameBackStep`].
+    async fn frame_back_step(&self) -> Result<(), MpvError>;
+
+    /// Apply a profile from mpv's config, or restore the options it changed.
+    async fn apply_profile(&self, name: &str, mode: ProfileMode) -> Result<(), MpvError>;
+
+    /// Seek to the start of a nearby subtitle line. See [`MpvCommand::SubSeek`].
+    async fn sub_seek(&self, skip: i64) -> Result<(), MpvError>;
+
+    /// Load a Lua or JavaScript script into mpv.
+    ///
+    /// See [`MpvCommand::LoadScript`] for the security implications.
+    async fn load_script(&self, path: &str) -> Result<(), MpvError>;
+
     // SETTERS
 
     /// Set the volume of the player.
+    ///
+    /// [`NumberChangeOptions::Increase`] and [`NumberChangeOptions::Decrease`] use
+    /// [`MpvCommand::Add`], so that changes from several clients add up.
     async fn set_volume(
         &self,
         input_volume: f64,
         option: NumberChangeOptions,
     ) -> Result<(), MpvError>;
 
-    /// Set the playback speed of the player.
+    /// Set the volume of the player to a fixed level, and return the resulting volume.
+    ///
+    /// [`VolumePreset::Max`] reads the `volume-max` property to find the highest level.
+    async fn set_volume_preset(&self, preset: VolumePreset) -> Result<f64, MpvError>;
+
+    /// Set the playback speed of the player, and return the resulting speed.
+    ///
+    /// The speed is clamped to the range `0.01..=100.0` that mpv accepts,
+    /// so repeatedly decreasing the speed will never bring it to zero or below.
     async fn set_speed(
         &self,
         input_speed: f64,
         option: NumberChangeOptions,
+    ) -> Result<f64, MpvError>;
+
+    /// Reset the playback speed of the player to normal speed.
+    async fn reset_speed(&self) -> Result<(), MpvError>;
+
+    /// Switch the playback speed between `a` and `b`, and return the resulting speed.
+    ///
+    /// If the speed is currently `a`, it is set to `b`. Otherwise it is set to `a`, so a speed
+    /// that is neither of the two is first reset to `a`. This is useful for a "hold for 2x"
+    /// control, with `a` as the normal speed. The speed is clamped like in [`MpvExt::set_speed`].
+    async fn toggle_speed(&self, a: f64, b: f64) -> Result<f64, MpvError>;
+
+    /// Multiply the playback speed by `factor`, and return the resulting speed.
+    ///
+    /// The speed is read back afterwards, since mpv clamps it to the range it accepts.
+    async fn multiply_speed(&self, factor: f64) -> Result<f64, MpvError>;
+
+    /// Multiply the volume by `factor`, and return the resulting volume.
+    ///
+    /// The volume is read back afterwards, since mpv clamps it to `0..=volume-max`.
+    async fn multiply_volume(&self, factor: f64) -> Result<f64, MpvError>;
+
+    /// Set the video zoom, as a power of two. `0` is no zoom, `1` is twice the size.
+    async fn set_video_zoom(&self, zoom: f64, option: NumberChangeOptions) -> Result<(), MpvError>;
+
+    /// Set the video pan, in units of the video size. `(0, 0)` is centered,
+    /// `(0.5, 0)` moves the video half its width to the right.
+    ///
+    /// With [`NumberChangeOptions::Increase`] and [`NumberChangeOptions::Decrease`],
+    /// `x` and `y` are added to or subtracted from the current pan.
+    async fn set_video_pan(
+        &self,
+        x: f64,
+        y: f64,
+        option: NumberChangeOptions,
     ) -> Result<(), MpvError>;
 
+    /// Resize the video window relative to the video size, e.g. `2.0` for double size.
+    async fn set_window_scale(&self, scale: f64) -> Result<(), MpvError>;
+
     /// Toggle/set the pause state of the player.
     async fn set_playback(&self, option: Switch) -> Result<(), MpvError>;
 
     /// Toggle/set the mute state of the player.
     async fn set_mute(&self, option: Switch) -> Result<(), MpvError>;
 
+    /// Show, hide or toggle the subtitles.
+    ///
+    /// [`Switch::Toggle`] uses `cycle sub-visibility`, so it is a single command.
+    async fn set_sub_visibility(&self, option: Switch) -> Result<(), MpvError>;
+
     /// Toggle/set whether the player should loop the current playlist.
     async fn set_loop_playlist(&self, option: Switch) -> Result<(), MpvError>;
 
     /// Toggle/set whether the player should loop the current video.
     async fn set_loop_file(&self, option: Switch) -> Result<(), MpvError>;
 
+    /// Make mpv quit when there is nothing left to play, by setting the `idle` option to `no`.
+    ///
+    /// This overrides `--idle` from the command line, which is usually needed to start mpv
+    /// without a file to control it over IPC. If mpv is already idle, it quits right away.
+    async fn enable_quit_when_idle(&self) -> Result<(), MpvError>;
+
+    /// Keep mpv running when there is nothing left to play, by setting the `idle` option to `yes`.
+    ///
+    /// This is the same as starting mpv with `--idle=yes`, and undoes [`MpvExt::enable_quit_when_idle`].
+    async fn keep_alive_when_idle(&self) -> Result<(), MpvError>;
+
+    /// Remove an overlay drawn with [`MpvCommand::OsdOverlay`], by replacing it with an empty one.
+    async fn clear_osd_overlay(&self, id: u64) -> Result<(), MpvError>;
+
+    /// Set how many times the A-B loop should repeat before playback continues.
+    ///
+    /// [`LoopProperty::No`] is sent as `0`, meaning that the A-B loop is not repeated.
+    async fn set_ab_loop_count(&self, count: LoopProperty) -> Result<(), MpvError>;
+
+    /// Set an A-B loop from `before` the current position to `after` it, and return the loop points.
+    ///
+    /// The loop points are clamped to the start and end of the file. If the duration of the
+    /// file is unknown, e.g. for live streams, only the start is clamped. Returns
+    /// [`MpvError::Other`] if nothing is playing.
+    async fn ab_loop_around(
+        &self,
+        before: Duration,
+        after: Duration,
+    ) -> Result<(f64, f64), MpvError>;
+
+    /// Set the hardware decoding mode, e.g. `"auto"`, `"auto-copy"` or `"no"` to disable it.
+    async fn set_hwdec(&self, mode: &str) -> Result<(), MpvError>;
+
+    /// Reset the adjustable playback parameters to their defaults.
+    ///
+    /// This sets `speed` to `1`, `audio-delay`, `sub-delay`, `video-zoom`, `video-pan-x`
+    /// and `video-pan-y` to `0`, and `volume` to the given value, or `100` if it is `None`.
+    /// All properties are set at once with [`Mpv::set_properties`]. If some of them fail,
+    /// the others are still reset, and the first error is returned. The error names the
+    /// property in its command, see [`MpvError::MpvError`].
+    async fn reset_playback_params(&self, volume: Option<f64>) -> Result<(), MpvError>;
+
     // GETTERS
 
     /// Get a list of all entries in the playlist.
@@ -144,9 +398,37 @@ pub trait MpvExt {
     /// Get metadata about the current video.
     async fn get_metadata(&self) -> Result<HashMap<String, MpvDataType>, MpvError>;
 
+    /// Get a single metadata tag of the current video, or `None` if the tag is not present.
+    ///
+    /// This reads `metadata/by-key/<key>`, which is cheaper than fetching the whole map
+    /// with [`MpvExt::get_metadata`]. mpv compares the key case-insensitively,
+    /// so `"title"` and `"TITLE"` both find a `Title` tag.
+    async fn get_metadata_value(&self, key: &str) -> Result<Option<String>, MpvError>;
+
+    /// Get the metadata of the current chapter.
+    ///
+    /// Some containers store chapter titles here rather than in the main metadata.
+    /// Returns an empty map if the file has no chapters.
+    async fn get_chapter_metadata(&self) -> Result<HashMap<String, MpvDataType>, MpvError>;
+
+    /// Get the metadata of the current video, filtered by the `display-tags` option.
+    ///
+    /// Returns an empty map if no file is loaded.
+    async fn get_filtered_metadata(&self) -> Result<HashMap<String, MpvDataType>, MpvError>;
+
     /// Get the path of the current video.
+    ///
+    /// This is the path or URL as it was given to mpv, e.g. the URL of a web page
+    /// for network streams. Returns an error if no file is loaded.
     async fn get_file_path(&self) -> Result<String, MpvError>;
 
+    /// Get the path or URL that mpv actually opened for the current video,
+    /// or `None` if no file is loaded.
+    ///
+    /// This usually equals [`MpvExt::get_file_path`], but differs when the path was resolved
+    /// to another URL first, e.g. by the `ytdl_hook` script for web pages.
+    async fn get_stream_open_filename(&self) -> Result<Option<String>, MpvError>;
+
     /// Get the current volume of the player.
     async fn get_volume(&self) -> Result<f64, MpvError>;
 
@@ -154,43 +436,305 @@ pub trait MpvExt {
     async fn get_speed(&self) -> Result<f64, MpvError>;
 
     /// Get the current position in the current video.
+    ///
+    /// Returns `None` if the position is unavailable, or not a valid time.
+    /// See [`MpvExt::get_duration`] for details.
     async fn get_time_pos(&self) -> Result<Option<f64>, MpvError>;
 
     /// Get the amount of time remaining in the current video.
+    ///
+    /// Returns `None` if the remaining time is unavailable, or not a valid time.
+    /// See [`MpvExt::get_duration`] for details.
     async fn get_time_remaining(&self) -> Result<Option<f64>, MpvError>;
 
+    /// Get the amount of time remaining in the current video, formatted as `HH:MM:SS`.
+    ///
+    /// See [`format_duration`] for details about the format.
+    async fn get_time_remaining_formatted(&self) -> Result<Option<String>, MpvError>;
+
+    /// Get the wall-clock time remaining in the current video, accounting for the playback speed.
+    ///
+    /// At twice the speed, this is half of [`MpvExt::get_time_remaining`], which makes it the
+    /// right value for an "ends at" clock. Returns `None` if it is unavailable, or not a valid time.
+    async fn get_playtime_remaining(&self) -> Result<Option<f64>, MpvError>;
+
     /// Get the total duration of the current video.
-    async fn get_duration(&self) -> Result<f64, MpvError>;
+    ///
+    /// Returns `None` if the duration is unavailable, or not a valid time.
+    /// Some streams briefly report negative times while starting up. Since these would
+    /// only lead to nonsensical progress bars, negative and non-finite durations are
+    /// treated as unavailable, just like [`MpvExt::get_time_pos`] and
+    /// [`MpvExt::get_time_remaining`] do. The raw values are still available through
+    /// [`Mpv::get_property`].
+    async fn get_duration(&self) -> Result<Option<f64>, MpvError>;
+
+    /// Get the current position and the total duration of the current video, as `(position, duration)`.
+    ///
+    /// Both properties are requested at once with [`Mpv::get_properties`], so mpv handles
+    /// the two reads right after each other. This keeps the pair consistent for computing
+    /// the progress, unlike reading them separately, which can straddle a file transition.
+    /// Returns `None` if either of them is unavailable, e.g. while idle.
+    async fn get_progress(&self) -> Result<Option<(Duration, Duration)>, MpvError>;
 
-    /// Get the current position in the playlist.
+    /// Get the selected position in the playlist (`playlist-pos`).
+    ///
+    /// This is the entry that playlist navigation starts from, and usually the one being played.
+    /// It differs from [`MpvExt::get_playlist_playing_pos`] while switching entries,
+    /// as mpv updates it as soon as a new entry is selected, before that entry is loaded.
     async fn get_playlist_pos(&self) -> Result<usize, MpvError>;
 
+    /// Get the position of the playlist entry that is actually being played (`playlist-playing-pos`).
+    ///
+    /// This is `None` while mpv is idle, even if [`MpvExt::get_playlist_pos`] points to an entry.
+    async fn get_playlist_playing_pos(&self) -> Result<Option<usize>, MpvError>;
+
+    /// Get the current video zoom. See [`MpvExt::set_video_zoom`].
+    async fn get_video_zoom(&self) -> Result<f64, MpvError>;
+
+    /// Get the current video pan as `(x, y)`. See [`MpvExt::set_video_pan`].
+    async fn get_video_pan(&self) -> Result<(f64, f64), MpvError>;
+
+    /// Get the time ranges that are buffered in the demuxer cache, as `(start, end)` pairs in seconds.
+    ///
+    /// This is useful for shading the buffered parts of a seek bar.
+    /// The list is empty if no file is loaded, or nothing is cached.
+    async fn get_cached_ranges(&self) -> Result<Vec<(f64, f64)>, MpvError>;
+
+    /// Get the size of the video window, and the window scale.
+    ///
+    /// This is `None` if there is no video window, e.g. when mpv runs with `--no-video`.
+    async fn get_window_geometry(&self) -> Result<Option<WindowGeometry>, MpvError>;
+
+    /// Get the render timings of the video output, e.g. for a diagnostics overlay.
+    ///
+    /// This is `None` until the first frame was rendered. Only the GPU based
+    /// video outputs time their passes, and mpv versions without the `vo-passes` property
+    /// return [`MpvError::Unsupported`].
+    async fn get_render_stats(&self) -> Result<Option<RenderStats>, MpvError>;
+
     // BOOLEAN GETTERS
 
     /// Check whether the player is muted.
     async fn is_muted(&self) -> Result<bool, MpvError>;
 
+    /// Check whether subtitles are shown. See [`MpvExt::set_sub_visibility`].
+    async fn is_sub_visible(&self) -> Result<bool, MpvError>;
+
     /// Check whether the player is currently playing.
     async fn is_playing(&self) -> Result<bool, MpvError>;
 
+    /// Find out why playback is paused, e.g. to show a spinner while buffering, but a pause icon
+    /// when the user paused.
+    ///
+    /// This reads `pause`, `paused-for-cache` and `core-idle`, and checks them in that order:
+    /// - If `pause` is set, this is [`PauseReason::UserPaused`], even while the cache is filling up,
+    ///   since playback does not continue by itself.
+    /// - Otherwise, if `paused-for-cache` is set, this is [`PauseReason::Buffering`].
+    /// - Otherwise, if `core-idle` is set, playback is stalled for another reason, like a seek or
+    ///   a file that is being opened, which is also [`PauseReason::Buffering`]. Note that this
+    ///   includes mpv being idle without a file.
+    /// - Otherwise, this is [`PauseReason::Playing`].
+    ///
+    /// Properties that are unavailable are treated as not set.
+    async fn pause_reason(&self) -> Result<PauseReason, MpvError>;
+
     /// Check whether the player is looping the current playlist.
     async fn playlist_is_looping(&self) -> Result<LoopProperty, MpvError>;
 
     /// Check whether the player is looping the current video.
     async fn file_is_looping(&self) -> Result<LoopProperty, MpvError>;
+
+    /// Check whether the player is looping the current playlist at all,
+    /// either infinitely or a set number of times.
+    async fn is_looping_playlist(&self) -> Result<bool, MpvError>;
+
+    /// Check whether the player is looping the current video at all,
+    /// either infinitely or a set number of times.
+    async fn is_looping_file(&self) -> Result<bool, MpvError>;
+
+    /// Check whether the current file is read from the network, e.g. to show a network indicator.
+    ///
+    /// Returns `false` if no file is loaded.
+    async fn is_network_stream(&self) -> Result<bool, MpvError>;
+
+    /// Check whether video output is configured, e.g. to only show a video surface when there is video.
+    ///
+    /// Returns `false` if this is unavailable, e.g. when nothing is playing.
+    async fn has_video_output(&self) -> Result<bool, MpvError>;
+
+    /// Check whether audio output is configured, i.e. whether audio is actually being played.
+    ///
+    /// Returns `false` if this is unavailable, e.g. when nothing is playing.
+    async fn has_audio_output(&self) -> Result<bool, MpvError>;
+
+    /// Get how many times the A-B loop will repeat.
+    async fn get_ab_loop_count(&self) -> Result<LoopProperty, MpvError>;
+
+    /// Get the requested hardware decoding mode. `"no"` means that it is disabled.
+    async fn get_hwdec(&self) -> Result<Option<String>, MpvError>;
+
+    /// Get the hardware decoder that is currently in use, or `"no"` if decoding in software.
+    ///
+    /// Returns `None` if no video is being decoded.
+    async fn get_active_hwdec(&self) -> Result<Option<String>, MpvError>;
+
+    /// Get all audio, video and subtitle tracks of the current file.
+    async fn get_track_list(&self) -> Result<Vec<Track>, MpvError>;
+
+    /// Get the tracks of the current file of a single type, e.g. to list the available subtitles.
+    ///
+    /// The full track list is read at once, so the tracks are consistent with each other.
+    async fn get_tracks_by_type(&self, kind: TrackType) -> Result<Vec<Track>, MpvError>;
+
+    /// Get the selected video, audio and subtitle track, from the `current-tracks/*` properties.
+    ///
+    /// Types without a selected track are `None`, e.g. `sub` while subtitles are disabled.
+    async fn get_current_tracks(&self) -> Result<CurrentTracks, MpvError>;
+
+    /// Classify the current file as an image, audio, or video, e.g. to pick a layout for it.
+    ///
+    /// The classification is based on the video track that is currently selected
+    /// (the one in `current-tracks/video`), or the first video track if none is selected:
+    ///
+    /// - A video track that is cover art does not count as video. The file is
+    ///   [`MediaKind::AudioOnly`] if it has an audio track, and [`MediaKind::Image`] otherwise.
+    /// - Any other video track makes the file [`MediaKind::Image`] if it consists of
+    ///   a single image, and [`MediaKind::Video`] otherwise.
+    /// - Without a video track, the file is [`MediaKind::AudioOnly`] if it has an
+    ///   audio track, and [`MediaKind::None`] otherwise.
+    async fn media_kind(&self) -> Result<MediaKind, MpvError>;
+
+    /// Get the frame rate of the video as estimated from the decoded frames,
+    /// or `None` if no video is being decoded.
+    ///
+    /// This is more accurate than [`MpvExt::get_container_fps`] for variable frame rate video.
+    async fn get_estimated_vf_fps(&self) -> Result<Option<f64>, MpvError>;
+
+    /// Get the number of the current video frame, counting from `0`,
+    /// or `None` if no video is being decoded.
+    ///
+    /// mpv estimates this from the playback position and the frame rate,
+    /// so it can be off for variable frame rate video.
+    async fn get_estimated_frame_number(&self) -> Result<Option<usize>, MpvError>;
+
+    /// Get the total number of video frames in the current file,
+    /// or `None` if no video is being decoded. See [`MpvExt::get_estimated_frame_number`].
+    async fn get_estimated_frame_count(&self) -> Result<Option<usize>, MpvError>;
+
+    /// Get the frame rate of the video as reported by the file,
+    /// or `None` if the file has no video.
+    async fn get_container_fps(&self) -> Result<Option<f64>, MpvError>;
+
+    /// Get the current video bitrate in bits per second,
+    /// or `None` if there is no video track, or the bitrate is not known yet.
+    async fn get_video_bitrate(&self) -> Result<Option<f64>, MpvError>;
+
+    /// Get the current audio bitrate in bits per second,
+    /// or `None` if there is no audio track, or the bitrate is not known yet.
+    async fn get_audio_bitrate(&self) -> Result<Option<f64>, MpvError>;
+
+    /// Get the name of the container format of the current file, like `"mp4"` or `"matroska,webm"`,
+    /// or `None` if no file is loaded.
+    async fn get_file_format(&self) -> Result<Option<String>, MpvError>;
+
+    /// Wait for the next change of a property, and return its new value.
+    ///
+    /// The property is observed only for the duration of this call. The value that
+    /// mpv reports right after observing is the current one, not a change, so it is skipped.
+    /// Returns `None` if the property changed to being unavailable, and
+    /// [`MpvError::Timeout`] if it did not change within `timeout`.
+    /// The property is unobserved again in both cases.
+    ///
+    /// Unlike waiting for a property to reach a certain value, this returns on any change,
+    /// e.g. the next tick of `time-pos`.
+    async fn next_property_change<T: GetPropertyTypeHandler>(
+        &self,
+        property: &str,
+        timeout: Duration,
+    ) -> Result<Option<T>, MpvError>;
+
+    /// Wait until mpv has loaded a file, e.g. right after connecting to a freshly spawned mpv.
+    ///
+    /// Resolves immediately if a file is already loaded, which is checked through the
+    /// `file-format` property. Otherwise this waits for the next [`Event::FileLoaded`],
+    /// and returns [`MpvError::Timeout`] if no file was loaded within `timeout`.
+    async fn wait_until_loaded(&self, timeout: Duration) -> Result<(), MpvError>;
+
+    /// Read a property, retrying while it is unavailable, e.g. `time-pos` right after a `loadfile`.
+    ///
+    /// The property is read up to `attempts` times, waiting `delay` between attempts, so this
+    /// takes at most about `attempts * delay`. Only the property being unavailable is retried.
+    /// Other errors are returned right away, and `None` is returned if the property was still
+    /// unavailable after the last attempt.
+    async fn get_property_retrying<T: GetPropertyTypeHandler>(
+        &self,
+        property: &str,
+        attempts: usize,
+        delay: Duration,
+    ) -> Result<Option<T>, MpvError>;
 }
 
 impl MpvExt for Mpv {
     // COMMANDS
 
-    async fn seek(&self, seconds: f64, option: SeekOptions) -> Result<(), MpvError> {
-        self.run_command(MpvCommand::Seek { seconds, option }).await
+    async fn seek(&self, seconds: f64, option: impl Into<SeekMode>) -> Result<(), MpvError> {
+        self.seek_with_precision(seconds, option, SeekPrecision::Default)
+            .await
+    }
+
+    async fn seek_to_timestamp(&self, timestamp: &str) -> Result<(), MpvError> {
+        let (seconds, option) = parse_timestamp(timestamp)?;
+        self.seek(seconds, option).await
+    }
+
+    async fn seek_with_precision(
+        &self,
+        seconds: f64,
+        option: impl Into<SeekMode>,
+        precision: SeekPrecision,
+    ) -> Result<(), MpvError> {
+        let option = option.into();
+        if self.default_osd {
+            let flags = seek_flags(option, precision);
+            return self
+                .run_command_raw_timed("osd-msg", &["seek", &seconds.to_string(), &flags])
+                .await
+                .map(|_| ());
+        }
+
+        self.run_command(MpvCommand::Seek {
+            seconds,
+            option,
+            precision,
+        })
+        .await
     }
 
     async fn playlist_shuffle(&self) -> Result<(), MpvError> {
         self.run_command(MpvCommand::PlaylistShuffle).await
     }
 
+    async fn playlist_shuffle_seeded(&self, seed: u64) -> Result<(), MpvError> {
+        let len = self.get_playlist().await?.0.len();
+
+        // The original positions of the entries, in their current order
+        let mut entries: Vec<usize> = (0..len).collect();
+        for (to, entry) in seeded_permutation(len, seed).into_iter().enumerate() {
+            let from = entries[to..]
+                .iter()
+                .position(|&current| current == entry)
+                .map(|offset| to + offset);
+            if let Some(from) = from.filter(|&from| from != to) {
+                self.run_command(MpvCommand::PlaylistMove { from, to })
+                    .await?;
+                let entry = entries.remove(from);
+                entries.insert(to, entry);
+            }
+        }
+
+        Ok(())
+    }
+
     async fn playlist_remove_id(&self, id: usize) -> Result<(), MpvError> {
         self.run_command(MpvCommand::PlaylistRemove(id)).await
     }
@@ -218,10 +762,54 @@ impl MpvExt for Mpv {
             .await
     }
 
+    async fn playlist_move_up(&self, index: usize) -> Result<(), MpvError> {
+        if index == 0 {
+            return Err(MpvError::Other(
+                "Can not move the first playlist entry up".to_owned(),
+            ));
+        }
+        self.playlist_move_id(index, index - 1).await
+    }
+
+    async fn playlist_move_down(&self, index: usize) -> Result<(), MpvError> {
+        let count = self
+            .get_property::<usize>("playlist-count")
+            .await?
+            .unwrap_or_default();
+        if index + 1 >= count {
+            return Err(MpvError::Other(format!(
+                "Can not move playlist entry {} down, the playlist has {} entries",
+                index, count
+            )));
+        }
+        // The entry is moved in front of `to`, so skip past the entry that it swaps with.
+        self.playlist_move_id(index, index + 2).await
+    }
+
     async fn playlist_clear(&self) -> Result<(), MpvError> {
         self.run_command(MpvCommand::PlaylistClear).await
     }
 
+    async fn set_playlist(&self, playlist: &Playlist) -> Result<(), MpvError> {
+        if playlist.0.is_empty() {
+            return self.run_command(MpvCommand::Stop).await;
+        }
+
+        for (index, entry) in playlist.0.iter().enumerate() {
+            let option = if index == 0 {
+                PlaylistAddOptions::Replace
+            } else {
+                PlaylistAddOptions::Append
+            };
+            self.run_command(MpvCommand::LoadFile {
+                file: entry.filename.clone(),
+                option,
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
     async fn playlist_add(
         &self,
         file: &str,
@@ -247,16 +835,79 @@ impl MpvExt for Mpv {
         }
     }
 
+    async fn playlist_add_with_options(
+        &self,
+        file: &str,
+        options: FileOptions,
+    ) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::LoadFileWithOptions {
+            file: file.to_string(),
+            option: PlaylistAddOptions::Append,
+            options: options.into(),
+        })
+        .await
+    }
+
     async fn restart(&self) -> Result<(), MpvError> {
         self.run_command(MpvCommand::Seek {
             seconds: 0f64,
-            option: SeekOptions::Absolute,
+            option: SeekOptions::Absolute.into(),
+            precision: SeekPrecision::Default,
         })
         .await
     }
 
     async fn prev(&self) -> Result<(), MpvError> {
-        self.run_command(MpvCommand::PlaylistPrev).await
+        self.prev_with_mode(PlaylistJumpMode::default()).await
+    }
+
+    async fn prev_with_mode(&self, mode: PlaylistJumpMode) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::PlaylistPrev(mode)).await
+    }
+
+    async fn reload_current(&self) -> Result<(), MpvError> {
+        let path = match self.get_property::<String>("path").await? {
+            Some(path) => path,
+            None => {
+                return Err(MpvError::Other(
+                    "Can not reload the current file, no file is loaded".to_owned(),
+                ));
+            }
+        };
+
+        let mut options = HashMap::new();
+        if let Some(time_pos) = self.get_time_pos().await? {
+            options.insert("start".to_string(), time_pos.to_string());
+        }
+
+        // Subscribe before loading, to make sure we don't miss the event.
+        let mut events = self.live_event_stream();
+
+        self.run_command(MpvCommand::LoadFileWithOptions {
+            file: path,
+            option: PlaylistAddOptions::Replace,
+            options,
+        })
+        .await?;
+
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(Event::PlaybackRestart) => return Ok(()),
+                Ok(Event::EndFile {
+                    reason: EventEndFileReason::Error,
+                    file_error,
+                    ..
+                }) => {
+                    return Err(MpvError::Other(format!(
+                        "Failed to reload the current file: {}",
+                        file_error.unwrap_or_default()
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        Err(MpvError::Disconnected)
     }
 
     async fn observe_property(&self, id: u64, property: &str) -> Result<(), MpvError> {
@@ -267,12 +918,24 @@ impl MpvExt for Mpv {
         .await
     }
 
+    async fn observe_property_notify_only(&self, id: u64, property: &str) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::ObserveNone {
+            id,
+            property: property.to_string(),
+        })
+        .await
+    }
+
     async fn unobserve_property(&self, id: u64) -> Result<(), MpvError> {
         self.run_command(MpvCommand::Unobserve(id)).await
     }
 
     async fn next(&self) -> Result<(), MpvError> {
-        self.run_command(MpvCommand::PlaylistNext).await
+        self.next_with_mode(PlaylistJumpMode::default()).await
+    }
+
+    async fn next_with_mode(&self, mode: PlaylistJumpMode) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::PlaylistNext(mode)).await
     }
 
     async fn kill(&self) -> Result<(), MpvError> {
@@ -283,6 +946,37 @@ impl MpvExt for Mpv {
         self.run_command(MpvCommand::Stop).await
     }
 
+    async fn drop_buffers(&self) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::DropBuffers).await
+    }
+
+    async fn frame_step(&self) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::FrameStep).await
+    }
+
+    async fn frame_back_step(&self) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::FrameBackStep).await
+    }
+
+    async fn apply_profile(&self, name: &str, mode: ProfileMode) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::ApplyProfile {
+            name: name.to_owned(),
+            mode,
+        })
+        .await
+    }
+
+    async fn sub_seek(&self, skip: i64) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::SubSeek { skip }).await
+    }
+
+    async fn load_script(&self, path: &str) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::LoadScript {
+            path: path.to_owned(),
+        })
+        .await
+    }
+
     // SETTERS
 
     async fn set_volume(
@@ -290,31 +984,118 @@ impl MpvExt for Mpv {
         input_volume: f64,
         option: NumberChangeOptions,
     ) -> Result<(), MpvError> {
-        let volume = self.get_volume().await?;
-
         match option {
             NumberChangeOptions::Increase => {
-                self.set_property("volume", volume + input_volume).await
+                add_number_property(self, "volume", input_volume).await
             }
             NumberChangeOptions::Decrease => {
-                self.set_property("volume", volume - input_volume).await
+                add_number_property(self, "volume", -input_volume).await
+            }
+            NumberChangeOptions::Absolute => {
+                set_number_property(self, "volume", input_volume).await
             }
-            NumberChangeOptions::Absolute => self.set_property("volume", input_volume).await,
         }
     }
 
+    async fn set_volume_preset(&self, preset: VolumePreset) -> Result<f64, MpvError> {
+        let volume = match preset {
+            VolumePreset::Mute => 0.0,
+            VolumePreset::Quarter => 25.0,
+            VolumePreset::Half => 50.0,
+            VolumePreset::Full => 100.0,
+            VolumePreset::Max => self
+                .get_property("volume-max")
+                .await?
+                .ok_or(MpvError::MissingMpvData)?,
+        };
+
+        set_number_property(self, "volume", volume).await?;
+        Ok(volume)
+    }
+
     async fn set_speed(
         &self,
         input_speed: f64,
         option: NumberChangeOptions,
+    ) -> Result<f64, MpvError> {
+        let speed = match option {
+            NumberChangeOptions::Increase => self.get_speed().await? + input_speed,
+            NumberChangeOptions::Decrease => self.get_speed().await? - input_speed,
+            NumberChangeOptions::Absolute => input_speed,
+        }
+        .clamp(MIN_SPEED, MAX_SPEED);
+
+        set_number_property(self, "speed", speed).await?;
+        Ok(speed)
+    }
+
+    async fn reset_speed(&self) -> Result<(), MpvError> {
+        self.set_property("speed", 1.0).await
+    }
+
+    async fn toggle_speed(&self, a: f64, b: f64) -> Result<f64, MpvError> {
+        // mpv reports back the exact value that was set, so this only guards against rounding.
+        let speed = if (self.get_speed().await? - a).abs() < 1e-9 {
+            b
+        } else {
+            a
+        };
+        self.set_speed(speed, NumberChangeOptions::Absolute).await
+    }
+
+    async fn multiply_speed(&self, factor: f64) -> Result<f64, MpvError> {
+        self.run_command(MpvCommand::Multiply {
+            property: "speed".to_owned(),
+            value: factor,
+        })
+        .await?;
+        self.get_speed().await
+    }
+
+    async fn multiply_volume(&self, factor: f64) -> Result<f64, MpvError> {
+        self.run_command(MpvCommand::Multiply {
+            property: "volume".to_owned(),
+            value: factor,
+        })
+        .await?;
+        self.get_volume().await
+    }
+
+    async fn set_video_zoom(&self, zoom: f64, option: NumberChangeOptions) -> Result<(), MpvError> {
+        let zoom = match option {
+            NumberChangeOptions::Increase => self.get_video_zoom().await? + zoom,
+            NumberChangeOptions::Decrease => self.get_video_zoom().await? - zoom,
+            NumberChangeOptions::Absolute => zoom,
+        };
+        self.set_property("video-zoom", zoom).await
+    }
+
+    async fn set_video_pan(
+        &self,
+        x: f64,
+        y: f64,
+        option: NumberChangeOptions,
     ) -> Result<(), MpvError> {
-        let speed = self.get_speed().await?;
+        let (x, y) = match option {
+            NumberChangeOptions::Increase => {
+                let (pan_x, pan_y) = self.get_video_pan().await?;
+                (pan_x + x, pan_y + y)
+            }
+            NumberChangeOptions::Decrease => {
+                let (pan_x, pan_y) = self.get_video_pan().await?;
+                (pan_x - x, pan_y - y)
+            }
+            NumberChangeOptions::Absolute => (x, y),
+        };
+        self.set_properties(&[
+            ("video-pan-x", serde_json::json!(x)),
+            ("video-pan-y", serde_json::json!(y)),
+        ])
+        .await
+    }
 
-        match option {
-            NumberChangeOptions::Increase => self.set_property("speed", speed + input_speed).await,
-            NumberChangeOptions::Decrease => self.set_property("speed", speed - input_speed).await,
-            NumberChangeOptions::Absolute => self.set_property("speed", input_speed).await,
-        }
+    async fn set_window_scale(&self, scale: f64) -> Result<(), MpvError> {
+        self.set_property("window-scale", scale).await
     }
 
     async fn set_playback(&self, option: Switch) -> Result<(), MpvError> {
@@ -347,6 +1128,20 @@ impl MpvExt for Mpv {
         self.set_property("mute", enabled).await
     }
 
+    async fn set_sub_visibility(&self, option: Switch) -> Result<(), MpvError> {
+        match option {
+            Switch::On => self.set_property("sub-visibility", true).await,
+            Switch::Off => self.set_property("sub-visibility", false).await,
+            Switch::Toggle => {
+                self.run_command(MpvCommand::Cycle {
+                    property: "sub-visibility".to_owned(),
+                    direction: CycleDirection::Up,
+                })
+                .await
+            }
+        }
+    }
+
     async fn set_loop_playlist(&self, option: Switch) -> Result<(), MpvError> {
         let enabled = match option {
             Switch::On => "inf",
@@ -373,6 +1168,78 @@ impl MpvExt for Mpv {
         self.set_property("loop-file", enabled).await
     }
 
+    async fn enable_quit_when_idle(&self) -> Result<(), MpvError> {
+        self.set_property("idle", "no").await
+    }
+
+    async fn keep_alive_when_idle(&self) -> Result<(), MpvError> {
+        self.set_property("idle", "yes").await
+    }
+
+    async fn clear_osd_overlay(&self, id: u64) -> Result<(), MpvError> {
+        self.run_command(MpvCommand::OsdOverlay {
+            id,
+            format: OsdOverlayFormat::None,
+            data: String::new(),
+            res_x: 0,
+            res_y: 0,
+            z: 0,
+        })
+        .await
+    }
+
+    async fn set_ab_loop_count(&self, count: LoopProperty) -> Result<(), MpvError> {
+        let count = match count {
+            LoopProperty::N(n) => serde_json::json!(n),
+            LoopProperty::Inf => serde_json::json!("inf"),
+            LoopProperty::No => serde_json::json!(0),
+        };
+        self.set_property("ab-loop-count", count).await
+    }
+
+    async fn ab_loop_around(
+        &self,
+        before: Duration,
+        after: Duration,
+    ) -> Result<(f64, f64), MpvError> {
+        let position = match self.get_time_pos().await? {
+            Some(position) => position,
+            None => {
+                return Err(MpvError::Other(
+                    "Can not set an A-B loop, nothing is playing".to_owned(),
+                ));
+            }
+        };
+        let duration = self.get_duration().await?;
+
+        let a = (position - before.as_secs_f64()).max(0.0);
+        let b = position + after.as_secs_f64();
+        let b = duration.map_or(b, |duration| b.min(duration));
+        self.set_properties(&[
+            ("ab-loop-a", serde_json::json!(a)),
+            ("ab-loop-b", serde_json::json!(b)),
+        ])
+        .await?;
+        Ok((a, b))
+    }
+
+    async fn set_hwdec(&self, mode: &str) -> Result<(), MpvError> {
+        self.set_property("hwdec", mode).await
+    }
+
+    async fn reset_playback_params(&self, volume: Option<f64>) -> Result<(), MpvError> {
+        self.set_properties(&[
+            ("speed", serde_json::json!(1.0)),
+            ("audio-delay", serde_json::json!(0)),
+            ("sub-delay", serde_json::json!(0)),
+            ("video-zoom", serde_json::json!(0)),
+            ("video-pan-x", serde_json::json!(0)),
+            ("video-pan-y", serde_json::json!(0)),
+            ("volume", serde_json::json!(volume.unwrap_or(100.0))),
+        ])
+        .await
+    }
+
     // GETTERS
 
     async fn get_playlist(&self) -> Result<Playlist, MpvError> {
@@ -391,6 +1258,26 @@ impl MpvExt for Mpv {
         }
     }
 
+    async fn get_chapter_metadata(&self) -> Result<HashMap<String, MpvDataType>, MpvError> {
+        let data = self.get_property("chapter-metadata").await?;
+        match parse_property("chapter-metadata", data)? {
+            Property::ChapterMetadata(value) => Ok(value.unwrap_or_default()),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_filtered_metadata(&self) -> Result<HashMap<String, MpvDataType>, MpvError> {
+        let data = self.get_property("filtered-metadata").await?;
+        match parse_property("filtered-metadata", data)? {
+            Property::FilteredMetadata(value) => Ok(value.unwrap_or_default()),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_metadata_value(&self, key: &str) -> Result<Option<String>, MpvError> {
+        self.get_property(&format!("metadata/by-key/{}", key)).await
+    }
+
     async fn get_file_path(&self) -> Result<String, MpvError> {
         let data = self.get_property("path").await?;
         match parse_property("path", data)? {
@@ -399,6 +1286,14 @@ impl MpvExt for Mpv {
         }
     }
 
+    async fn get_stream_open_filename(&self) -> Result<Option<String>, MpvError> {
+        let data = self.get_property("stream-open-filename").await?;
+        match parse_property("stream-open-filename", data)? {
+            Property::StreamOpenFilename(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
     async fn get_volume(&self) -> Result<f64, MpvError> {
         let data = self.get_property("volume").await?;
         match parse_property("volume", data)? {
@@ -418,7 +1313,7 @@ impl MpvExt for Mpv {
     async fn get_time_pos(&self) -> Result<Option<f64>, MpvError> {
         let data = self.get_property("time-pos").await?;
         match parse_property("time-pos", data)? {
-            Property::TimePos(value) => Ok(value),
+            Property::TimePos(value) => Ok(valid_time(value)),
             prop => Err(MpvError::UnexpectedProperty(prop)),
         }
     }
@@ -426,19 +1321,55 @@ impl MpvExt for Mpv {
     async fn get_time_remaining(&self) -> Result<Option<f64>, MpvError> {
         let data = self.get_property("time-remaining").await?;
         match parse_property("time-remaining", data)? {
-            Property::TimeRemaining(value) => Ok(value),
+            Property::TimeRemaining(value) => Ok(valid_time(value)),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_time_remaining_formatted(&self) -> Result<Option<String>, MpvError> {
+        Ok(self.get_time_remaining().await?.map(format_duration))
+    }
+
+    async fn get_playtime_remaining(&self) -> Result<Option<f64>, MpvError> {
+        let data = self.get_property("playtime-remaining").await?;
+        match parse_property("playtime-remaining", data)? {
+            Property::PlaytimeRemaining(value) => Ok(valid_time(value)),
             prop => Err(MpvError::UnexpectedProperty(prop)),
         }
     }
 
-    async fn get_duration(&self) -> Result<f64, MpvError> {
+    async fn get_duration(&self) -> Result<Option<f64>, MpvError> {
         let data = self.get_property("duration").await?;
         match parse_property("duration", data)? {
-            Property::Duration(Some(value)) => Ok(value),
+            Property::Duration(value) => Ok(valid_time(value)),
             prop => Err(MpvError::UnexpectedProperty(prop)),
         }
     }
 
+    async fn get_progress(&self) -> Result<Option<(Duration, Duration)>, MpvError> {
+        const PROPERTIES: [&str; 2] = ["time-pos", "duration"];
+        let values = self.get_properties(&PROPERTIES).await?;
+        let times = PROPERTIES
+            .iter()
+            .zip(values)
+            .map(|(name, value)| {
+                let data = value.as_ref().map(json_to_value).transpose()?;
+                match parse_property(name, data)? {
+                    Property::TimePos(time) | Property::Duration(time) => Ok(valid_time(time)),
+                    prop => Err(MpvError::UnexpectedProperty(prop)),
+                }
+            })
+            .collect::<Result<Vec<_>, MpvError>>()?;
+
+        match times[..] {
+            [Some(position), Some(duration)] => Ok(Some((
+                Duration::from_secs_f64(position),
+                Duration::from_secs_f64(duration),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
     async fn get_playlist_pos(&self) -> Result<usize, MpvError> {
         let data = self.get_property("playlist-pos").await?;
         match parse_property("playlist-pos", data)? {
@@ -447,6 +1378,68 @@ impl MpvExt for Mpv {
         }
     }
 
+    async fn get_playlist_playing_pos(&self) -> Result<Option<usize>, MpvError> {
+        let data = self.get_property("playlist-playing-pos").await?;
+        match parse_property("playlist-playing-pos", data)? {
+            Property::PlaylistPlayingPos(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_video_zoom(&self) -> Result<f64, MpvError> {
+        let data = self.get_property("video-zoom").await?;
+        match parse_property("video-zoom", data)? {
+            Property::VideoZoom(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_video_pan(&self) -> Result<(f64, f64), MpvError> {
+        let data = self.get_property("video-pan-x").await?;
+        let x = match parse_property("video-pan-x", data)? {
+            Property::VideoPanX(value) => value,
+            prop => return Err(MpvError::UnexpectedProperty(prop)),
+        };
+        let data = self.get_property("video-pan-y").await?;
+        match parse_property("video-pan-y", data)? {
+            Property::VideoPanY(y) => Ok((x, y)),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_cached_ranges(&self) -> Result<Vec<(f64, f64)>, MpvError> {
+        let data = self.get_property("demuxer-cache-state").await?;
+        match parse_property("demuxer-cache-state", data)? {
+            Property::DemuxerCacheState(cache_state) => Ok(cache_state
+                .map(|cache_state| cache_state.seekable_ranges)
+                .unwrap_or_default()),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_render_stats(&self) -> Result<Option<RenderStats>, MpvError> {
+        self.require_property("vo-passes").await?;
+        let data = self.get_property("vo-passes").await?;
+        match parse_property("vo-passes", data)? {
+            Property::VoPasses(render_stats) => Ok(render_stats),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_window_geometry(&self) -> Result<Option<WindowGeometry>, MpvError> {
+        let data = self.get_property("osd-dimensions").await?;
+        let osd = match parse_property("osd-dimensions", data)? {
+            Property::OsdDimensions(Some(osd)) => osd,
+            Property::OsdDimensions(None) => return Ok(None),
+            prop => return Err(MpvError::UnexpectedProperty(prop)),
+        };
+        let data = self.get_property("window-scale").await?;
+        match parse_property("window-scale", data)? {
+            Property::WindowScale(scale) => Ok(scale.map(|scale| WindowGeometry { osd, scale })),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
     // BOOLEAN GETTERS
 
     async fn is_muted(&self) -> Result<bool, MpvError> {
@@ -457,6 +1450,39 @@ impl MpvExt for Mpv {
         }
     }
 
+    async fn is_sub_visible(&self) -> Result<bool, MpvError> {
+        let data = self.get_property("sub-visibility").await?;
+        match parse_property("sub-visibility", data)? {
+            Property::SubVisibility(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn pause_reason(&self) -> Result<PauseReason, MpvError> {
+        const PROPERTIES: [&str; 3] = ["pause", "paused-for-cache", "core-idle"];
+        let values = self.get_properties(&PROPERTIES).await?;
+        let flags = PROPERTIES
+            .iter()
+            .zip(values)
+            .map(|(name, value)| {
+                let data = value.as_ref().map(json_to_value).transpose()?;
+                match parse_property(name, data)? {
+                    Property::Pause(flag) => Ok(flag),
+                    Property::PausedForCache(flag) | Property::CoreIdle(flag) => {
+                        Ok(flag.unwrap_or(false))
+                    }
+                    prop => Err(MpvError::UnexpectedProperty(prop)),
+                }
+            })
+            .collect::<Result<Vec<_>, MpvError>>()?;
+
+        Ok(match flags[..] {
+            [true, _, _] => PauseReason::UserPaused,
+            [false, true, _] | [false, false, true] => PauseReason::Buffering,
+            _ => PauseReason::Playing,
+        })
+    }
+
     async fn is_playing(&self) -> Result<bool, MpvError> {
         let data = self.get_property("pause").await?;
         match parse_property("pause", data)? {
@@ -480,4 +1506,309 @@ impl MpvExt for Mpv {
             prop => Err(MpvError::UnexpectedProperty(prop)),
         }
     }
+
+    async fn is_looping_playlist(&self) -> Result<bool, MpvError> {
+        Ok(self.playlist_is_looping().await? != LoopProperty::No)
+    }
+
+    async fn is_looping_file(&self) -> Result<bool, MpvError> {
+        Ok(self.file_is_looping().await? != LoopProperty::No)
+    }
+
+    async fn is_network_stream(&self) -> Result<bool, MpvError> {
+        let data = self.get_property("demuxer-via-network").await?;
+        match parse_property("demuxer-via-network", data)? {
+            Property::DemuxerViaNetwork(value) => Ok(value.unwrap_or(false)),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn has_video_output(&self) -> Result<bool, MpvError> {
+        let data = self.get_property("vo-configured").await?;
+        match parse_property("vo-configured", data)? {
+            Property::VoConfigured(value) => Ok(value.unwrap_or(false)),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn has_audio_output(&self) -> Result<bool, MpvError> {
+        let data = self.get_property("ao-configured").await?;
+        match parse_property("ao-configured", data)? {
+            Property::AoConfigured(value) => Ok(value.unwrap_or(false)),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_ab_loop_count(&self) -> Result<LoopProperty, MpvError> {
+        let data = self.get_property("ab-loop-count").await?;
+        match parse_property("ab-loop-count", data)? {
+            Property::AbLoopCount(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_hwdec(&self) -> Result<Option<String>, MpvError> {
+        let data = self.get_property("hwdec").await?;
+        match parse_property("hwdec", data)? {
+            Property::Hwdec(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_active_hwdec(&self) -> Result<Option<String>, MpvError> {
+        let data = self.get_property("hwdec-current").await?;
+        match parse_property("hwdec-current", data)? {
+            Property::HwdecCurrent(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_track_list(&self) -> Result<Vec<Track>, MpvError> {
+        let data = self.get_property("track-list").await?;
+        match parse_property("track-list", data)? {
+            Property::TrackList(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_tracks_by_type(&self, kind: TrackType) -> Result<Vec<Track>, MpvError> {
+        let tracks = self.get_track_list().await?;
+        Ok(tracks
+            .into_iter()
+            .filter(|track| track.track_type == kind)
+            .collect())
+    }
+
+    async fn get_current_tracks(&self) -> Result<CurrentTracks, MpvError> {
+        Ok(CurrentTracks {
+            video: get_current_track(self, "current-tracks/video").await?,
+            audio: get_current_track(self, "current-tracks/audio").await?,
+            sub: get_current_track(self, "current-tracks/sub").await?,
+        })
+    }
+
+    async fn media_kind(&self) -> Result<MediaKind, MpvError> {
+        let tracks = self.get_track_list().await?;
+
+        let video_tracks = || {
+            tracks
+                .iter()
+                .filter(|track| track.track_type == TrackType::Video)
+        };
+        let video = video_tracks()
+            .find(|track| track.selected)
+            .or_else(|| video_tracks().next());
+        let has_audio = tracks
+            .iter()
+            .any(|track| track.track_type == TrackType::Audio);
+
+        Ok(match video {
+            Some(video) if video.albumart && has_audio => MediaKind::AudioOnly,
+            Some(video) if video.albumart || video.image => MediaKind::Image,
+            Some(_) => MediaKind::Video,
+            None if has_audio => MediaKind::AudioOnly,
+            None => MediaKind::None,
+        })
+    }
+
+    async fn get_estimated_vf_fps(&self) -> Result<Option<f64>, MpvError> {
+        let data = self.get_property("estimated-vf-fps").await?;
+        match parse_property("estimated-vf-fps", data)? {
+            Property::EstimatedVfFps(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_estimated_frame_number(&self) -> Result<Option<usize>, MpvError> {
+        let data = self.get_property("estimated-frame-number").await?;
+        match parse_property("estimated-frame-number", data)? {
+            Property::EstimatedFrameNumber(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_estimated_frame_count(&self) -> Result<Option<usize>, MpvError> {
+        let data = self.get_property("estimated-frame-count").await?;
+        match parse_property("estimated-frame-count", data)? {
+            Property::EstimatedFrameCount(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_container_fps(&self) -> Result<Option<f64>, MpvError> {
+        let data = self.get_property("container-fps").await?;
+        match parse_property("container-fps", data)? {
+            Property::ContainerFps(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_video_bitrate(&self) -> Result<Option<f64>, MpvError> {
+        let data = self.get_property("video-bitrate").await?;
+        match parse_property("video-bitrate", data)? {
+            Property::VideoBitrate(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_audio_bitrate(&self) -> Result<Option<f64>, MpvError> {
+        let data = self.get_property("audio-bitrate").await?;
+        match parse_property("audio-bitrate", data)? {
+            Property::AudioBitrate(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn get_file_format(&self) -> Result<Option<String>, MpvError> {
+        let data = self.get_property("file-format").await?;
+        match parse_property("file-format", data)? {
+            Property::FileFormat(value) => Ok(value),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        }
+    }
+
+    async fn next_property_change<T: GetPropertyTypeHandler>(
+        &self,
+        property: &str,
+        timeout: Duration,
+    ) -> Result<Option<T>, MpvError> {
+        let id = self.next_observe_id.fetch_add(1, Ordering::Relaxed);
+
+        // Subscribe before observing, to not miss a change right after the current value.
+        let mut events = self.get_event_stream().await;
+        self.observe_property(id, property).await?;
+
+        let change = tokio::time::timeout(timeout, async {
+            let mut skipped_current_value = false;
+            while let Some(event) = events.next().await {
+                match event? {
+                    Event::PropertyChange {
+                        id: Some(event_id),
+                        data,
+                        ..
+                    } if event_id == id => {
+                        if skipped_current_value {
+                            return Ok(data);
+                        }
+                        skipped_current_value = true;
+                    }
+                    _ => {}
+                }
+            }
+            Err(MpvError::MpvSocketConnectionError(
+                "Event stream ended before the property changed".to_owned(),
+            ))
+        })
+        .await;
+
+        let unobserved = self.unobserve_property(id).await;
+        let data = change.map_err(|_| {
+            MpvError::Timeout(format!(
+                "property '{}' did not change within {:?}",
+                property, timeout
+            ))
+        })??;
+        unobserved?;
+
+        match data {
+            None | Some(MpvDataType::Null) => Ok(None),
+            Some(data) => T::parse_property_value(Value::from(data)).map(Some),
+        }
+    }
+
+    async fn wait_until_loaded(&self, timeout: Duration) -> Result<(), MpvError> {
+        // Subscribe before checking, to not miss a file being loaded in between.
+        let mut events = self.live_event_stream();
+        if self.get_file_format().await?.is_some() {
+            return Ok(());
+        }
+
+        tokio::time::timeout(timeout, async {
+            while let Some(event) = events.next().await {
+                if let Event::FileLoaded = event? {
+                    return Ok(());
+                }
+            }
+            Err(MpvError::Disconnected)
+        })
+        .await
+        .map_err(|_| MpvError::Timeout(format!("no file was loaded within {:?}", timeout)))?
+    }
+
+    async fn get_property_retrying<T: GetPropertyTypeHandler>(
+        &self,
+        property: &str,
+        attempts: usize,
+        delay: Duration,
+    ) -> Result<Option<T>, MpvError> {
+        for attempt in 0..attempts.max(1) {
+            if attempt > 0 {
+                tokio::time::sleep(delay).await;
+            }
+            if let Some(value) = self.get_property(property).await? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Treat times that can not be a playback position or duration as unavailable.
+fn valid_time(time: Option<f64>) -> Option<f64> {
+    time.filter(|time| time.is_finite() && *time >= 0.0)
+}
+
+/// Get the selected track from one of the `current-tracks/*` properties.
+async fn get_current_track(mpv: &Mpv, property: &str) -> Result<Option<Track>, MpvError> {
+    let data = mpv.get_property(property).await?;
+    match parse_property(property, data)? {
+        Property::CurrentTrack(track) => Ok(track.map(|track| *track)),
+        prop => Err(MpvError::UnexpectedProperty(prop)),
+    }
+}
+
+/// Set a numeric property, through the OSD if [`MpvBuilder::default_osd`](crate::MpvBuilder::default_osd) is enabled.
+async fn set_number_property(mpv: &Mpv, property: &str, value: f64) -> Result<(), MpvError> {
+    if mpv.default_osd {
+        mpv.run_command_raw_timed("osd-msg", &["set", property, &value.to_string()])
+            .await
+            .map(|_| ())
+    } else {
+        mpv.set_property(property, value).await
+    }
+}
+
+/// Add to a numeric property, through the OSD if [`MpvBuilder::default_osd`](crate::MpvBuilder::default_osd) is enabled.
+async fn add_number_property(mpv: &Mpv, property: &str, value: f64) -> Result<(), MpvError> {
+    if mpv.default_osd {
+        mpv.run_command_raw_timed("osd-msg", &["add", property, &value.to_string()])
+            .await
+            .map(|_| ())
+    } else {
+        mpv.run_command(MpvCommand::Add {
+            property: property.to_owned(),
+            value,
+        })
+        .await
+    }
+}
+
+/// A random permutation of `0..len` that only depends on `seed`, using a Fisher-Yates shuffle.
+fn seeded_permutation(len: usize, seed: u64) -> Vec<usize> {
+    // SplitMix64, which is small and good enough for shuffling playlists
+    let mut state = seed;
+    let mut next_random = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+
+    let mut permutation: Vec<usize> = (0..len).collect();
+    for i in (1..len).rev() {
+        let j = (next_random() % (i as u64 + 1)) as usize;
+        permutation.swap(i, j);
+    }
+    permutation
 }
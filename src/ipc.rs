@@ -1,30 +1,56 @@
 //! IPC handling thread/task. Handles communication between [`Mpv`](crate::Mpv) instances and mpv's unix socket
 
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
 use futures::{SinkExt, StreamExt};
-use serde_json::{Value, json};
+use serde_json::{Map, Value, json};
 use tokio::{
     net::UnixStream,
     sync::{broadcast, mpsc, oneshot},
+    time::{Interval, MissedTickBehavior},
 };
-use tokio_util::codec::{Framed, LinesCodec};
+use tokio_util::codec::Framed;
 
-use crate::MpvError;
+use crate::{
+    Event, MpvCodec, MpvError, Property, event_parser::parse_event,
+    message_parser::MAX_VALUE_DEPTH, parse_property,
+};
 
 /// Container for all state that regards communication with the mpv IPC socket
 /// and message passing with [`Mpv`](crate::Mpv) controllers.
-pub(crate) struct MpvIpc {
-    socket: Framed<UnixStream, LinesCodec>,
+pub(crate) struct MpvIpc<C> {
+    socket: Framed<UnixStream, C>,
     command_channel: mpsc::Receiver<(MpvIpcCommand, oneshot::Sender<MpvIpcResponse>)>,
     event_channel: broadcast::Sender<MpvIpcEvent>,
+    event_replay: Option<Arc<Mutex<MpvIpcEventReplay>>>,
+    lossless_event_channels: Arc<Mutex<Vec<mpsc::UnboundedSender<Event>>>>,
+    observed_properties: Arc<Mutex<MpvIpcObservedProperties>>,
+    last_event: Arc<Mutex<Option<Instant>>>,
+    heartbeat: Option<MpvIpcHeartbeat>,
+    rate_limit: Option<MpvIpcRateLimit>,
+    notify_only_ids: HashSet<u64>,
+    max_value_depth: usize,
+    dropped_events: u64,
+    last_request_id: u64,
 }
 
 /// Commands that can be sent to [`MpvIpc`]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum MpvIpcCommand {
     Command(Vec<String>),
+    NamedCommand(Map<String, Value>),
     GetProperty(String),
     SetProperty(String, Value),
+    GetProperties(Vec<String>),
+    SetProperties(Vec<(String, Value)>),
+    #[cfg(feature = "test-util")]
+    GetPropertyWithMeta(String),
     ObserveProperty(u64, String),
+    ObservePropertyNotifyOnly(u64, String),
     UnobserveProperty(u64),
     Exit,
 }
@@ -37,34 +63,353 @@ pub(crate) struct MpvIpcResponse(pub(crate) Result<Option<Value>, MpvError>);
 #[derive(Debug, Clone)]
 pub(crate) struct MpvIpcEvent(pub(crate) Value);
 
-impl MpvIpc {
+/// A bounded buffer of the most recent events, replayed to new event subscribers.
+///
+/// See [`MpvBuilder::event_replay_depth`](crate::MpvBuilder::event_replay_depth).
+#[derive(Debug)]
+pub(crate) struct MpvIpcEventReplay {
+    depth: usize,
+    events: VecDeque<MpvIpcEvent>,
+}
+
+impl MpvIpcEventReplay {
+    pub(crate) fn new(depth: usize) -> Self {
+        MpvIpcEventReplay {
+            depth,
+            events: VecDeque::with_capacity(depth),
+        }
+    }
+
+    fn push(&mut self, event: MpvIpcEvent) {
+        if self.events.len() == self.depth {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    pub(crate) fn events(&self) -> Vec<MpvIpcEvent> {
+        self.events.iter().cloned().collect()
+    }
+}
+
+/// The properties that are currently observed, and the latest value received for each of them.
+///
+/// See [`Mpv::dump_observed`](crate::Mpv::dump_observed).
+#[derive(Debug, Default)]
+pub(crate) struct MpvIpcObservedProperties {
+    names: HashMap<u64, HashSet<String>>,
+    values: HashMap<String, Property>,
+}
+
+impl MpvIpcObservedProperties {
+    fn observe(&mut self, id: u64, name: &str) {
+        self.names.entry(id).or_default().insert(name.to_owned());
+    }
+
+    fn unobserve(&mut self, id: u64) {
+        self.names.remove(&id);
+        let names = &self.names;
+        self.values
+            .retain(|name, _| names.values().any(|observed| observed.contains(name)));
+    }
+
+    fn update(&mut self, id: u64, name: &str, property: Property) {
+        if self
+            .names
+            .get(&id)
+            .is_some_and(|observed| observed.contains(name))
+        {
+            self.values.insert(name.to_owned(), property);
+        }
+    }
+
+    pub(crate) fn values(&self) -> HashMap<String, Property> {
+        self.values.clone()
+    }
+
+    /// The number of observed properties, counting a property observed with several ids once per id.
+    pub(crate) fn observed_count(&self) -> usize {
+        self.names.values().map(HashSet::len).sum()
+    }
+}
+
+/// Periodic liveness check of the connection, see [`MpvBuilder::heartbeat`](crate::MpvBuilder::heartbeat).
+pub(crate) struct MpvIpcHeartbeat {
+    interval: Interval,
+    timeout: Duration,
+    last_heartbeat: Arc<Mutex<Option<Instant>>>,
+}
+
+impl MpvIpcHeartbeat {
+    pub(crate) fn new(
+        interval: Duration,
+        timeout: Duration,
+        last_heartbeat: Arc<Mutex<Option<Instant>>>,
+    ) -> Self {
+        let mut interval =
+            tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        MpvIpcHeartbeat {
+            interval,
+            timeout,
+            last_heartbeat,
+        }
+    }
+}
+
+/// Wait for the next heartbeat, or return `None` right away if heartbeats are disabled.
+async fn next_heartbeat(heartbeat: &mut Option<MpvIpcHeartbeat>) -> Option<()> {
+    heartbeat.as_mut()?.interval.tick().await;
+    Some(())
+}
+
+/// A limit on the number of commands sent to mpv, see [`MpvBuilder::rate_limit`](crate::MpvBuilder::rate_limit).
+///
+/// Commands over the limit are queued, and sent in order as later windows open.
+/// A queued `set_property` is replaced by a newer one for the same property,
+/// as long as only other `set_property` commands were queued after it.
+pub(crate) struct MpvIpcRateLimit {
+    max_commands: usize,
+    interval: Duration,
+    window_start: tokio::time::Instant,
+    sent_in_window: usize,
+    queue: VecDeque<(MpvIpcCommand, oneshot::Sender<MpvIpcResponse>)>,
+}
+
+impl MpvIpcRateLimit {
+    pub(crate) fn new(max_commands: usize, interval: Duration) -> Self {
+        MpvIpcRateLimit {
+            max_commands: max_commands.max(1),
+            interval,
+            window_start: tokio::time::Instant::now(),
+            sent_in_window: 0,
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Take a slot in the current window, starting a new window if the current one has passed.
+    fn try_acquire(&mut self) -> bool {
+        let now = tokio::time::Instant::now();
+        if now.duration_since(self.window_start) >= self.interval {
+            self.window_start = now;
+            self.sent_in_window = 0;
+        }
+
+        if self.sent_in_window < self.max_commands {
+            self.sent_in_window += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Give back a new command if it can be sent right away without overtaking
+    /// queued commands, or queue it otherwise.
+    fn admit_or_enqueue(
+        &mut self,
+        command: MpvIpcCommand,
+        tx: oneshot::Sender<MpvIpcResponse>,
+    ) -> Option<(MpvIpcCommand, oneshot::Sender<MpvIpcResponse>)> {
+        if self.queue.is_empty() && self.try_acquire() {
+            return Some((command, tx));
+        }
+
+        log::trace!("Rate limited, queueing command: {:?}", command);
+        self.enqueue(command, tx);
+        None
+    }
+
+    /// Take the next queued command, if there is a slot for it.
+    fn next_ready(&mut self) -> Option<(MpvIpcCommand, oneshot::Sender<MpvIpcResponse>)> {
+        if self.queue.is_empty() || !self.try_acquire() {
+            return None;
+        }
+        self.queue.pop_front()
+    }
+
+    /// Queue a command, coalescing it with a queued `set_property` of the same property.
+    ///
+    /// The caller of the replaced command gets a successful reply right away.
+    fn enqueue(&mut self, command: MpvIpcCommand, tx: oneshot::Sender<MpvIpcResponse>) {
+        if let MpvIpcCommand::SetProperty(property, value) = &command {
+            let queued = self
+                .queue
+                .iter_mut()
+                .rev()
+                .take_while(|(queued, _)| matches!(queued, MpvIpcCommand::SetProperty(..)))
+                .find(|(queued, _)| match queued {
+                    MpvIpcCommand::SetProperty(queued_property, _) => queued_property == property,
+                    _ => false,
+                });
+            if let Some((MpvIpcCommand::SetProperty(_, queued_value), queued_tx)) = queued {
+                log::trace!(
+                    "Coalescing set_property for {} while rate limited",
+                    property
+                );
+                *queued_value = value.clone();
+                let replaced_tx = std::mem::replace(queued_tx, tx);
+                let _ = replaced_tx.send(MpvIpcResponse(Ok(None)));
+                return;
+            }
+        }
+
+        self.queue.push_back((command, tx));
+    }
+}
+
+/// Wait until queued commands can be sent, or return `None` right away if nothing is queued.
+async fn next_rate_limit_window(rate_limit: &Option<MpvIpcRateLimit>) -> Option<()> {
+    let rate_limit = rate_limit
+        .as_ref()
+        .filter(|rate_limit| !rate_limit.queue.is_empty())?;
+    tokio::time::sleep_until(rate_limit.window_start + rate_limit.interval).await;
+    Some(())
+}
+
+impl<C: MpvCodec> MpvIpc<C> {
     pub(crate) fn new(
         socket: UnixStream,
+        codec: C,
         command_channel: mpsc::Receiver<(MpvIpcCommand, oneshot::Sender<MpvIpcResponse>)>,
         event_channel: broadcast::Sender<MpvIpcEvent>,
+        event_replay: Option<Arc<Mutex<MpvIpcEventReplay>>>,
+        lossless_event_channels: Arc<Mutex<Vec<mpsc::UnboundedSender<Event>>>>,
+        observed_properties: Arc<Mutex<MpvIpcObservedProperties>>,
     ) -> Self {
         MpvIpc {
-            socket: Framed::new(socket, LinesCodec::new()),
+            socket: Framed::new(socket, codec),
             command_channel,
             event_channel,
+            event_replay,
+            lossless_event_channels,
+            observed_properties,
+            last_event: Arc::default(),
+            heartbeat: None,
+            rate_limit: None,
+            notify_only_ids: HashSet::new(),
+            max_value_depth: MAX_VALUE_DEPTH,
+            dropped_events: 0,
+            last_request_id: 0,
         }
     }
 
+    /// The time the last event was received from mpv, shared with [`Mpv`](crate::Mpv).
+    pub(crate) fn last_event(&self) -> Arc<Mutex<Option<Instant>>> {
+        self.last_event.clone()
+    }
+
     pub(crate) async fn send_command(
         &mut self,
         command: &[Value],
     ) -> Result<Option<Value>, MpvError> {
-        let ipc_command = json!({ "command": command });
+        self.send_json_command(Value::Array(command.to_vec())).await
+    }
+
+    /// Send a command in the named argument form, e.g. `{ "name": "overlay-remove", "id": 0 }`
+    pub(crate) async fn send_named_command(
+        &mut self,
+        command: Map<String, Value>,
+    ) -> Result<Option<Value>, MpvError> {
+        self.send_json_command(Value::Object(command)).await
+    }
+
+    /// Send a command, which is either a JSON array or a JSON object with named arguments.
+    async fn send_json_command(&mut self, command: Value) -> Result<Option<Value>, MpvError> {
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+
+            let command_name = match &command {
+                Value::Array(parts) => parts.first().and_then(Value::as_str),
+                Value::Object(map) => map.get("name").and_then(Value::as_str),
+                _ => None,
+            }
+            .unwrap_or_default();
+            let span = tracing::debug_span!(
+                "mpv_command",
+                command = command_name,
+                duration_ms = tracing::field::Empty,
+                success = tracing::field::Empty,
+            );
+            let start = std::time::Instant::now();
+            let result = self
+                .send_json_command_untraced(&command)
+                .instrument(span.clone())
+                .await;
+            span.record("duration_ms", start.elapsed().as_secs_f64() * 1000.0);
+            span.record("success", result.is_ok());
+            result
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        self.send_json_command_untraced(&command).await
+    }
+
+    async fn send_json_command_untraced(
+        &mut self,
+        command: &Value,
+    ) -> Result<Option<Value>, MpvError> {
+        let request_id = self.write_command(command).await?;
+        self.flush().await?;
+
+        let response = self.read_response(request_id).await?;
+        parse_mpv_response_data(response, command)
+    }
+
+    /// Send several commands at once, without waiting for each reply before sending the next.
+    ///
+    /// mpv handles the commands of a single client in order, so the replies arrive in
+    /// the same order as the commands. The result of each command is returned separately,
+    /// while errors with the connection itself fail the whole batch.
+    async fn send_json_commands(
+        &mut self,
+        commands: &[Value],
+    ) -> Result<Vec<Result<Option<Value>, MpvError>>, MpvError> {
+        let mut request_ids = Vec::with_capacity(commands.len());
+        for command in commands {
+            request_ids.push(self.write_command(command).await?);
+        }
+        self.flush().await?;
+
+        let mut results = Vec::with_capacity(commands.len());
+        for (command, request_id) in commands.iter().zip(request_ids) {
+            let response = self.read_response(request_id).await?;
+            results.push(parse_mpv_response_data(response, command));
+        }
+        Ok(results)
+    }
+
+    /// Queue a command to be written to the socket, and return its request id.
+    /// The socket must be flushed afterwards.
+    async fn write_command(&mut self, command: &Value) -> Result<u64, MpvError> {
+        self.last_request_id += 1;
+        let request_id = self.last_request_id;
+        let ipc_command = json!({ "command": command, "request_id": request_id });
         let ipc_command_str =
             serde_json::to_string(&ipc_command).map_err(MpvError::JsonParseError)?;
 
         log::trace!("Sending command: {}", ipc_command_str);
 
         self.socket
-            .send(ipc_command_str)
+            .feed(ipc_command_str)
             .await
             .map_err(|why| MpvError::MpvSocketConnectionError(why.to_string()))?;
 
+        Ok(request_id)
+    }
+
+    /// Write all queued commands to the socket.
+    async fn flush(&mut self) -> Result<(), MpvError> {
+        SinkExt::<String>::flush(&mut self.socket)
+            .await
+            .map_err(|why| MpvError::MpvSocketConnectionError(why.to_string()))
+    }
+
+    /// Read lines from the socket until a reply arrives, handling any events received in the meantime.
+    ///
+    /// Since mpv replies in order, the reply is expected to carry `request_id`.
+    /// A mismatch is logged, but the reply is still returned.
+    async fn read_response(&mut self, request_id: u64) -> Result<Value, MpvError> {
         let response = loop {
             let response = self
                 .socket
@@ -78,21 +423,70 @@ impl MpvIpc {
             let parsed_response =
                 serde_json::from_str::<Value>(&response).map_err(MpvError::JsonParseError);
 
-            if parsed_response
-                .as_ref()
-                .ok()
-                .and_then(|v| v.as_object().map(|o| o.contains_key("event")))
-                .unwrap_or(false)
-            {
-                self.handle_event(parsed_response).await;
-            } else {
-                break parsed_response;
+            match parsed_response {
+                Ok(value) if is_event(&value) => self.handle_event(Ok(value)).await,
+                // Anything that is not an object can not be an event, so it is treated as a
+                // broken reply, instead of waiting for a reply that might never come.
+                Ok(value) if value.is_object() && !is_reply(&value) => {
+                    log::trace!("Ignoring unexpected message from mpv: {:?}", value);
+                }
+                parsed_response => break parsed_response,
             }
         };
 
         log::trace!("Received response: {:?}", response);
 
-        parse_mpv_response_data(response?, command)
+        if let Ok(response) = &response {
+            let received_id = response.get("request_id").and_then(Value::as_u64);
+            if received_id != Some(request_id) {
+                log::debug!(
+                    "Expected reply with request id {}, received {:?}",
+                    request_id,
+                    received_id
+                );
+            }
+        }
+
+        response
+    }
+
+    /// Limit how deeply property values in events can be nested, see [`MAX_VALUE_DEPTH`].
+    pub(crate) fn with_max_value_depth(mut self, max_value_depth: usize) -> Self {
+        self.max_value_depth = max_value_depth;
+        self
+    }
+
+    /// Check the connection periodically, see [`MpvIpcHeartbeat`].
+    pub(crate) fn with_heartbeat(mut self, heartbeat: MpvIpcHeartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// Limit the number of commands sent to mpv, see [`MpvIpcRateLimit`].
+    pub(crate) fn with_rate_limit(mut self, rate_limit: MpvIpcRateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Request a cheap property, and record the time if mpv replied in time.
+    async fn send_heartbeat(&mut self) -> Result<(), MpvError> {
+        let Some(timeout) = self.heartbeat.as_ref().map(|heartbeat| heartbeat.timeout) else {
+            return Ok(());
+        };
+
+        tokio::time::timeout(timeout, self.get_mpv_property("mpv-version"))
+            .await
+            .map_err(|_| {
+                MpvError::Timeout(format!(
+                    "mpv did not answer the heartbeat within {:?}",
+                    timeout
+                ))
+            })??;
+
+        if let Some(heartbeat) = &self.heartbeat {
+            *heartbeat.last_heartbeat.lock().unwrap() = Some(Instant::now());
+        }
+        Ok(())
     }
 
     pub(crate) async fn get_mpv_property(
@@ -103,6 +497,26 @@ impl MpvIpc {
             .await
     }
 
+    /// Get a property, and also return the request id of mpv's reply.
+    #[cfg(feature = "test-util")]
+    pub(crate) async fn get_mpv_property_with_meta(
+        &mut self,
+        property: &str,
+    ) -> Result<(Option<Value>, u64), MpvError> {
+        let command = json!(["get_property", property]);
+        let request_id = self.write_command(&command).await?;
+        self.flush().await?;
+
+        let response = self.read_response(request_id).await?;
+        let received_id = response.get("request_id").and_then(Value::as_u64).ok_or(
+            MpvError::ValueContainsUnexpectedType {
+                expected_type: "object with a 'request_id' number".to_owned(),
+                received: response.clone(),
+            },
+        )?;
+        parse_mpv_response_data(response, &command).map(|data| (data, received_id))
+    }
+
     pub(crate) async fn set_mpv_property(
         &mut self,
         property: &str,
@@ -112,28 +526,133 @@ impl MpvIpc {
             .await
     }
 
+    /// Get several properties at once. See [`MpvIpc::send_json_commands`].
+    pub(crate) async fn get_mpv_properties(
+        &mut self,
+        properties: &[String],
+    ) -> Result<Vec<Result<Option<Value>, MpvError>>, MpvError> {
+        let commands: Vec<_> = properties
+            .iter()
+            .map(|property| json!(["get_property", property]))
+            .collect();
+        self.send_json_commands(&commands).await
+    }
+
+    /// Set several properties at once. See [`MpvIpc::send_json_commands`].
+    pub(crate) async fn set_mpv_properties(
+        &mut self,
+        properties: Vec<(String, Value)>,
+    ) -> Result<Vec<Result<Option<Value>, MpvError>>, MpvError> {
+        let commands: Vec<_> = properties
+            .into_iter()
+            .map(|(property, value)| json!(["set_property", property, value]))
+            .collect();
+        self.send_json_commands(&commands).await
+    }
+
     pub(crate) async fn observe_property(
         &mut self,
         id: u64,
         property: &str,
     ) -> Result<Option<Value>, MpvError> {
-        self.send_command(&[json!("observe_property"), json!(id), json!(property)])
-            .await
+        // The property is registered before sending the command,
+        // since mpv might send the initial value before the reply.
+        self.observed_properties
+            .lock()
+            .unwrap()
+            .observe(id, property);
+
+        let result = self
+            .send_command(&[json!("observe_property"), json!(id), json!(property)])
+            .await;
+        if result.is_err() {
+            self.observed_properties.lock().unwrap().unobserve(id);
+        }
+        result
+    }
+
+    /// Observe a property, but strip the values from its change events.
+    ///
+    /// mpv's JSON IPC does not support observing without values,
+    /// so they are removed here instead, see [`MpvCommand::ObserveNone`](crate::MpvCommand::ObserveNone).
+    pub(crate) async fn observe_property_notify_only(
+        &mut self,
+        id: u64,
+        property: &str,
+    ) -> Result<Option<Value>, MpvError> {
+        // Like in observe_property, the id is registered before sending the command.
+        self.notify_only_ids.insert(id);
+
+        let result = self
+            .send_command(&[json!("observe_property"), json!(id), json!(property)])
+            .await;
+        if result.is_err() {
+            self.notify_only_ids.remove(&id);
+        }
+        result
     }
 
     pub(crate) async fn unobserve_property(&mut self, id: u64) -> Result<Option<Value>, MpvError> {
-        self.send_command(&[json!("unobserve_property"), json!(id)])
-            .await
+        let result = self
+            .send_command(&[json!("unobserve_property"), json!(id)])
+            .await;
+        if result.is_ok() {
+            self.observed_properties.lock().unwrap().unobserve(id);
+            self.notify_only_ids.remove(&id);
+        }
+        result
+    }
+
+    /// Remove the value from change events of properties observed with
+    /// [`MpvIpc::observe_property_notify_only`].
+    fn strip_notify_only_data(&self, mut event: Value) -> Value {
+        let is_notify_only = event.get("event").and_then(Value::as_str) == Some("property-change")
+            && event
+                .get("id")
+                .and_then(Value::as_u64)
+                .is_some_and(|id| self.notify_only_ids.contains(&id));
+
+        if is_notify_only {
+            if let Some(event) = event.as_object_mut() {
+                event.remove("data");
+            }
+        }
+        event
     }
 
     async fn handle_event(&mut self, event: Result<Value, MpvError>) {
+        let event = event.map(|event| self.strip_notify_only_data(event));
         match &event {
             Ok(event) => {
                 log::trace!("Parsed event: {:?}", event);
-                if let Err(broadcast::error::SendError(_)) =
-                    self.event_channel.send(MpvIpcEvent(event.to_owned()))
-                {
-                    log::trace!("Failed to send event to channel, ignoring");
+                let event = MpvIpcEvent(event.to_owned());
+                *self.last_event.lock().unwrap() = Some(Instant::now());
+
+                self.send_lossless_event(&event);
+                self.update_observed_property(&event);
+
+                // The replay buffer lock is held while broadcasting, so that new subscribers
+                // never observe an event both in the replay buffer and the broadcast channel.
+                let result = match &self.event_replay {
+                    Some(replay) => {
+                        let mut replay = replay.lock().unwrap();
+                        replay.push(event.clone());
+                        self.event_channel.send(event)
+                    }
+                    None => self.event_channel.send(event),
+                };
+
+                if let Err(broadcast::error::SendError(_)) = result {
+                    self.dropped_events += 1;
+                    log::trace!(
+                        "Failed to send event to channel, ignoring ({} dropped so far)",
+                        self.dropped_events
+                    );
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        dropped_events = self.dropped_events,
+                        "No event subscribers, dropped event"
+                    );
                 }
             }
             Err(e) => {
@@ -142,6 +661,107 @@ impl MpvIpc {
         }
     }
 
+    /// Cache the new value of an observed property, if the event is a property change.
+    fn update_observed_property(&self, event: &MpvIpcEvent) {
+        if event.0.get("event").and_then(Value::as_str) != Some("property-change") {
+            return;
+        }
+
+        if let Ok(Event::PropertyChange {
+            id: Some(id),
+            name,
+            data,
+        }) = parse_event(event.clone(), self.max_value_depth)
+        {
+            match parse_property(&name, data) {
+                Ok(property) => self
+                    .observed_properties
+                    .lock()
+                    .unwrap()
+                    .update(id, &name, property),
+                Err(e) => log::trace!("Error parsing observed property, ignoring: {:?}", e),
+            }
+        }
+    }
+
+    /// Forward an event to all channels created with [`Mpv::get_event_channel`](crate::Mpv::get_event_channel),
+    /// removing the ones that have been closed.
+    fn send_lossless_event(&self, event: &MpvIpcEvent) {
+        let mut channels = self.lossless_event_channels.lock().unwrap();
+        if channels.is_empty() {
+            return;
+        }
+
+        match parse_event(event.clone(), self.max_value_depth) {
+            Ok(event) => channels.retain(|channel| channel.send(event.clone()).is_ok()),
+            Err(e) => log::trace!(
+                "Error parsing event for lossless channels, ignoring: {:?}",
+                e
+            ),
+        }
+    }
+
+    /// Run a command from a [`Mpv`](crate::Mpv) handle, and send back the response.
+    ///
+    /// Returns `true` if the command was [`MpvIpcCommand::Exit`], and the task should stop.
+    async fn handle_command(
+        &mut self,
+        cmd: MpvIpcCommand,
+        tx: oneshot::Sender<MpvIpcResponse>,
+    ) -> bool {
+        let cmd_is_exit = matches!(cmd, MpvIpcCommand::Exit);
+        let response = match cmd {
+            MpvIpcCommand::Command(command) => {
+                let refs = command.iter().map(|s| json!(s)).collect::<Vec<Value>>();
+                self.send_command(refs.as_slice()).await
+            }
+            MpvIpcCommand::NamedCommand(command) => self.send_named_command(command).await,
+            MpvIpcCommand::GetProperty(property) => self.get_mpv_property(&property).await,
+            MpvIpcCommand::SetProperty(property, value) => {
+                self.set_mpv_property(&property, value).await
+            }
+            MpvIpcCommand::GetProperties(properties) => self
+                .get_mpv_properties(&properties)
+                .await
+                .and_then(|results| {
+                    results
+                        .into_iter()
+                        .map(|result| result.map(|value| value.unwrap_or(Value::Null)))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map(|values| Some(Value::Array(values))),
+            MpvIpcCommand::SetProperties(properties) => self
+                .set_mpv_properties(properties)
+                .await
+                .and_then(|results| {
+                    results
+                        .into_iter()
+                        .try_for_each(|result| result.map(|_| ()))
+                })
+                .map(|_| None),
+            #[cfg(feature = "test-util")]
+            MpvIpcCommand::GetPropertyWithMeta(property) => self
+                .get_mpv_property_with_meta(&property)
+                .await
+                .map(|(data, request_id)| Some(json!({ "data": data, "request_id": request_id }))),
+            MpvIpcCommand::ObserveProperty(id, property) => {
+                self.observe_property(id, &property).await
+            }
+            MpvIpcCommand::ObservePropertyNotifyOnly(id, property) => {
+                self.observe_property_notify_only(id, &property).await
+            }
+            MpvIpcCommand::UnobserveProperty(id) => self.unobserve_property(id).await,
+            MpvIpcCommand::Exit => Ok(None),
+        };
+
+        // The caller may have stopped waiting, e.g. because of a timeout. That only
+        // concerns this one command, so it must not take down the whole connection.
+        if tx.send(MpvIpcResponse(response)).is_err() {
+            log::debug!("The response to a command was dropped, since nobody was waiting for it");
+        }
+        cmd_is_exit
+    }
+
     pub(crate) async fn run(mut self) -> Result<(), MpvError> {
         loop {
             tokio::select! {
@@ -154,35 +774,39 @@ impl MpvIpc {
                         serde_json::from_str::<Value>(&event)
                         .map_err(MpvError::JsonParseError));
 
-                self.handle_event(parsed_event).await;
+                match parsed_event {
+                    Ok(value) if !is_event(&value) => {
+                        log::trace!("Ignoring unexpected message from mpv: {:?}", value);
+                    }
+                    parsed_event => self.handle_event(parsed_event).await,
+                }
+              }
+              Some(()) = next_heartbeat(&mut self.heartbeat) => {
+                  if let Err(err) = self.send_heartbeat().await {
+                      log::warn!("Heartbeat failed, closing the connection: {}", err);
+                      return Err(err);
+                  }
               }
               Some((cmd, tx)) = self.command_channel.recv() => {
                   log::trace!("Handling command: {:?}", cmd);
-                  match cmd {
-                      MpvIpcCommand::Command(command) => {
-                          let refs = command.iter().map(|s| json!(s)).collect::<Vec<Value>>();
-                          let response = self.send_command(refs.as_slice()).await;
-                          tx.send(MpvIpcResponse(response)).unwrap()
+                  let command = match &mut self.rate_limit {
+                      Some(rate_limit) if cmd != MpvIpcCommand::Exit => {
+                          rate_limit.admit_or_enqueue(cmd, tx)
                       }
-                      MpvIpcCommand::GetProperty(property) => {
-                          let response = self.get_mpv_property(&property).await;
-                          tx.send(MpvIpcResponse(response)).unwrap()
+                      _ => Some((cmd, tx)),
+                  };
+                  if let Some((cmd, tx)) = command {
+                      if self.handle_command(cmd, tx).await {
+                          return Ok(());
                       }
-                      MpvIpcCommand::SetProperty(property, value) => {
-                          let response = self.set_mpv_property(&property, value).await;
-                          tx.send(MpvIpcResponse(response)).unwrap()
-                      }
-                      MpvIpcCommand::ObserveProperty(id, property) => {
-                          let response = self.observe_property(id, &property).await;
-                          tx.send(MpvIpcResponse(response)).unwrap()
-                      }
-                      MpvIpcCommand::UnobserveProperty(id) => {
-                          let response = self.unobserve_property(id).await;
-                          tx.send(MpvIpcResponse(response)).unwrap()
-                      }
-                      MpvIpcCommand::Exit => {
-                        tx.send(MpvIpcResponse(Ok(None))).unwrap();
-                        return Ok(());
+                  }
+              }
+              Some(()) = next_rate_limit_window(&self.rate_limit) => {
+                  while let Some((cmd, tx)) =
+                      self.rate_limit.as_mut().and_then(MpvIpcRateLimit::next_ready)
+                  {
+                      if self.handle_command(cmd, tx).await {
+                          return Ok(());
                       }
                   }
               }
@@ -191,15 +815,34 @@ impl MpvIpc {
     }
 }
 
+/// Whether a message from mpv is an event, as opposed to a reply to a command.
+fn is_event(value: &Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|object| object.contains_key("event"))
+}
+
+/// Whether a message from mpv is a reply to a command. Every reply contains an `error` field,
+/// which is `"success"` if the command succeeded.
+fn is_reply(value: &Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|object| !object.contains_key("event") && object.contains_key("error"))
+}
+
 /// This function does the most basic JSON parsing and error handling
 /// for status codes and errors that all responses from mpv are
 /// expected to contain.
-fn parse_mpv_response_data(value: Value, command: &[Value]) -> Result<Option<Value>, MpvError> {
+fn parse_mpv_response_data(value: Value, command: &Value) -> Result<Option<Value>, MpvError> {
     log::trace!("Parsing mpv response data: {:?}", value);
+    let command_parts = || match command {
+        Value::Array(parts) => parts.to_owned(),
+        command => vec![command.to_owned()],
+    };
     let result = value
         .as_object()
-        .ok_or(MpvError::ValueContainsUnexpectedType {
-            expected_type: "object".to_string(),
+        .ok_or_else(|| MpvError::UnexpectedReply {
+            command: command_parts(),
             received: value.clone(),
         })
         .and_then(|o| {
@@ -223,7 +866,7 @@ fn parse_mpv_response_data(value: Value, command: &[Value]) -> Result<Option<Val
             "success" => Ok(data),
             "property unavailable" => Ok(None),
             err => Err(MpvError::MpvError {
-                command: command.to_owned(),
+                command: command_parts(),
                 message: err.to_string(),
             }),
         });
@@ -1,15 +1,27 @@
 #![doc = include_str!("../README.md")]
 
+mod builder;
+mod capabilities;
 mod core_api;
 mod error;
 mod event_parser;
 mod highlevel_api_extension;
 mod ipc;
 mod message_parser;
+#[cfg(feature = "process")]
+mod process;
 mod property_parser;
+mod snapshot;
+mod streams;
+mod timestamp;
 
+pub use builder::*;
+pub use capabilities::*;
 pub use core_api::*;
 pub use error::*;
 pub use event_parser::*;
 pub use highlevel_api_extension::*;
 pub use property_parser::*;
+pub use snapshot::*;
+pub use streams::*;
+pub use timestamp::*;
@@ -2,23 +2,34 @@
 
 use std::collections::HashMap;
 
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use crate::{MpvDataType, MpvError, PlaylistEntry};
+use crate::{MpvDataType, MpvError, Playlist, PlaylistEntry};
 
 pub trait TypeHandler: Sized {
     fn get_value(value: Value) -> Result<Self, MpvError>;
     fn as_string(&self) -> String;
 }
 
+/// Create the error for when mpv sent a value of the wrong type,
+/// with a separate error for when mpv sent `null`.
+fn unexpected_value(expected_type: &str, received: Value) -> MpvError {
+    match received {
+        Value::Null => MpvError::UnexpectedNull {
+            expected_type: expected_type.to_string(),
+        },
+        received => MpvError::ValueContainsUnexpectedType {
+            expected_type: expected_type.to_string(),
+            received,
+        },
+    }
+}
+
 impl TypeHandler for String {
     fn get_value(value: Value) -> Result<String, MpvError> {
         value
             .as_str()
-            .ok_or(MpvError::ValueContainsUnexpectedType {
-                expected_type: "String".to_string(),
-                received: value.clone(),
-            })
+            .ok_or_else(|| unexpected_value("String", value.clone()))
             .map(|s| s.to_string())
     }
 
@@ -31,10 +42,7 @@ impl TypeHandler for bool {
     fn get_value(value: Value) -> Result<bool, MpvError> {
         value
             .as_bool()
-            .ok_or(MpvError::ValueContainsUnexpectedType {
-                expected_type: "bool".to_string(),
-                received: value.clone(),
-            })
+            .ok_or_else(|| unexpected_value("bool", value.clone()))
     }
 
     fn as_string(&self) -> String {
@@ -48,10 +56,9 @@ impl TypeHandler for bool {
 
 impl TypeHandler for f64 {
     fn get_value(value: Value) -> Result<f64, MpvError> {
-        value.as_f64().ok_or(MpvError::ValueContainsUnexpectedType {
-            expected_type: "f64".to_string(),
-            received: value.clone(),
-        })
+        value
+            .as_f64()
+            .ok_or_else(|| unexpected_value("f64", value.clone()))
     }
 
     fn as_string(&self) -> String {
@@ -64,10 +71,7 @@ impl TypeHandler for usize {
         value
             .as_u64()
             .map(|u| u as usize)
-            .ok_or(MpvError::ValueContainsUnexpectedType {
-                expected_type: "usize".to_string(),
-                received: value.clone(),
-            })
+            .ok_or_else(|| unexpected_value("usize", value.clone()))
     }
 
     fn as_string(&self) -> String {
@@ -117,9 +121,27 @@ impl TypeHandler for Vec<PlaylistEntry> {
     }
 }
 
+/// How deeply arrays and objects can be nested before [`json_to_value`] gives up.
+///
+/// mpv's own values are only nested a few levels deep, but data from untrusted
+/// network streams can end up in events, so this protects against stack overflows.
+/// Events use the limit set with [`MpvBuilder::max_value_depth`](crate::MpvBuilder::max_value_depth) instead.
+pub(crate) const MAX_VALUE_DEPTH: usize = 64;
+
 pub(crate) fn json_to_value(value: &Value) -> Result<MpvDataType, MpvError> {
+    json_to_value_with_max_depth(value, MAX_VALUE_DEPTH)
+}
+
+/// Like [`json_to_value`], but with a custom limit for how deeply arrays and objects can be nested.
+pub(crate) fn json_to_value_with_max_depth(
+    value: &Value,
+    max_depth: usize,
+) -> Result<MpvDataType, MpvError> {
     match value {
-        Value::Array(array) => Ok(MpvDataType::Array(json_array_to_vec(array)?)),
+        Value::Array(_) | Value::Object(_) if max_depth == 0 => Err(MpvError::Other(
+            "Value is nested too deeply to be parsed".to_owned(),
+        )),
+        Value::Array(array) => Ok(MpvDataType::Array(json_array_to_vec(array, max_depth - 1)?)),
         Value::Bool(b) => Ok(MpvDataType::Bool(*b)),
         Value::Number(n) => {
             if n.is_i64() && n.as_i64().unwrap() == -1 {
@@ -135,24 +157,75 @@ pub(crate) fn json_to_value(value: &Value) -> Result<MpvDataType, MpvError> {
                 })
             }
         }
-        Value::Object(map) => Ok(MpvDataType::HashMap(json_map_to_hashmap(map)?)),
+        Value::Object(map) => Ok(MpvDataType::HashMap(json_map_to_hashmap_with_max_depth(
+            map,
+            max_depth - 1,
+        )?)),
         Value::String(s) => Ok(MpvDataType::String(s.to_string())),
         Value::Null => Ok(MpvDataType::Null),
     }
 }
 
+impl From<MpvDataType> for Value {
+    fn from(data: MpvDataType) -> Self {
+        match data {
+            MpvDataType::Array(array) => Value::Array(array.into_iter().map(Value::from).collect()),
+            MpvDataType::Bool(b) => Value::Bool(b),
+            MpvDataType::Double(d) => serde_json::Number::from_f64(d)
+                .map(Value::Number)
+                .unwrap_or(Value::Null),
+            MpvDataType::HashMap(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect(),
+            ),
+            MpvDataType::Null => Value::Null,
+            MpvDataType::MinusOne => Value::from(-1),
+            MpvDataType::Playlist(Playlist(entries)) => Value::Array(
+                entries
+                    .into_iter()
+                    .map(|entry| {
+                        let mut map = Map::new();
+                        map.insert("filename".to_owned(), Value::String(entry.filename));
+                        if let Some(title) = entry.title {
+                            map.insert("title".to_owned(), Value::String(title));
+                        }
+                        map.insert("current".to_owned(), Value::Bool(entry.current));
+                        Value::Object(map)
+                    })
+                    .collect(),
+            ),
+            MpvDataType::String(s) => Value::String(s),
+            MpvDataType::Usize(u) => Value::from(u),
+        }
+    }
+}
+
 pub(crate) fn json_map_to_hashmap(
     map: &serde_json::map::Map<String, Value>,
+) -> Result<HashMap<String, MpvDataType>, MpvError> {
+    json_map_to_hashmap_with_max_depth(map, MAX_VALUE_DEPTH - 1)
+}
+
+fn json_map_to_hashmap_with_max_depth(
+    map: &serde_json::map::Map<String, Value>,
+    max_depth: usize,
 ) -> Result<HashMap<String, MpvDataType>, MpvError> {
     let mut output_map: HashMap<String, MpvDataType> = HashMap::new();
     for (ref key, value) in map.iter() {
-        output_map.insert(key.to_string(), json_to_value(value)?);
+        output_map.insert(
+            key.to_string(),
+            json_to_value_with_max_depth(value, max_depth)?,
+        );
     }
     Ok(output_map)
 }
 
-pub(crate) fn json_array_to_vec(array: &[Value]) -> Result<Vec<MpvDataType>, MpvError> {
-    array.iter().map(json_to_value).collect()
+fn json_array_to_vec(array: &[Value], max_depth: usize) -> Result<Vec<MpvDataType>, MpvError> {
+    array
+        .iter()
+        .map(|value| json_to_value_with_max_depth(value, max_depth))
+        .collect()
 }
 
 fn json_map_to_playlist_entry(
@@ -286,7 +359,10 @@ mod test {
         ]);
 
         println!("{:?}", json.as_array().unwrap());
-        println!("{:?}", json_array_to_vec(json.as_array().unwrap()));
+        println!(
+            "{:?}",
+            json_array_to_vec(json.as_array().unwrap(), MAX_VALUE_DEPTH)
+        );
 
         let expected = vec![
             MpvDataType::Array(vec![
@@ -306,12 +382,82 @@ mod test {
             )])),
         ];
 
-        match json_array_to_vec(json.as_array().unwrap()) {
+        match json_array_to_vec(json.as_array().unwrap(), MAX_VALUE_DEPTH) {
             Ok(v) => assert_eq!(v, expected),
             Err(e) => panic!("{:?}", e),
         }
     }
 
+    #[test]
+    fn test_mpv_data_to_json() {
+        let json = json!({
+            "array": [1, 2, 3],
+            "bool": true,
+            "double": 1.5,
+            "usize": 1,
+            "minus_one": -1,
+            "null": null,
+            "string": "string",
+            "object": {
+                "key": "value"
+            }
+        });
+
+        let data = json_to_value(&json).unwrap();
+        assert_eq!(Value::from(data), json);
+
+        let playlist = MpvDataType::Playlist(Playlist(vec![PlaylistEntry {
+            id: 0,
+            filename: "file1".to_string(),
+            title: None,
+            current: true,
+        }]));
+        assert_eq!(
+            Value::from(playlist),
+            json!([{ "filename": "file1", "current": true }])
+        );
+
+        assert_eq!(Value::from(MpvDataType::Double(f64::NAN)), Value::Null);
+    }
+
+    #[test]
+    fn test_json_to_value_max_depth() {
+        let nested = |depth: usize| {
+            (0..depth).fold(json!("leaf"), |value, i| {
+                if i % 2 == 0 {
+                    json!([value])
+                } else {
+                    json!({ "key": value })
+                }
+            })
+        };
+
+        assert!(json_to_value(&nested(MAX_VALUE_DEPTH)).is_ok());
+        assert_eq!(
+            json_to_value(&nested(1000)),
+            Err(MpvError::Other(
+                "Value is nested too deeply to be parsed".to_owned()
+            ))
+        );
+        assert!(json_to_value_with_max_depth(&nested(3), 3).is_ok());
+        assert!(json_to_value_with_max_depth(&nested(4), 3).is_err());
+        assert_eq!(
+            json_to_value_with_max_depth(&json!("not nested"), 0),
+            Ok(MpvDataType::String("not nested".to_owned()))
+        );
+
+        let event = json!({
+            "event": "property-change",
+            "id": 1,
+            "name": "metadata",
+            "data": nested(1000),
+        });
+        assert!(matches!(
+            crate::event_parser::parse_event(crate::ipc::MpvIpcEvent(event), MAX_VALUE_DEPTH),
+            Err(MpvError::Other(_))
+        ));
+    }
+
     #[test]
     fn test_json_array_to_playlist() -> Result<(), MpvError> {
         let json = json!([
@@ -0,0 +1,149 @@
+//! Helpers for spawning and managing mpv as a child process.
+//!
+//! This module is only available with the `process` feature enabled.
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::StreamExt;
+use tokio::process::{Child, Command};
+
+use crate::{Event, Mpv, MpvCommand, MpvError};
+
+/// How long to wait for a freshly spawned mpv process to create its socket.
+const SPAWN_SOCKET_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Counter to keep socket paths unique between spawns within the same process.
+static SPAWN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Pick a socket path in the system temp directory that is unlikely to collide
+/// with other mpv instances.
+fn temp_socket_path() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or_default();
+
+    std::env::temp_dir()
+        .join(format!(
+            "mpv-ipc-{}-{}-{}",
+            std::process::id(),
+            SPAWN_COUNTER.fetch_add(1, Ordering::Relaxed),
+            nanos
+        ))
+        .to_string_lossy()
+        .into_owned()
+}
+
+impl Mpv {
+    /// Spawn a new mpv process, and connect to it.
+    ///
+    /// The process is started as `mpv --input-ipc-server=<socket> <args...>`,
+    /// where the socket is a fresh path in the system temp directory.
+    /// This waits for mpv to create the socket before connecting.
+    ///
+    /// The returned [`Child`] is configured to be killed when dropped,
+    /// so make sure to hold on to it for as long as you need the mpv process.
+    /// You can use it to wait for the process to exit, or to kill it forcefully.
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let (mut child, mpv) = Mpv::spawn(&["--idle", "--no-video"]).await?;
+    ///     let version: Option<String> = mpv.get_property("mpv-version").await?;
+    ///     child.kill().await.unwrap();
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn spawn(args: &[&str]) -> Result<(Child, Mpv), MpvError> {
+        let socket_path = temp_socket_path();
+
+        log::debug!("Spawning mpv with socket at {}", socket_path);
+
+        let child = Command::new("mpv")
+            .arg(format!("--input-ipc-server={}", socket_path))
+            .args(args)
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| MpvError::Other(format!("Failed to start mpv: {}", err)))?;
+
+        let mpv = Mpv::connect_when_available(&socket_path, SPAWN_SOCKET_TIMEOUT).await?;
+
+        Ok((child, mpv))
+    }
+
+    /// Ask mpv to quit, and wait for the process to exit.
+    ///
+    /// This sends [`MpvCommand::Quit`], waits for the [`Event::Shutdown`] event,
+    /// and then waits for `child` to exit. If this takes longer than `timeout` in total,
+    /// the process is killed, and [`MpvError::Timeout`] is returned. If sending the quit
+    /// command fails, the process is still waited for or killed, and that error is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let (child, mpv) = Mpv::spawn(&["--idle"]).await?;
+    ///     mpv.quit_and_wait(child, Duration::from_secs(1)).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn quit_and_wait(self, mut child: Child, timeout: Duration) -> Result<(), MpvError> {
+        let mut events = self.get_event_stream().await;
+
+        // Keep waiting even if quitting failed, so the process is still killed on timeout,
+        // but report the quit error first.
+        let mut quit_error = None;
+        let result = tokio::time::timeout(timeout, async {
+            match self.run_command(MpvCommand::Quit).await {
+                // mpv might close the socket before the reply is received
+                Ok(()) | Err(MpvError::MpvSocketConnectionError(_)) => {}
+                Err(err) => quit_error = Some(err),
+            }
+
+            while let Some(event) = events.next().await {
+                if let Ok(Event::Shutdown) = event {
+                    break;
+                }
+            }
+
+            child.wait().await.map_err(|err| {
+                MpvError::Other(format!("Failed to wait for mpv process to exit: {}", err))
+            })
+        })
+        .await;
+
+        if let Some(err) = quit_error {
+            if result.is_err() {
+                log::debug!("mpv did not exit within {:?}, killing it", timeout);
+                if let Err(kill_err) = child.kill().await {
+                    log::debug!("Failed to kill mpv: {}", kill_err);
+                }
+            }
+            return Err(err);
+        }
+
+        match result {
+            Ok(result) => result.map(|_| ()),
+            Err(_) => {
+                log::debug!("mpv did not exit within {:?}, killing it", timeout);
+                child
+                    .kill()
+                    .await
+                    .map_err(|err| MpvError::Other(format!("Failed to kill mpv: {}", err)))?;
+                Err(MpvError::Timeout(format!(
+                    "mpv did not exit within {:?}, and was killed",
+                    timeout
+                )))
+            }
+        }
+    }
+}
@@ -9,11 +9,14 @@
 // TODO: reuse this logic for providing a more typesafe response API to `Mpv::get_property()`
 //       Although this data is currently of type `Option<`
 
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{MpvDataType, MpvError, PlaylistEntry};
+use crate::{
+    DemuxerCacheState, MpvDataType, MpvError, OsdDimensions, PlaylistEntry, RenderPass,
+    RenderStats, Track, TrackType,
+};
 
 /// An incomplete list of properties that mpv can return.
 ///
@@ -30,16 +33,77 @@ pub enum Property {
     PlaybackTime(Option<f64>),
     Duration(Option<f64>),
     Metadata(Option<HashMap<String, MpvDataType>>),
+    FilteredMetadata(Option<HashMap<String, MpvDataType>>),
+    ChapterMetadata(Option<HashMap<String, MpvDataType>>),
     Playlist(Vec<PlaylistEntry>),
+    /// The selected entry in the playlist, which is `None` if the playlist is empty.
+    ///
+    /// This is usually the entry being played, but it can point elsewhere, e.g. while
+    /// a new entry is being loaded. See [`Property::PlaylistPlayingPos`].
     PlaylistPos(Option<usize>),
+    /// The playlist entry that is actually being played, which is `None` while idle.
+    PlaylistPlayingPos(Option<usize>),
     LoopFile(LoopProperty),
     LoopPlaylist(LoopProperty),
+    AbLoopCount(LoopProperty),
     TimePos(Option<f64>),
     TimeRemaining(Option<f64>),
+    /// The remaining time in real seconds, accounting for the playback speed.
+    PlaytimeRemaining(Option<f64>),
     Speed(f64),
     Volume(f64),
     Mute(bool),
+    /// Whether subtitles are shown.
+    SubVisibility(bool),
     EofReached(bool),
+    /// Whether mpv is idle, i.e. no file is loaded.
+    IdleActive(bool),
+    /// The requested hardware decoding mode, e.g. `"auto-copy"`. `"no"` means disabled.
+    Hwdec(Option<String>),
+    /// The hardware decoder in use, or `"no"` if decoding in software.
+    /// This is `None` if no video is being decoded.
+    HwdecCurrent(Option<String>),
+    TrackList(Vec<Track>),
+    /// The selected track of one type, from `current-tracks/video`, `current-tracks/audio`
+    /// or `current-tracks/sub`. This is `None` if no track of that type is selected.
+    ///
+    /// The track is boxed to keep the size of [`Property`] down.
+    CurrentTrack(Option<Box<Track>>),
+    Chapter(Option<usize>),
+    EstimatedVfFps(Option<f64>),
+    /// The number of the current frame, which is `None` if no video is being decoded.
+    EstimatedFrameNumber(Option<usize>),
+    /// The total number of frames in the file, which is `None` if no video is being decoded.
+    EstimatedFrameCount(Option<usize>),
+    StreamOpenFilename(Option<String>),
+    FileFormat(Option<String>),
+    DemuxerViaNetwork(Option<bool>),
+    /// Whether video output is configured, e.g. to only show a video surface while there is video.
+    VoConfigured(Option<bool>),
+    /// Whether playback is paused to wait for the cache, which is `None` if nothing is playing.
+    PausedForCache(Option<bool>),
+    /// Whether playback is not progressing, e.g. because it is paused, buffering or seeking.
+    CoreIdle(Option<bool>),
+    /// Whether audio output is configured, i.e. whether audio is actually being played.
+    AoConfigured(Option<bool>),
+    ContainerFps(Option<f64>),
+    /// The current video bitrate in bits per second, which is `None` if there is no video track.
+    VideoBitrate(Option<f64>),
+    /// The current audio bitrate in bits per second, which is `None` if there is no audio track.
+    AudioBitrate(Option<f64>),
+    VideoZoom(f64),
+    VideoPanX(f64),
+    VideoPanY(f64),
+    /// The state of the demuxer cache, which is `None` if no file is loaded.
+    DemuxerCacheState(Option<DemuxerCacheState>),
+    /// The size of the OSD, which is `None` if there is no video window.
+    OsdDimensions(Option<OsdDimensions>),
+    /// The size of the video window relative to the video size, which is `None` if there is no video window.
+    WindowScale(Option<f64>),
+    /// The render timings of the video output, which is `None` if nothing has been rendered.
+    VoPasses(Option<RenderStats>),
+    /// How precise seeks are by default, `yes`, `no`, or a mode like `"absolute"` or `"default"`.
+    HrSeek(BoolOrString),
     Unknown {
         name: String,
         data: Option<MpvDataType>,
@@ -58,6 +122,18 @@ pub enum LoopProperty {
     No,
 }
 
+/// A value of a property that is either a flag, or one of a set of strings.
+///
+/// Some mpv properties accept `yes` and `no` next to other choices, like `hr-seek` with
+/// `yes`, `no`, `absolute` and `default`. mpv reports the flag values either as a bool or
+/// as `"yes"`/`"no"`, which are both parsed into [`BoolOrString::Bool`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BoolOrString {
+    Bool(bool),
+    String(String),
+}
+
 /// Parse a highlevel [`Property`] object from mpv data.
 ///
 /// This is intended to be used with the `data` field of
@@ -69,10 +145,7 @@ pub fn parse_property(name: &str, data: Option<MpvDataType>) -> Result<Property,
                 Some(MpvDataType::String(s)) => Some(s),
                 Some(MpvDataType::Null) => None,
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "String".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("String", data));
                 }
                 None => {
                     return Err(MpvError::MissingMpvData);
@@ -84,10 +157,7 @@ pub fn parse_property(name: &str, data: Option<MpvDataType>) -> Result<Property,
             let pause = match data {
                 Some(MpvDataType::Bool(b)) => b,
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "bool".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("bool", data));
                 }
                 None => {
                     return Err(MpvError::MissingMpvData);
@@ -100,10 +170,7 @@ pub fn parse_property(name: &str, data: Option<MpvDataType>) -> Result<Property,
                 Some(MpvDataType::Double(d)) => Some(d),
                 None | Some(MpvDataType::Null) => None,
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "f64".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("f64", data));
                 }
             };
             Ok(Property::PlaybackTime(playback_time))
@@ -113,108 +180,36 @@ pub fn parse_property(name: &str, data: Option<MpvDataType>) -> Result<Property,
                 Some(MpvDataType::Double(d)) => Some(d),
                 None | Some(MpvDataType::Null) => None,
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "f64".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("f64", data));
                 }
             };
             Ok(Property::Duration(duration))
         }
-        "metadata" => {
-            let metadata = match data {
-                Some(MpvDataType::HashMap(m)) => Some(m),
-                None | Some(MpvDataType::Null) => None,
-                Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "HashMap".to_owned(),
-                        received: data,
-                    });
-                }
-            };
-            Ok(Property::Metadata(metadata))
-        }
+        "metadata" => Ok(Property::Metadata(parse_metadata(data)?)),
+        "filtered-metadata" => Ok(Property::FilteredMetadata(parse_metadata(data)?)),
+        "chapter-metadata" => Ok(Property::ChapterMetadata(parse_metadata(data)?)),
         "playlist" => {
             let playlist = match data {
                 Some(MpvDataType::Array(a)) => mpv_array_to_playlist(&a)?,
                 None => Vec::new(),
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "Array".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("Array", data));
                 }
             };
             Ok(Property::Playlist(playlist))
         }
-        "playlist-pos" => {
-            let playlist_pos = match data {
-                Some(MpvDataType::Usize(u)) => Some(u),
-                Some(MpvDataType::MinusOne) => None,
-                Some(MpvDataType::Null) => None,
-                None => None,
-                Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "usize or -1".to_owned(),
-                        received: data,
-                    });
-                }
-            };
-            Ok(Property::PlaylistPos(playlist_pos))
-        }
-        "loop-file" => {
-            let loop_file = match data.to_owned() {
-                Some(MpvDataType::Usize(n)) => Some(LoopProperty::N(n)),
-                Some(MpvDataType::Bool(b)) => match b {
-                    true => Some(LoopProperty::Inf),
-                    false => Some(LoopProperty::No),
-                },
-                Some(MpvDataType::String(s)) => match s.as_str() {
-                    "inf" => Some(LoopProperty::Inf),
-                    _ => None,
-                },
-                _ => None,
-            }
-            .ok_or(match data {
-                Some(data) => MpvError::DataContainsUnexpectedType {
-                    expected_type: "'inf', bool, or usize".to_owned(),
-                    received: data,
-                },
-                None => MpvError::MissingMpvData,
-            })?;
-            Ok(Property::LoopFile(loop_file))
-        }
-        "loop-playlist" => {
-            let loop_playlist = match data.to_owned() {
-                Some(MpvDataType::Usize(n)) => Some(LoopProperty::N(n)),
-                Some(MpvDataType::Bool(b)) => match b {
-                    true => Some(LoopProperty::Inf),
-                    false => Some(LoopProperty::No),
-                },
-                Some(MpvDataType::String(s)) => match s.as_str() {
-                    "inf" => Some(LoopProperty::Inf),
-                    _ => None,
-                },
-                _ => None,
-            }
-            .ok_or(match data {
-                Some(data) => MpvError::DataContainsUnexpectedType {
-                    expected_type: "'inf', bool, or usize".to_owned(),
-                    received: data,
-                },
-                None => MpvError::MissingMpvData,
-            })?;
-
-            Ok(Property::LoopPlaylist(loop_playlist))
-        }
+        "playlist-pos" => Ok(Property::PlaylistPos(parse_optional_index(data)?)),
+        "playlist-playing-pos" => Ok(Property::PlaylistPlayingPos(parse_optional_index(data)?)),
+        "chapter" => Ok(Property::Chapter(parse_optional_index(data)?)),
+        "loop-file" => Ok(Property::LoopFile(parse_loop_property(data)?)),
+        "loop-playlist" => Ok(Property::LoopPlaylist(parse_loop_property(data)?)),
+        "ab-loop-count" => Ok(Property::AbLoopCount(parse_loop_property(data)?)),
+        "hr-seek" => Ok(Property::HrSeek(parse_bool_or_string(data)?)),
         "time-pos" => {
             let time_pos = match data {
                 Some(MpvDataType::Double(d)) => Some(d),
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "f64".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("f64", data));
                 }
                 None => None,
             };
@@ -225,23 +220,18 @@ pub fn parse_property(name: &str, data: Option<MpvDataType>) -> Result<Property,
             let time_remaining = match data {
                 Some(MpvDataType::Double(d)) => Some(d),
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "f64".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("f64", data));
                 }
                 None => None,
             };
             Ok(Property::TimeRemaining(time_remaining))
         }
+        "playtime-remaining" => Ok(Property::PlaytimeRemaining(parse_optional_f64(data)?)),
         "speed" => {
             let speed = match data {
                 Some(MpvDataType::Double(d)) => d,
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "f64".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("f64", data));
                 }
                 None => {
                     return Err(MpvError::MissingMpvData);
@@ -253,10 +243,7 @@ pub fn parse_property(name: &str, data: Option<MpvDataType>) -> Result<Property,
             let volume = match data {
                 Some(MpvDataType::Double(d)) => d,
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "f64".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("f64", data));
                 }
                 None => {
                     return Err(MpvError::MissingMpvData);
@@ -268,10 +255,7 @@ pub fn parse_property(name: &str, data: Option<MpvDataType>) -> Result<Property,
             let mute = match data {
                 Some(MpvDataType::Bool(b)) => b,
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "bool".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("bool", data));
                 }
                 None => {
                     return Err(MpvError::MissingMpvData);
@@ -279,19 +263,109 @@ pub fn parse_property(name: &str, data: Option<MpvDataType>) -> Result<Property,
             };
             Ok(Property::Mute(mute))
         }
+        "sub-visibility" => {
+            let visible = match data {
+                Some(MpvDataType::Bool(b)) => b,
+                Some(data) => {
+                    return Err(unexpected_data("bool", data));
+                }
+                None => {
+                    return Err(MpvError::MissingMpvData);
+                }
+            };
+            Ok(Property::SubVisibility(visible))
+        }
+        "idle-active" => {
+            let idle_active = match data {
+                Some(MpvDataType::Bool(b)) => b,
+                Some(data) => {
+                    return Err(unexpected_data("bool", data));
+                }
+                None => {
+                    return Err(MpvError::MissingMpvData);
+                }
+            };
+            Ok(Property::IdleActive(idle_active))
+        }
         "eof-reached" => {
             let eof_reached = match data {
                 Some(MpvDataType::Bool(b)) => b,
                 Some(data) => {
-                    return Err(MpvError::DataContainsUnexpectedType {
-                        expected_type: "bool".to_owned(),
-                        received: data,
-                    });
+                    return Err(unexpected_data("bool", data));
                 }
                 None => true,
             };
             Ok(Property::EofReached(eof_reached))
         }
+        "hwdec" => Ok(Property::Hwdec(parse_optional_string(data)?)),
+        "hwdec-current" => Ok(Property::HwdecCurrent(parse_optional_string(data)?)),
+        "track-list" => {
+            let track_list = match data {
+                Some(MpvDataType::Array(a)) => mpv_array_to_track_list(&a)?,
+                None => Vec::new(),
+                Some(data) => {
+                    return Err(unexpected_data("Array", data));
+                }
+            };
+            Ok(Property::TrackList(track_list))
+        }
+        "current-tracks/video" | "current-tracks/audio" | "current-tracks/sub" => {
+            let track = match data {
+                Some(MpvDataType::HashMap(map)) => Some(Box::new(mpv_data_to_track(&map)?)),
+                None | Some(MpvDataType::Null) => None,
+                Some(data) => {
+                    return Err(unexpected_data("HashMap", data));
+                }
+            };
+            Ok(Property::CurrentTrack(track))
+        }
+        "stream-open-filename" => Ok(Property::StreamOpenFilename(parse_optional_string(data)?)),
+        "file-format" => Ok(Property::FileFormat(parse_optional_string(data)?)),
+        "demuxer-via-network" => Ok(Property::DemuxerViaNetwork(parse_optional_bool(data)?)),
+        "vo-configured" => Ok(Property::VoConfigured(parse_optional_bool(data)?)),
+        "paused-for-cache" => Ok(Property::PausedForCache(parse_optional_bool(data)?)),
+        "core-idle" => Ok(Property::CoreIdle(parse_optional_bool(data)?)),
+        "ao-configured" => Ok(Property::AoConfigured(parse_optional_bool(data)?)),
+        "estimated-vf-fps" => Ok(Property::EstimatedVfFps(parse_optional_f64(data)?)),
+        "estimated-frame-number" => Ok(Property::EstimatedFrameNumber(parse_optional_usize(data)?)),
+        "estimated-frame-count" => Ok(Property::EstimatedFrameCount(parse_optional_usize(data)?)),
+        "container-fps" => Ok(Property::ContainerFps(parse_optional_f64(data)?)),
+        "video-bitrate" => Ok(Property::VideoBitrate(parse_optional_f64(data)?)),
+        "audio-bitrate" => Ok(Property::AudioBitrate(parse_optional_f64(data)?)),
+        "video-zoom" => Ok(Property::VideoZoom(parse_f64(data)?)),
+        "video-pan-x" => Ok(Property::VideoPanX(parse_f64(data)?)),
+        "video-pan-y" => Ok(Property::VideoPanY(parse_f64(data)?)),
+        "osd-dimensions" => {
+            let osd_dimensions = match data {
+                Some(MpvDataType::HashMap(map)) => Some(mpv_data_to_osd_dimensions(&map)?),
+                None | Some(MpvDataType::Null) => None,
+                Some(data) => {
+                    return Err(unexpected_data("HashMap", data));
+                }
+            };
+            Ok(Property::OsdDimensions(osd_dimensions))
+        }
+        "demuxer-cache-state" => {
+            let cache_state = match data {
+                Some(MpvDataType::HashMap(map)) => Some(mpv_data_to_demuxer_cache_state(&map)),
+                None | Some(MpvDataType::Null) => None,
+                Some(data) => {
+                    return Err(unexpected_data("HashMap", data));
+                }
+            };
+            Ok(Property::DemuxerCacheState(cache_state))
+        }
+        "window-scale" => Ok(Property::WindowScale(parse_optional_f64(data)?)),
+        "vo-passes" => {
+            let render_stats = match data {
+                Some(MpvDataType::HashMap(map)) => Some(mpv_data_to_render_stats(&map)),
+                None | Some(MpvDataType::Null) => None,
+                Some(data) => {
+                    return Err(unexpected_data("HashMap", data));
+                }
+            };
+            Ok(Property::VoPasses(render_stats))
+        }
         // TODO: add missing cases
         _ => Ok(Property::Unknown {
             name: name.to_owned(),
@@ -300,36 +374,141 @@ pub fn parse_property(name: &str, data: Option<MpvDataType>) -> Result<Property,
     }
 }
 
+/// Parse a [`LoopProperty`] from the data of properties like `loop-file`,
+/// which can be a number, a boolean, or the string `"inf"`.
+fn parse_loop_property(data: Option<MpvDataType>) -> Result<LoopProperty, MpvError> {
+    let expected_type = "'inf', bool, or usize";
+    let value = match data {
+        Some(MpvDataType::Usize(n)) => return Ok(LoopProperty::N(n)),
+        Some(MpvDataType::Bool(_) | MpvDataType::String(_)) => parse_bool_or_string(data)?,
+        Some(data) => return Err(unexpected_data(expected_type, data)),
+        None => return Err(MpvError::MissingMpvData),
+    };
+
+    match value {
+        BoolOrString::Bool(true) => Ok(LoopProperty::Inf),
+        BoolOrString::Bool(false) => Ok(LoopProperty::No),
+        BoolOrString::String(s) if s == "inf" => Ok(LoopProperty::Inf),
+        BoolOrString::String(s) => Err(unexpected_data(expected_type, MpvDataType::String(s))),
+    }
+}
+
+/// Parse the data of properties that are either a flag or a string, see [`BoolOrString`].
+fn parse_bool_or_string(data: Option<MpvDataType>) -> Result<BoolOrString, MpvError> {
+    match data {
+        Some(MpvDataType::Bool(b)) => Ok(BoolOrString::Bool(b)),
+        Some(MpvDataType::String(s)) => match s.as_str() {
+            "yes" => Ok(BoolOrString::Bool(true)),
+            "no" => Ok(BoolOrString::Bool(false)),
+            _ => Ok(BoolOrString::String(s)),
+        },
+        Some(data) => Err(unexpected_data("bool or String", data)),
+        None => Err(MpvError::MissingMpvData),
+    }
+}
+
+/// Create the error for when mpv sent data of the wrong type,
+/// with a separate error for when mpv sent `null`.
+fn unexpected_data(expected_type: &str, received: MpvDataType) -> MpvError {
+    match received {
+        MpvDataType::Null => MpvError::UnexpectedNull {
+            expected_type: expected_type.to_owned(),
+        },
+        received => MpvError::DataContainsUnexpectedType {
+            expected_type: expected_type.to_owned(),
+            received,
+        },
+    }
+}
+
+/// Parse the data of metadata maps like `metadata` and `chapter-metadata`.
+fn parse_metadata(
+    data: Option<MpvDataType>,
+) -> Result<Option<HashMap<String, MpvDataType>>, MpvError> {
+    match data {
+        Some(MpvDataType::HashMap(m)) => Ok(Some(m)),
+        None | Some(MpvDataType::Null) => Ok(None),
+        Some(data) => Err(unexpected_data("HashMap", data)),
+    }
+}
+
+/// Parse the data of number properties that always have a value.
+fn parse_f64(data: Option<MpvDataType>) -> Result<f64, MpvError> {
+    match data {
+        Some(MpvDataType::Double(d)) => Ok(d),
+        Some(MpvDataType::Usize(u)) => Ok(u as f64),
+        Some(data) => Err(unexpected_data("f64", data)),
+        None => Err(MpvError::MissingMpvData),
+    }
+}
+
+/// Parse the data of number properties that might be unavailable.
+fn parse_optional_f64(data: Option<MpvDataType>) -> Result<Option<f64>, MpvError> {
+    match data {
+        Some(MpvDataType::Double(d)) => Ok(Some(d)),
+        Some(MpvDataType::Usize(u)) => Ok(Some(u as f64)),
+        None | Some(MpvDataType::Null) => Ok(None),
+        Some(data) => Err(unexpected_data("f64", data)),
+    }
+}
+
+/// Parse the data of flag properties that might be unavailable.
+fn parse_optional_bool(data: Option<MpvDataType>) -> Result<Option<bool>, MpvError> {
+    match data {
+        Some(MpvDataType::Bool(b)) => Ok(Some(b)),
+        None | Some(MpvDataType::Null) => Ok(None),
+        Some(data) => Err(unexpected_data("bool", data)),
+    }
+}
+
+/// Parse the data of count properties that might be unavailable.
+fn parse_optional_usize(data: Option<MpvDataType>) -> Result<Option<usize>, MpvError> {
+    match data {
+        Some(MpvDataType::Usize(u)) => Ok(Some(u)),
+        None | Some(MpvDataType::Null) => Ok(None),
+        Some(data) => Err(unexpected_data("usize", data)),
+    }
+}
+
+/// Parse the data of index properties, where `-1` means that nothing is selected.
+fn parse_optional_index(data: Option<MpvDataType>) -> Result<Option<usize>, MpvError> {
+    match data {
+        Some(MpvDataType::Usize(u)) => Ok(Some(u)),
+        None | Some(MpvDataType::MinusOne) | Some(MpvDataType::Null) => Ok(None),
+        Some(data) => Err(unexpected_data("usize or -1", data)),
+    }
+}
+
+/// Parse the data of string properties that might be unavailable.
+fn parse_optional_string(data: Option<MpvDataType>) -> Result<Option<String>, MpvError> {
+    match data {
+        Some(MpvDataType::String(s)) => Ok(Some(s)),
+        None | Some(MpvDataType::Null) => Ok(None),
+        Some(data) => Err(unexpected_data("String", data)),
+    }
+}
+
 fn mpv_data_to_playlist_entry(
     map: &HashMap<String, MpvDataType>,
 ) -> Result<PlaylistEntry, MpvError> {
     let filename = match map.get("filename") {
         Some(MpvDataType::String(s)) => s.to_string(),
         Some(data) => {
-            return Err(MpvError::DataContainsUnexpectedType {
-                expected_type: "String".to_owned(),
-                received: data.clone(),
-            });
+            return Err(unexpected_data("String", data.clone()));
         }
         None => return Err(MpvError::MissingMpvData),
     };
     let title = match map.get("title") {
         Some(MpvDataType::String(s)) => Some(s.to_string()),
         Some(data) => {
-            return Err(MpvError::DataContainsUnexpectedType {
-                expected_type: "String".to_owned(),
-                received: data.clone(),
-            });
+            return Err(unexpected_data("String", data.clone()));
         }
         None => None,
     };
     let current = match map.get("current") {
         Some(MpvDataType::Bool(b)) => *b,
         Some(data) => {
-            return Err(MpvError::DataContainsUnexpectedType {
-                expected_type: "bool".to_owned(),
-                received: data.clone(),
-            });
+            return Err(unexpected_data("bool", data.clone()));
         }
         None => false,
     };
@@ -346,12 +525,612 @@ fn mpv_array_to_playlist(array: &[MpvDataType]) -> Result<Vec<PlaylistEntry>, Mp
         .iter()
         .map(|value| match value {
             MpvDataType::HashMap(map) => mpv_data_to_playlist_entry(map),
-            _ => Err(MpvError::DataContainsUnexpectedType {
-                expected_type: "HashMap".to_owned(),
-                received: value.clone(),
-            }),
+            _ => Err(unexpected_data("HashMap", value.clone())),
         })
         .enumerate()
         .map(|(id, entry)| entry.map(|entry| PlaylistEntry { id, ..entry }))
         .collect()
 }
+
+fn mpv_data_to_track(map: &HashMap<String, MpvDataType>) -> Result<Track, MpvError> {
+    let get_string = |key: &str| match map.get(key) {
+        Some(MpvDataType::String(s)) => Ok(Some(s.to_string())),
+        None | Some(MpvDataType::Null) => Ok(None),
+        Some(data) => Err(unexpected_data("String", data.clone())),
+    };
+    let get_bool = |key: &str| match map.get(key) {
+        Some(MpvDataType::Bool(b)) => Ok(*b),
+        None => Ok(false),
+        Some(data) => Err(unexpected_data("bool", data.clone())),
+    };
+
+    let id = match map.get("id") {
+        Some(MpvDataType::Usize(id)) => *id,
+        Some(data) => {
+            return Err(unexpected_data("usize", data.clone()));
+        }
+        None => return Err(MpvError::MissingMpvData),
+    };
+    let track_type = match get_string("type")?.as_deref() {
+        Some("video") => TrackType::Video,
+        Some("audio") => TrackType::Audio,
+        Some("sub") => TrackType::Sub,
+        Some(other) => TrackType::Unimplemented(other.to_owned()),
+        None => return Err(MpvError::MissingMpvData),
+    };
+
+    Ok(Track {
+        id,
+        track_type,
+        title: get_string("title")?,
+        lang: get_string("lang")?,
+        codec: get_string("codec")?,
+        selected: get_bool("selected")?,
+        default: get_bool("default")?,
+        forced: get_bool("forced")?,
+        external: get_bool("external")?,
+        external_filename: get_string("external-filename")?,
+        image: get_bool("image")?,
+        albumart: get_bool("albumart")?,
+    })
+}
+
+fn mpv_array_to_track_list(array: &[MpvDataType]) -> Result<Vec<Track>, MpvError> {
+    array
+        .iter()
+        .map(|value| match value {
+            MpvDataType::HashMap(map) => mpv_data_to_track(map),
+            _ => Err(unexpected_data("HashMap", value.clone())),
+        })
+        .collect()
+}
+
+/// Parse the `demuxer-cache-state` property.
+///
+/// The layout of this property has changed between mpv releases, so fields and ranges
+/// that are missing or malformed are skipped rather than treated as errors.
+fn mpv_data_to_demuxer_cache_state(map: &HashMap<String, MpvDataType>) -> DemuxerCacheState {
+    let get_f64 = |map: &HashMap<String, MpvDataType>, key: &str| match map.get(key) {
+        Some(MpvDataType::Double(d)) => Some(*d),
+        Some(MpvDataType::Usize(u)) => Some(*u as f64),
+        _ => None,
+    };
+
+    let seekable_ranges = match map.get("seekable-ranges") {
+        Some(MpvDataType::Array(ranges)) => ranges
+            .iter()
+            .filter_map(|range| match range {
+                MpvDataType::HashMap(range) => {
+                    Some((get_f64(range, "start")?, get_f64(range, "end")?))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    DemuxerCacheState {
+        seekable_ranges,
+        cache_end: get_f64(map, "cache-end"),
+        cache_duration: get_f64(map, "cache-duration"),
+    }
+}
+
+/// Parse the `vo-passes` property, skipping passes that are missing their description or timings.
+///
+/// Only the aggregate timings are kept, as the list of samples varies between mpv versions.
+fn mpv_data_to_render_stats(map: &HashMap<String, MpvDataType>) -> RenderStats {
+    let get_usize = |pass: &HashMap<String, MpvDataType>, key: &str| match pass.get(key) {
+        Some(MpvDataType::Usize(u)) => Some(*u),
+        _ => None,
+    };
+    let get_nanos = |pass: &HashMap<String, MpvDataType>, key: &str| {
+        get_usize(pass, key).map(|nanos| Duration::from_nanos(nanos as u64))
+    };
+    let passes = |key: &str| match map.get(key) {
+        Some(MpvDataType::Array(passes)) => passes
+            .iter()
+            .filter_map(|pass| match pass {
+                MpvDataType::HashMap(pass) => Some(RenderPass {
+                    desc: match pass.get("desc") {
+                        Some(MpvDataType::String(desc)) => desc.to_owned(),
+                        _ => return None,
+                    },
+                    last: get_nanos(pass, "last")?,
+                    avg: get_nanos(pass, "avg")?,
+                    peak: get_nanos(pass, "peak")?,
+                    count: get_usize(pass, "count").unwrap_or_default(),
+                }),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    RenderStats {
+        fresh: passes("fresh"),
+        redraw: passes("redraw"),
+    }
+}
+
+fn mpv_data_to_osd_dimensions(
+    map: &HashMap<String, MpvDataType>,
+) -> Result<OsdDimensions, MpvError> {
+    let get_usize = |key: &str| match map.get(key) {
+        Some(MpvDataType::Usize(u)) => Ok(*u),
+        Some(data) => Err(unexpected_data("usize", data.clone())),
+        None => Err(MpvError::MissingMpvData),
+    };
+    let get_f64 = |key: &str| parse_f64(map.get(key).cloned());
+
+    Ok(OsdDimensions {
+        width: get_usize("w")?,
+        height: get_usize("h")?,
+        par: get_f64("par")?,
+        aspect: get_f64("aspect")?,
+        margin_top: get_usize("mt")?,
+        margin_bottom: get_usize("mb")?,
+        margin_left: get_usize("ml")?,
+        margin_right: get_usize("mr")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_loop_properties() {
+        let string = |s: &str| Some(MpvDataType::String(s.to_owned()));
+
+        assert_eq!(
+            parse_property("loop-file", Some(MpvDataType::Bool(false))),
+            Ok(Property::LoopFile(LoopProperty::No))
+        );
+        assert_eq!(
+            parse_property("loop-playlist", Some(MpvDataType::Bool(true))),
+            Ok(Property::LoopPlaylist(LoopProperty::Inf))
+        );
+        assert_eq!(
+            parse_property("loop-file", string("inf")),
+            Ok(Property::LoopFile(LoopProperty::Inf))
+        );
+        assert_eq!(
+            parse_property("loop-playlist", string("no")),
+            Ok(Property::LoopPlaylist(LoopProperty::No))
+        );
+        assert_eq!(
+            parse_property("loop-file", Some(MpvDataType::Usize(2))),
+            Ok(Property::LoopFile(LoopProperty::N(2)))
+        );
+        assert_eq!(
+            parse_property("loop-playlist", string("force")),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "'inf', bool, or usize".to_owned(),
+                received: MpvDataType::String("force".to_owned())
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_bool_or_string() {
+        let string = |s: &str| Some(MpvDataType::String(s.to_owned()));
+
+        assert_eq!(
+            parse_property("hr-seek", Some(MpvDataType::Bool(true))),
+            Ok(Property::HrSeek(BoolOrString::Bool(true)))
+        );
+        assert_eq!(
+            parse_property("hr-seek", string("no")),
+            Ok(Property::HrSeek(BoolOrString::Bool(false)))
+        );
+        assert_eq!(
+            parse_property("hr-seek", string("absolute")),
+            Ok(Property::HrSeek(BoolOrString::String(
+                "absolute".to_owned()
+            )))
+        );
+        assert_eq!(
+            parse_property("hr-seek", Some(MpvDataType::Usize(1))),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "bool or String".to_owned(),
+                received: MpvDataType::Usize(1)
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_ab_loop_count() {
+        assert_eq!(
+            parse_property(
+                "ab-loop-count",
+                Some(MpvDataType::String("inf".to_string()))
+            ),
+            Ok(Property::AbLoopCount(LoopProperty::Inf))
+        );
+        assert_eq!(
+            parse_property("ab-loop-count", Some(MpvDataType::Usize(3))),
+            Ok(Property::AbLoopCount(LoopProperty::N(3)))
+        );
+        assert_eq!(
+            parse_property("ab-loop-count", None),
+            Err(MpvError::MissingMpvData)
+        );
+    }
+
+    #[test]
+    fn test_parse_playlist_positions() {
+        assert_eq!(
+            parse_property("playlist-pos", Some(MpvDataType::Usize(2))),
+            Ok(Property::PlaylistPos(Some(2)))
+        );
+        assert_eq!(
+            parse_property("playlist-pos", Some(MpvDataType::MinusOne)),
+            Ok(Property::PlaylistPos(None))
+        );
+        assert_eq!(
+            parse_property("playlist-playing-pos", Some(MpvDataType::Usize(1))),
+            Ok(Property::PlaylistPlayingPos(Some(1)))
+        );
+        assert_eq!(
+            parse_property("playlist-playing-pos", Some(MpvDataType::MinusOne)),
+            Ok(Property::PlaylistPlayingPos(None))
+        );
+    }
+
+    #[test]
+    fn test_parse_demuxer_cache_state() {
+        let range = |start: f64, end: f64| {
+            MpvDataType::HashMap(HashMap::from([
+                ("start".to_string(), MpvDataType::Double(start)),
+                ("end".to_string(), MpvDataType::Double(end)),
+            ]))
+        };
+        let cache_state = HashMap::from([
+            (
+                "seekable-ranges".to_string(),
+                MpvDataType::Array(vec![range(0.0, 12.5), range(30.0, 45.25)]),
+            ),
+            ("bof-cached".to_string(), MpvDataType::Bool(true)),
+            ("eof-cached".to_string(), MpvDataType::Bool(false)),
+            ("cache-end".to_string(), MpvDataType::Double(45.25)),
+            ("reader-pts".to_string(), MpvDataType::Double(31.0)),
+            ("cache-duration".to_string(), MpvDataType::Double(14.25)),
+            ("fw-bytes".to_string(), MpvDataType::Usize(1048576)),
+        ]);
+
+        assert_eq!(
+            parse_property(
+                "demuxer-cache-state",
+                Some(MpvDataType::HashMap(cache_state))
+            ),
+            Ok(Property::DemuxerCacheState(Some(DemuxerCacheState {
+                seekable_ranges: vec![(0.0, 12.5), (30.0, 45.25)],
+                cache_end: Some(45.25),
+                cache_duration: Some(14.25),
+            })))
+        );
+        assert_eq!(
+            parse_property("demuxer-cache-state", None),
+            Ok(Property::DemuxerCacheState(None))
+        );
+    }
+
+    #[test]
+    fn test_parse_vo_passes() {
+        let pass = |desc: &str, last: usize, avg: usize, peak: usize| {
+            MpvDataType::HashMap(HashMap::from([
+                ("desc".to_string(), MpvDataType::String(desc.to_string())),
+                ("last".to_string(), MpvDataType::Usize(last)),
+                ("avg".to_string(), MpvDataType::Usize(avg)),
+                ("peak".to_string(), MpvDataType::Usize(peak)),
+                ("count".to_string(), MpvDataType::Usize(3)),
+                (
+                    "samples".to_string(),
+                    MpvDataType::Array(vec![MpvDataType::Usize(last); 3]),
+                ),
+            ]))
+        };
+        let vo_passes = HashMap::from([
+            (
+                "fresh".to_string(),
+                MpvDataType::Array(vec![
+                    pass("scaling (luma)", 120_000, 110_000, 250_000),
+                    // Passes without a description are skipped
+                    MpvDataType::HashMap(HashMap::from([(
+                        "avg".to_string(),
+                        MpvDataType::Usize(1),
+                    )])),
+                ]),
+            ),
+            (
+                "redraw".to_string(),
+                MpvDataType::Array(vec![pass("output to screen", 40_000, 45_000, 90_000)]),
+            ),
+        ]);
+
+        let render_pass = |desc: &str, last: u64, avg: u64, peak: u64| RenderPass {
+            desc: desc.to_string(),
+            last: Duration::from_micros(last),
+            avg: Duration::from_micros(avg),
+            peak: Duration::from_micros(peak),
+            count: 3,
+        };
+        assert_eq!(
+            parse_property("vo-passes", Some(MpvDataType::HashMap(vo_passes))),
+            Ok(Property::VoPasses(Some(RenderStats {
+                fresh: vec![render_pass("scaling (luma)", 120, 110, 250)],
+                redraw: vec![render_pass("output to screen", 40, 45, 90)],
+            })))
+        );
+        assert_eq!(
+            parse_property("vo-passes", None),
+            Ok(Property::VoPasses(None))
+        );
+    }
+
+    #[test]
+    fn test_parse_osd_dimensions() {
+        let map = HashMap::from([
+            ("w".to_string(), MpvDataType::Usize(1920)),
+            ("h".to_string(), MpvDataType::Usize(1080)),
+            ("par".to_string(), MpvDataType::Double(1.0)),
+            ("aspect".to_string(), MpvDataType::Double(1.777778)),
+            ("mt".to_string(), MpvDataType::Usize(0)),
+            ("mb".to_string(), MpvDataType::Usize(0)),
+            ("ml".to_string(), MpvDataType::Usize(240)),
+            ("mr".to_string(), MpvDataType::Usize(240)),
+        ]);
+        assert_eq!(
+            parse_property("osd-dimensions", Some(MpvDataType::HashMap(map))),
+            Ok(Property::OsdDimensions(Some(OsdDimensions {
+                width: 1920,
+                height: 1080,
+                par: 1.0,
+                aspect: 1.777778,
+                margin_top: 0,
+                margin_bottom: 0,
+                margin_left: 240,
+                margin_right: 240,
+            })))
+        );
+        assert_eq!(
+            parse_property("osd-dimensions", None),
+            Ok(Property::OsdDimensions(None))
+        );
+    }
+
+    #[test]
+    fn test_parse_hwdec() {
+        assert_eq!(
+            parse_property("hwdec", Some(MpvDataType::String("auto-copy".to_string()))),
+            Ok(Property::Hwdec(Some("auto-copy".to_string())))
+        );
+        assert_eq!(
+            parse_property("hwdec", Some(MpvDataType::String("no".to_string()))),
+            Ok(Property::Hwdec(Some("no".to_string())))
+        );
+        assert_eq!(
+            parse_property(
+                "hwdec-current",
+                Some(MpvDataType::String("vaapi".to_string()))
+            ),
+            Ok(Property::HwdecCurrent(Some("vaapi".to_string())))
+        );
+        assert_eq!(
+            parse_property("hwdec-current", None),
+            Ok(Property::HwdecCurrent(None))
+        );
+    }
+
+    #[test]
+    fn test_parse_bitrates() {
+        assert_eq!(
+            parse_property("video-bitrate", Some(MpvDataType::Double(4_500_000.0))),
+            Ok(Property::VideoBitrate(Some(4_500_000.0)))
+        );
+        assert_eq!(
+            parse_property("audio-bitrate", Some(MpvDataType::Usize(128_000))),
+            Ok(Property::AudioBitrate(Some(128_000.0)))
+        );
+        assert_eq!(
+            parse_property("video-bitrate", None),
+            Ok(Property::VideoBitrate(None))
+        );
+        assert_eq!(
+            parse_property("audio-bitrate", Some(MpvDataType::Null)),
+            Ok(Property::AudioBitrate(None))
+        );
+        assert_eq!(
+            parse_property(
+                "audio-bitrate",
+                Some(MpvDataType::String("128k".to_owned()))
+            ),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "f64".to_owned(),
+                received: MpvDataType::String("128k".to_owned())
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_frame_numbers() {
+        assert_eq!(
+            parse_property("estimated-frame-number", Some(MpvDataType::Usize(719))),
+            Ok(Property::EstimatedFrameNumber(Some(719)))
+        );
+        assert_eq!(
+            parse_property("estimated-frame-count", Some(MpvDataType::Usize(43157))),
+            Ok(Property::EstimatedFrameCount(Some(43157)))
+        );
+        assert_eq!(
+            parse_property("estimated-frame-number", None),
+            Ok(Property::EstimatedFrameNumber(None))
+        );
+        assert_eq!(
+            parse_property("estimated-frame-count", Some(MpvDataType::Double(1.5))),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "usize".to_owned(),
+                received: MpvDataType::Double(1.5)
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_output_configured() {
+        assert_eq!(
+            parse_property("vo-configured", Some(MpvDataType::Bool(true))),
+            Ok(Property::VoConfigured(Some(true)))
+        );
+        assert_eq!(
+            parse_property("ao-configured", Some(MpvDataType::Bool(false))),
+            Ok(Property::AoConfigured(Some(false)))
+        );
+        assert_eq!(
+            parse_property("ao-configured", None),
+            Ok(Property::AoConfigured(None))
+        );
+        assert_eq!(
+            parse_property("vo-configured", Some(MpvDataType::String("yes".to_owned()))),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "bool".to_owned(),
+                received: MpvDataType::String("yes".to_owned())
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_pause_state() {
+        assert_eq!(
+            parse_property("paused-for-cache", Some(MpvDataType::Bool(true))),
+            Ok(Property::PausedForCache(Some(true)))
+        );
+        assert_eq!(
+            parse_property("paused-for-cache", None),
+            Ok(Property::PausedForCache(None))
+        );
+        assert_eq!(
+            parse_property("core-idle", Some(MpvDataType::Bool(false))),
+            Ok(Property::CoreIdle(Some(false)))
+        );
+    }
+
+    #[test]
+    fn test_parse_sub_visibility() {
+        assert_eq!(
+            parse_property("sub-visibility", Some(MpvDataType::Bool(false))),
+            Ok(Property::SubVisibility(false))
+        );
+        assert_eq!(
+            parse_property("sub-visibility", None),
+            Err(MpvError::MissingMpvData)
+        );
+        assert_eq!(
+            parse_property("sub-visibility", Some(MpvDataType::Usize(1))),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "bool".to_owned(),
+                received: MpvDataType::Usize(1)
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_playtime_remaining() {
+        assert_eq!(
+            parse_property("playtime-remaining", Some(MpvDataType::Double(45.5))),
+            Ok(Property::PlaytimeRemaining(Some(45.5)))
+        );
+        assert_eq!(
+            parse_property("playtime-remaining", None),
+            Ok(Property::PlaytimeRemaining(None))
+        );
+        assert_eq!(
+            parse_property("playtime-remaining", Some(MpvDataType::Null)),
+            Ok(Property::PlaytimeRemaining(None))
+        );
+        assert_eq!(
+            parse_property(
+                "playtime-remaining",
+                Some(MpvDataType::String("1".to_owned()))
+            ),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "f64".to_owned(),
+                received: MpvDataType::String("1".to_owned())
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_fps() {
+        assert_eq!(
+            parse_property("estimated-vf-fps", Some(MpvDataType::Double(23.976))),
+            Ok(Property::EstimatedVfFps(Some(23.976)))
+        );
+        assert_eq!(
+            parse_property("container-fps", Some(MpvDataType::Double(25.0))),
+            Ok(Property::ContainerFps(Some(25.0)))
+        );
+        assert_eq!(
+            parse_property("estimated-vf-fps", Some(MpvDataType::Null)),
+            Ok(Property::EstimatedVfFps(None))
+        );
+        assert_eq!(
+            parse_property("container-fps", None),
+            Ok(Property::ContainerFps(None))
+        );
+        assert_eq!(
+            parse_property("container-fps", Some(MpvDataType::Bool(true))),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "f64".to_owned(),
+                received: MpvDataType::Bool(true)
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_file_format_and_demuxer_via_network() {
+        assert_eq!(
+            parse_property(
+                "file-format",
+                Some(MpvDataType::String("matroska,webm".to_string()))
+            ),
+            Ok(Property::FileFormat(Some("matroska,webm".to_string())))
+        );
+        assert_eq!(
+            parse_property("file-format", None),
+            Ok(Property::FileFormat(None))
+        );
+        assert_eq!(
+            parse_property("demuxer-via-network", Some(MpvDataType::Bool(true))),
+            Ok(Property::DemuxerViaNetwork(Some(true)))
+        );
+        assert_eq!(
+            parse_property("demuxer-via-network", None),
+            Ok(Property::DemuxerViaNetwork(None))
+        );
+        assert_eq!(
+            parse_property("demuxer-via-network", Some(MpvDataType::Usize(1))),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "bool".to_owned(),
+                received: MpvDataType::Usize(1)
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_null_is_distinct_from_wrong_type() {
+        assert_eq!(
+            parse_property("volume", Some(MpvDataType::Null)),
+            Err(MpvError::UnexpectedNull {
+                expected_type: "f64".to_owned()
+            })
+        );
+        assert_eq!(
+            parse_property("volume", Some(MpvDataType::Bool(true))),
+            Err(MpvError::DataContainsUnexpectedType {
+                expected_type: "f64".to_owned(),
+                received: MpvDataType::Bool(true)
+            })
+        );
+    }
+}
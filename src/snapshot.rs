@@ -0,0 +1,285 @@
+//! Saving and restoring the playback state of an mpv instance.
+
+use std::{collections::HashMap, future::Future, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{
+    LoopProperty, Mpv, MpvCommand, MpvError, PlaylistAddOptions, Property, SeekOptions,
+    SeekPrecision, message_parser::json_to_value, parse_property,
+};
+
+/// The properties read by [`Mpv::snapshot`], in the order they are requested.
+const SNAPSHOT_PROPERTIES: [&str; 9] = [
+    "path",
+    "time-pos",
+    "pause",
+    "volume",
+    "speed",
+    "mute",
+    "loop-file",
+    "loop-playlist",
+    "playlist-pos",
+];
+
+/// The file that was playing when a [`PlaybackSnapshot`] was taken.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NowPlaying {
+    pub path: String,
+    pub time_pos: Option<f64>,
+}
+
+/// The playback state of mpv at some point in time, see [`Mpv::snapshot`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaybackSnapshot {
+    /// The current file, or `None` if mpv was idle.
+    pub now_playing: Option<NowPlaying>,
+    pub pause: bool,
+    pub volume: f64,
+    pub speed: f64,
+    pub mute: bool,
+    pub loop_file: LoopProperty,
+    pub loop_playlist: LoopProperty,
+    /// The position of the current file in the playlist. This is not restored by [`Mpv::restore`].
+    pub playlist_pos: Option<usize>,
+}
+
+impl Mpv {
+    /// Read the current playback state as a serializable [`PlaybackSnapshot`].
+    ///
+    /// All properties are read at once with [`Mpv::get_properties`].
+    /// The snapshot can be stored, and restored later with [`Mpv::restore`].
+    pub async fn snapshot(&self) -> Result<PlaybackSnapshot, MpvError> {
+        let values = self.get_properties(&SNAPSHOT_PROPERTIES).await?;
+        let properties: Vec<Property> = SNAPSHOT_PROPERTIES
+            .iter()
+            .zip(values)
+            .map(|(name, value)| {
+                let data = value.as_ref().map(json_to_value).transpose()?;
+                match (*name, data) {
+                    // mpv reports the path as unavailable while idle
+                    ("path", None) => Ok(Property::Path(None)),
+                    (name, data) => parse_property(name, data),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        let Ok(
+            [
+                Property::Path(path),
+                Property::TimePos(time_pos),
+                Property::Pause(pause),
+                Property::Volume(volume),
+                Property::Speed(speed),
+                Property::Mute(mute),
+                Property::LoopFile(loop_file),
+                Property::LoopPlaylist(loop_playlist),
+                Property::PlaylistPos(playlist_pos),
+            ],
+        ) = <[Property; SNAPSHOT_PROPERTIES.len()]>::try_from(properties)
+        else {
+            return Err(MpvError::Other(
+                "mpv did not return the expected snapshot properties".to_owned(),
+            ));
+        };
+
+        Ok(PlaybackSnapshot {
+            now_playing: path.map(|path| NowPlaying { path, time_pos }),
+            pause,
+            volume,
+            speed,
+            mute,
+            loop_file,
+            loop_playlist,
+            playlist_pos,
+        })
+    }
+
+    /// Pause playback while running `f`, and then restore the previous pause state.
+    ///
+    /// If playback was already paused, it stays paused. Otherwise it is resumed after `f`
+    /// finishes, even if `f` returns an error. If the returned future is dropped before
+    /// completion, playback is resumed in the background.
+    ///
+    /// If `f` succeeds but resuming playback fails, the error from resuming is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     mpv.with_paused(|| async {
+    ///         mpv.run_command_raw("frame-step", &[]).await?;
+    ///         mpv.get_property::<f64>("time-pos").await
+    ///     })
+    ///     .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn with_paused<F, Fut, T>(&self, f: F) -> Result<T, MpvError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, MpvError>>,
+    {
+        let was_paused: bool = self
+            .get_property("pause")
+            .await?
+            .ok_or(MpvError::MissingMpvData)?;
+
+        let guard = if was_paused {
+            None
+        } else {
+            self.set_property("pause", true).await?;
+            Some(UnpauseGuard {
+                mpv: Some(self.clone()),
+            })
+        };
+
+        let result = f().await;
+
+        match guard {
+            Some(guard) => {
+                let unpause_result = guard.unpause().await;
+                let value = result?;
+                unpause_result.map(|_| value)
+            }
+            None => result,
+        }
+    }
+
+    /// Restore a [`PlaybackSnapshot`] taken with [`Mpv::snapshot`].
+    ///
+    /// The state is restored in this order:
+    ///
+    /// 1. `pause`, `volume`, `speed`, `mute`, `loop_file` and `loop_playlist` are set
+    ///    at once with [`Mpv::set_properties`], so that the file does not start playing
+    ///    with the wrong settings.
+    /// 2. If a file was playing, it replaces the current playlist, and starts at the
+    ///    saved position through the per-file `start` option. This avoids seeking before
+    ///    the file has loaded.
+    ///
+    /// The playlist itself is not part of the snapshot, so `playlist_pos` is not restored.
+    /// This function returns once mpv has accepted the commands, not when the file has loaded.
+    pub async fn restore(&self, snapshot: &PlaybackSnapshot) -> Result<(), MpvError> {
+        self.set_properties(&[
+            ("pause", json!(snapshot.pause)),
+            ("volume", json!(snapshot.volume)),
+            ("speed", json!(snapshot.speed)),
+            ("mute", json!(snapshot.mute)),
+            ("loop-file", loop_property_value(&snapshot.loop_file)),
+            (
+                "loop-playlist",
+                loop_property_value(&snapshot.loop_playlist),
+            ),
+        ])
+        .await?;
+
+        let Some(now_playing) = &snapshot.now_playing else {
+            return Ok(());
+        };
+
+        match now_playing.time_pos {
+            Some(time_pos) => {
+                self.run_command(MpvCommand::LoadFileWithOptions {
+                    file: now_playing.path.clone(),
+                    option: PlaylistAddOptions::Replace,
+                    options: HashMap::from([("start".to_string(), time_pos.to_string())]),
+                })
+                .await
+            }
+            None => {
+                self.run_command(MpvCommand::LoadFile {
+                    file: now_playing.path.clone(),
+                    option: PlaylistAddOptions::Replace,
+                })
+                .await
+            }
+        }
+    }
+
+    /// Seek, set the playback speed and set the pause state, in that order.
+    ///
+    /// Each step is skipped if its argument is `None`, and awaited before the next one
+    /// starts. Seeking happens first, so that unpausing does not briefly show
+    /// the old position. The seek is absolute and exact.
+    ///
+    /// Returns the first error, without running the remaining steps.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     mpv.apply_playback_state(Some(Duration::from_secs(90)), Some(1.5), Some(false))
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn apply_playback_state(
+        &self,
+        pos: Option<Duration>,
+        speed: Option<f64>,
+        paused: Option<bool>,
+    ) -> Result<(), MpvError> {
+        if let Some(pos) = pos {
+            self.run_command(MpvCommand::Seek {
+                seconds: pos.as_secs_f64(),
+                option: SeekOptions::Absolute.into(),
+                precision: SeekPrecision::Exact,
+            })
+            .await?;
+        }
+        if let Some(speed) = speed {
+            self.set_property("speed", speed).await?;
+        }
+        if let Some(paused) = paused {
+            self.set_property("pause", paused).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Unpauses mpv when dropped, unless disarmed.
+///
+/// This makes sure that [`Mpv::with_paused`] restores the pause state even if the
+/// future is cancelled, or the closure panics.
+struct UnpauseGuard {
+    mpv: Option<Mpv>,
+}
+
+impl UnpauseGuard {
+    /// Unpause mpv now, and disarm the guard.
+    async fn unpause(mut self) -> Result<(), MpvError> {
+        match self.mpv.take() {
+            Some(mpv) => mpv.set_property("pause", false).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for UnpauseGuard {
+    fn drop(&mut self) {
+        if let (Some(mpv), Ok(handle)) = (self.mpv.take(), tokio::runtime::Handle::try_current()) {
+            handle.spawn(async move {
+                if let Err(err) = mpv.set_property("pause", false).await {
+                    log::debug!("Failed to restore pause state: {:?}", err);
+                }
+            });
+        }
+    }
+}
+
+/// Convert a [`LoopProperty`] to the value mpv expects for `loop-file` and `loop-playlist`.
+fn loop_property_value(property: &LoopProperty) -> Value {
+    match property {
+        LoopProperty::N(n) => json!(n),
+        LoopProperty::Inf => json!("inf"),
+        LoopProperty::No => json!("no"),
+    }
+}
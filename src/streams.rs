@@ -0,0 +1,570 @@
+//! Specialized streams built on top of [`Mpv::get_event_stream`].
+
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+};
+
+use futures::{Stream, StreamExt, future, stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Event, EventLogMessageLevel, Mpv, MpvCommand, MpvDataType, MpvError, Playlist, Property,
+    parse_property,
+};
+
+/// The first observe id used by [`Mpv::property_stream`].
+///
+/// Stream ids count upwards from here, to stay clear of the ids that users pick
+/// for [`MpvCommand::Observe`]. mpv stores ids as doubles, so they must stay below 2^53.
+pub(crate) const FIRST_STREAM_OBSERVE_ID: u64 = 1 << 32;
+
+/// A log message from mpv, as `(level, prefix, text)`.
+pub type LogMessage = (EventLogMessageLevel, String, String);
+
+/// The overall playback status, see [`Mpv::state_stream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlaybackStatus {
+    /// A file is loaded and playing.
+    Playing,
+    /// A file is loaded, but paused.
+    Paused,
+    /// No file is loaded, or playback has reached the end of the file.
+    Stopped,
+}
+
+/// Derives the [`PlaybackStatus`] from the `pause`, `idle-active` and `eof-reached` properties.
+#[derive(Debug, Default)]
+struct PlaybackStatusTracker {
+    pause: Option<bool>,
+    idle_active: Option<bool>,
+    eof_reached: Option<bool>,
+    last_status: Option<PlaybackStatus>,
+}
+
+impl PlaybackStatusTracker {
+    /// Update the tracked properties, and return the status if it changed.
+    ///
+    /// Nothing is returned until all three properties have been received.
+    fn update(&mut self, property: Property) -> Option<PlaybackStatus> {
+        match property {
+            Property::Pause(pause) => self.pause = Some(pause),
+            Property::IdleActive(idle_active) => self.idle_active = Some(idle_active),
+            Property::EofReached(eof_reached) => self.eof_reached = Some(eof_reached),
+            _ => return None,
+        }
+
+        let status = match (self.pause?, self.idle_active?, self.eof_reached?) {
+            (_, true, _) | (_, _, true) => PlaybackStatus::Stopped,
+            (true, false, false) => PlaybackStatus::Paused,
+            (false, false, false) => PlaybackStatus::Playing,
+        };
+        if self.last_status == Some(status) {
+            return None;
+        }
+        self.last_status = Some(status);
+        Some(status)
+    }
+}
+
+/// The changes between two successive values of the `metadata` property,
+/// see [`Mpv::metadata_diff_stream`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetadataDiff {
+    /// The keys that were added, or whose value changed, with their new values.
+    pub changed: HashMap<String, MpvDataType>,
+    /// The keys that were removed.
+    pub removed: Vec<String>,
+}
+
+/// Remembers the previous metadata, to compute the [`MetadataDiff`] to the next one.
+#[derive(Debug, Default)]
+struct MetadataTracker {
+    previous: HashMap<String, MpvDataType>,
+    seen_first: bool,
+}
+
+impl MetadataTracker {
+    /// Replace the remembered metadata, and return what changed, if anything.
+    /// The first update is always returned, even if it is empty.
+    fn update(&mut self, metadata: HashMap<String, MpvDataType>) -> Option<MetadataDiff> {
+        let changed: HashMap<_, _> = metadata
+            .iter()
+            .filter(|(key, value)| self.previous.get(*key) != Some(*value))
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect();
+        let mut removed: Vec<_> = self
+            .previous
+            .keys()
+            .filter(|key| !metadata.contains_key(*key))
+            .cloned()
+            .collect();
+        removed.sort();
+
+        self.previous = metadata;
+        let first = !self.seen_first;
+        self.seen_first = true;
+        if !first && changed.is_empty() && removed.is_empty() {
+            return None;
+        }
+        Some(MetadataDiff { changed, removed })
+    }
+}
+
+/// Stream returned by [`Mpv::log_stream`].
+///
+/// Log forwarding is disabled again when the stream is dropped.
+pub struct LogStream {
+    mpv: Mpv,
+    inner: Pin<Box<dyn Stream<Item = Result<LogMessage, MpvError>> + Send>>,
+}
+
+impl Stream for LogStream {
+    type Item = Result<LogMessage, MpvError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl Drop for LogStream {
+    fn drop(&mut self) {
+        // Drop can not be async, so the command is sent from a separate task.
+        // If the runtime is already gone, so is the connection, and there is nothing to disable.
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let mpv = self.mpv.clone();
+            handle.spawn(async move {
+                if let Err(err) = mpv.run_command(MpvCommand::RequestLogMessages(None)).await {
+                    log::debug!("Failed to disable log messages: {:?}", err);
+                }
+            });
+        }
+    }
+}
+
+/// Stream returned by [`Mpv::property_stream`] and the typed streams built on it,
+/// like [`Mpv::chapter_stream`].
+///
+/// Yields the current value of the observed property right away, and then every change to it.
+/// The property is unobserved again when the stream is dropped.
+pub struct PropertyStream<T> {
+    mpv: Mpv,
+    id: u64,
+    inner: Pin<Box<dyn Stream<Item = Result<T, MpvError>> + Send>>,
+}
+
+impl<T> Stream for PropertyStream<T> {
+    type Item = Result<T, MpvError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl<T> Drop for PropertyStream<T> {
+    fn drop(&mut self) {
+        // See the Drop implementation of LogStream.
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let mpv = self.mpv.clone();
+            let id = self.id;
+            handle.spawn(async move {
+                if let Err(err) = mpv.run_command(MpvCommand::Unobserve(id)).await {
+                    log::debug!("Failed to unobserve property stream {}: {:?}", id, err);
+                }
+            });
+        }
+    }
+}
+
+impl Mpv {
+    /// Enable forwarding of mpv's log messages at the given level (and above),
+    /// and create a stream of `(level, prefix, text)` tuples.
+    ///
+    /// Log forwarding is disabled when the stream is dropped. Since mpv only keeps one
+    /// log level per connection, there should only be one active log stream per connection.
+    ///
+    /// # Example
+    /// ```
+    /// use futures::StreamExt;
+    /// use mpvipc_async::{EventLogMessageLevel, Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     let mut logs = mpv.log_stream(EventLogMessageLevel::Warn).await?;
+    ///     while let Some(Ok((level, prefix, text))) = logs.next().await {
+    ///         println!("[{}] {:?}: {}", prefix, level, text.trim_end());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn log_stream(&self, level: EventLogMessageLevel) -> Result<LogStream, MpvError> {
+        // Subscribe before enabling forwarding, to not miss the first messages. Replayed
+        // messages are skipped, since they were forwarded for another level, or another stream.
+        let events = self.live_event_stream();
+        self.run_command(MpvCommand::RequestLogMessages(Some(level)))
+            .await?;
+
+        let inner = events.filter_map(|event| async move {
+            match event {
+                Ok(Event::LogMessage {
+                    prefix,
+                    level,
+                    text,
+                }) => Some(Ok((level, prefix, text))),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            }
+        });
+
+        Ok(LogStream {
+            mpv: self.clone(),
+            inner: Box::pin(inner),
+        })
+    }
+
+    /// Observe a property, and create a stream of its parsed values.
+    ///
+    /// The stream yields the current value first, followed by every change.
+    /// Each stream observes the property with its own id, so several streams can
+    /// observe the same property independently. The property is unobserved when
+    /// the stream is dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use futures::StreamExt;
+    /// use mpvipc_async::{Mpv, MpvError};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MpvError> {
+    ///     let mpv = Mpv::connect("/tmp/mpvsocket").await?;
+    ///     let mut volume = mpv.property_stream("volume").await?;
+    ///     while let Some(Ok(property)) = volume.next().await {
+    ///         println!("{:?}", property);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn property_stream(
+        &self,
+        property: &str,
+    ) -> Result<PropertyStream<Property>, MpvError> {
+        self.typed_property_stream(property, Ok).await
+    }
+
+    /// Observe the `chapter` property, and create a stream of the current chapter index.
+    ///
+    /// The stream yields `None` while there is no current chapter, e.g. before the first
+    /// chapter starts, or when the file has no chapters. See [`Mpv::property_stream`].
+    pub async fn chapter_stream(&self) -> Result<PropertyStream<Option<usize>>, MpvError> {
+        self.typed_property_stream("chapter", |property| match property {
+            Property::Chapter(chapter) => Ok(chapter),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        })
+        .await
+    }
+
+    /// Observe the `playlist` property, and create a stream of the playlist,
+    /// which yields the current playlist first, and then the whole new playlist on every change.
+    ///
+    /// An empty playlist is yielded as an empty [`Playlist`]. See [`Mpv::property_stream`].
+    pub async fn playlist_stream(&self) -> Result<PropertyStream<Playlist>, MpvError> {
+        self.typed_property_stream("playlist", |property| match property {
+            Property::Playlist(entries) => Ok(Playlist(entries)),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        })
+        .await
+    }
+
+    /// Observe the `metadata` property, and create a stream of the metadata of the current file.
+    ///
+    /// mpv sends the whole map on every change, e.g. when a radio stream starts a new song.
+    /// The map is empty while no file is loaded. See [`Mpv::property_stream`], and
+    /// [`Mpv::metadata_diff_stream`] for only the keys that changed.
+    pub async fn metadata_change_stream(
+        &self,
+    ) -> Result<PropertyStream<HashMap<String, MpvDataType>>, MpvError> {
+        self.typed_property_stream("metadata", |property| match property {
+            Property::Metadata(metadata) => Ok(metadata.unwrap_or_default()),
+            prop => Err(MpvError::UnexpectedProperty(prop)),
+        })
+        .await
+    }
+
+    /// Observe the `metadata` property, and create a stream of what changed between its values.
+    ///
+    /// The first item contains all the current metadata as changed keys, and is yielded even
+    /// if there is no metadata. After that, every item only contains the keys that were added,
+    /// changed or removed since the previous item. Later updates that do not change anything
+    /// are not yielded. The property is unobserved
+    /// when the stream is dropped.
+    pub async fn metadata_diff_stream(&self) -> Result<PropertyStream<MetadataDiff>, MpvError> {
+        let id = self.next_observe_id.fetch_add(1, Ordering::Relaxed);
+
+        // Subscribe before observing, since mpv sends the current value right away.
+        let events = self.get_event_stream().await;
+        self.run_command(MpvCommand::Observe {
+            id,
+            property: "metadata".to_owned(),
+        })
+        .await?;
+
+        let inner = events
+            .scan(MetadataTracker::default(), move |tracker, event| {
+                let diff = match event {
+                    Ok(Event::PropertyChange {
+                        id: Some(event_id),
+                        name,
+                        data,
+                    }) if event_id == id => match parse_property(&name, data) {
+                        Ok(Property::Metadata(metadata)) => {
+                            tracker.update(metadata.unwrap_or_default()).map(Ok)
+                        }
+                        Ok(prop) => Some(Err(MpvError::UnexpectedProperty(prop))),
+                        Err(err) => Some(Err(err)),
+                    },
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                };
+                future::ready(Some(diff))
+            })
+            .filter_map(future::ready);
+
+        Ok(PropertyStream {
+            mpv: self.clone(),
+            id,
+            inner: Box::pin(inner),
+        })
+    }
+
+    /// Create a stream of the overall [`PlaybackStatus`], which yields the current status first,
+    /// and then every time it changes.
+    ///
+    /// The status is derived from the `pause`, `idle-active` and `eof-reached` properties,
+    /// which are observed together with a single id. Changes to these properties that do
+    /// not change the overall status, like pausing while stopped, are not yielded.
+    /// The properties are unobserved when the stream is dropped.
+    pub async fn state_stream(&self) -> Result<PropertyStream<PlaybackStatus>, MpvError> {
+        let id = self.next_observe_id.fetch_add(1, Ordering::Relaxed);
+
+        // Subscribe before observing, since mpv sends the current values right away.
+        let events = self.get_event_stream().await;
+        for property in ["pause", "idle-active", "eof-reached"] {
+            let observed = self
+                .run_command(MpvCommand::Observe {
+                    id,
+                    property: property.to_owned(),
+                })
+                .await;
+            if let Err(err) = observed {
+                // There is no stream yet to unobserve on drop, so undo the earlier observes here.
+                if let Err(unobserve_err) = self.run_command(MpvCommand::Unobserve(id)).await {
+                    log::debug!("Failed to unobserve id {}: {}", id, unobserve_err);
+                }
+                return Err(err);
+            }
+        }
+
+        let inner = events
+            .scan(PlaybackStatusTracker::default(), move |tracker, event| {
+                let status = match event {
+                    Ok(Event::PropertyChange {
+                        id: Some(event_id),
+                        name,
+                        data,
+                    }) if event_id == id => parse_property(&name, data)
+                        .map(|property| tracker.update(property))
+                        .transpose(),
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                };
+                future::ready(Some(status))
+            })
+            .filter_map(future::ready);
+
+        Ok(PropertyStream {
+            mpv: self.clone(),
+            id,
+            inner: Box::pin(inner),
+        })
+    }
+
+    /// Observe a property, and create a stream of its values mapped through `map`.
+    async fn typed_property_stream<T: 'static>(
+        &self,
+        property: &str,
+        map: fn(Property) -> Result<T, MpvError>,
+    ) -> Result<PropertyStream<T>, MpvError> {
+        let id = self.next_observe_id.fetch_add(1, Ordering::Relaxed);
+
+        // Subscribe before observing, since mpv sends the current value right away.
+        let events = self.get_event_stream().await;
+        self.run_command(MpvCommand::Observe {
+            id,
+            property: property.to_owned(),
+        })
+        .await?;
+
+        let inner = events.filter_map(move |event| async move {
+            match event {
+                Ok(Event::PropertyChange {
+                    id: Some(event_id),
+                    name,
+                    data,
+                }) if event_id == id => Some(parse_property(&name, data).and_then(map)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            }
+        });
+
+        Ok(PropertyStream {
+            mpv: self.clone(),
+            id,
+            inner: Box::pin(inner),
+        })
+    }
+}
+
+/// Merge the events of several [`Mpv`] instances into a single stream.
+///
+/// Each event is tagged with the index of the instance it came from in `instances`.
+/// Events from the same instance keep their order, but there is no order between
+/// events from different instances.
+///
+/// # Example
+/// ```
+/// use futures::StreamExt;
+/// use mpvipc_async::{Mpv, MpvError, merge_event_streams};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), MpvError> {
+///     let left = Mpv::connect("/tmp/mpvsocket-left").await?;
+///     let right = Mpv::connect("/tmp/mpvsocket-right").await?;
+///     let mut events = merge_event_streams(&[&left, &right]).await;
+///     while let Some((index, event)) = events.next().await {
+///         println!("Player {}: {:?}", index, event?);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn merge_event_streams(
+    instances: &[&Mpv],
+) -> impl Stream<Item = (usize, Result<Event, MpvError>)> + use<> {
+    let mut streams = Vec::with_capacity(instances.len());
+    for (index, mpv) in instances.iter().enumerate() {
+        let events = mpv.get_event_stream().await;
+        streams.push(Box::pin(events.map(move |event| (index, event))));
+    }
+    stream::select_all(streams)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_diff() {
+        let string = |s: &str| MpvDataType::String(s.to_owned());
+        let mut tracker = MetadataTracker::default();
+
+        let first = HashMap::from([
+            ("icy-name".to_owned(), string("Radio")),
+            ("icy-title".to_owned(), string("First song")),
+            ("icy-genre".to_owned(), string("Jazz")),
+        ]);
+        assert_eq!(
+            tracker.update(first.clone()),
+            Some(MetadataDiff {
+                changed: first,
+                removed: Vec::new(),
+            })
+        );
+
+        let second = HashMap::from([
+            ("icy-name".to_owned(), string("Radio")),
+            ("icy-title".to_owned(), string("Second song")),
+        ]);
+        assert_eq!(
+            tracker.update(second.clone()),
+            Some(MetadataDiff {
+                changed: HashMap::from([("icy-title".to_owned(), string("Second song"))]),
+                removed: vec!["icy-genre".to_owned()],
+            })
+        );
+
+        // The same metadata again is not a change
+        assert_eq!(tracker.update(second), None);
+    }
+
+    #[test]
+    fn test_metadata_diff_empty_first() {
+        let mut tracker = MetadataTracker::default();
+
+        // The first update is yielded even without any metadata
+        assert_eq!(
+            tracker.update(HashMap::new()),
+            Some(MetadataDiff {
+                changed: HashMap::new(),
+                removed: Vec::new(),
+            })
+        );
+        assert_eq!(tracker.update(HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_playback_status_transitions() {
+        let mut tracker = PlaybackStatusTracker::default();
+
+        // Nothing is known until all properties have arrived
+        assert_eq!(tracker.update(Property::Pause(false)), None);
+        assert_eq!(tracker.update(Property::IdleActive(true)), None);
+        assert_eq!(
+            tracker.update(Property::EofReached(true)),
+            Some(PlaybackStatus::Stopped)
+        );
+
+        // Stopped -> Playing when a file is loaded
+        assert_eq!(tracker.update(Property::IdleActive(false)), None);
+        assert_eq!(
+            tracker.update(Property::EofReached(false)),
+            Some(PlaybackStatus::Playing)
+        );
+
+        // Playing -> Paused -> Playing
+        assert_eq!(
+            tracker.update(Property::Pause(true)),
+            Some(PlaybackStatus::Paused)
+        );
+        assert_eq!(
+            tracker.update(Property::Pause(false)),
+            Some(PlaybackStatus::Playing)
+        );
+
+        // Playing -> Stopped at the end of the file
+        assert_eq!(
+            tracker.update(Property::EofReached(true)),
+            Some(PlaybackStatus::Stopped)
+        );
+
+        // Pausing while stopped does not change the status
+        assert_eq!(tracker.update(Property::Pause(true)), None);
+
+        // Stopped -> Paused when a new file is loaded while paused
+        assert_eq!(
+            tracker.update(Property::EofReached(false)),
+            Some(PlaybackStatus::Paused)
+        );
+
+        // Paused -> Stopped when the player goes idle
+        assert_eq!(
+            tracker.update(Property::IdleActive(true)),
+            Some(PlaybackStatus::Stopped)
+        );
+
+        // Unrelated properties and repeated values are ignored
+        assert_eq!(tracker.update(Property::Volume(50.0)), None);
+        assert_eq!(tracker.update(Property::IdleActive(true)), None);
+    }
+}
@@ -0,0 +1,161 @@
+//! Formatting of playback positions and durations for display, and parsing of timestamps.
+
+use crate::{MpvError, SeekOptions};
+
+/// Format a number of seconds as `HH:MM:SS`.
+///
+/// Fractions of a second are truncated. Negative durations are prefixed with `-`,
+/// and durations of 100 hours or more use as many digits for the hours as needed.
+/// Values that are not finite, like [`f64::NAN`], are formatted as `00:00:00`.
+///
+/// # Example
+/// ```
+/// use mpvipc_async::format_duration;
+///
+/// assert_eq!(format_duration(3725.9), "01:02:05");
+/// assert_eq!(format_duration(-5.0), "-00:00:05");
+/// ```
+pub fn format_duration(seconds: f64) -> String {
+    let sign = if seconds < 0.0 { "-" } else { "" };
+    let total = if seconds.is_finite() {
+        seconds.abs() as u64
+    } else {
+        0
+    };
+
+    let hours = total / 3600;
+    let minutes = total / 60 % 60;
+    let seconds = total % 60;
+    format!("{}{:02}:{:02}:{:02}", sign, hours, minutes, seconds)
+}
+
+/// Parse a human readable seek target, like `"01:30"` or `"10%"`.
+///
+/// Timestamps are given as `[[HH:]MM:]SS[.fraction]`, e.g. `"00:01:30"`, `"1:30.5"` or `"90"`,
+/// and are returned as seconds with [`SeekOptions::Absolute`]. Minutes and seconds must be below
+/// `60` after a larger unit. Percentages between `0` and `100`, e.g. `"10%"` or `"12.5%"`,
+/// are returned with [`SeekOptions::AbsolutePercent`]. Anything else is rejected with
+/// [`MpvError::Other`]. See also [`MpvExt::seek_to_timestamp`](crate::MpvExt::seek_to_timestamp).
+///
+/// # Example
+/// ```
+/// use mpvipc_async::{SeekOptions, parse_timestamp};
+///
+/// assert_eq!(parse_timestamp("01:02:05.5").unwrap(), (3725.5, SeekOptions::Absolute));
+/// assert_eq!(parse_timestamp("10%").unwrap(), (10.0, SeekOptions::AbsolutePercent));
+/// assert!(parse_timestamp("1:2:3:4").is_err());
+/// ```
+pub fn parse_timestamp(timestamp: &str) -> Result<(f64, SeekOptions), MpvError> {
+    let invalid = || {
+        MpvError::Other(format!(
+            "Invalid timestamp '{}', expected [[HH:]MM:]SS[.fraction] or a percentage like 10%",
+            timestamp
+        ))
+    };
+    let trimmed = timestamp.trim();
+
+    if let Some(percent) = trimmed.strip_suffix('%') {
+        let percent = parse_decimal(percent).ok_or_else(invalid)?;
+        if percent > 100.0 {
+            return Err(invalid());
+        }
+        return Ok((percent, SeekOptions::AbsolutePercent));
+    }
+
+    let parts: Vec<&str> = trimmed.split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    let (seconds, larger_units) = parts.split_last().ok_or_else(invalid)?;
+    let seconds = parse_decimal(seconds).ok_or_else(invalid)?;
+    if !larger_units.is_empty() && seconds >= 60.0 {
+        return Err(invalid());
+    }
+
+    let mut whole_minutes = 0;
+    for (i, part) in larger_units.iter().enumerate() {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let value: u64 = part.parse().map_err(|_| invalid())?;
+        let is_minutes = i == larger_units.len() - 1;
+        if is_minutes && larger_units.len() > 1 && value >= 60 {
+            return Err(invalid());
+        }
+        whole_minutes = whole_minutes * 60 + value;
+    }
+
+    Ok((whole_minutes as f64 * 60.0 + seconds, SeekOptions::Absolute))
+}
+
+/// Parse a non-negative decimal number like `5` or `12.25`, without signs or exponents.
+fn parse_decimal(s: &str) -> Option<f64> {
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, "0"));
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(whole) || !is_digits(fraction) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.0), "00:00:00");
+        assert_eq!(format_duration(0.999), "00:00:00");
+        assert_eq!(format_duration(59.0), "00:00:59");
+        assert_eq!(format_duration(60.0), "00:01:00");
+        assert_eq!(format_duration(3599.0), "00:59:59");
+        assert_eq!(format_duration(3600.0), "01:00:00");
+        assert_eq!(format_duration(86399.0), "23:59:59");
+        assert_eq!(format_duration(86400.0), "24:00:00");
+        assert_eq!(format_duration(360000.0), "100:00:00");
+    }
+
+    #[test]
+    fn test_format_negative_duration() {
+        assert_eq!(format_duration(-0.5), "-00:00:00");
+        assert_eq!(format_duration(-59.0), "-00:00:59");
+        assert_eq!(format_duration(-3600.0), "-01:00:00");
+    }
+
+    #[test]
+    fn test_format_non_finite_duration() {
+        assert_eq!(format_duration(f64::NAN), "00:00:00");
+        assert_eq!(format_duration(f64::INFINITY), "00:00:00");
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let absolute = |seconds: f64| Ok((seconds, SeekOptions::Absolute));
+        assert_eq!(parse_timestamp("90"), absolute(90.0));
+        assert_eq!(parse_timestamp("1.5"), absolute(1.5));
+        assert_eq!(parse_timestamp("01:30"), absolute(90.0));
+        assert_eq!(parse_timestamp("00:01:30"), absolute(90.0));
+        assert_eq!(parse_timestamp("1:02:05.25"), absolute(3725.25));
+        assert_eq!(parse_timestamp("100:00:00"), absolute(360000.0));
+        assert_eq!(parse_timestamp(" 00:00:10 "), absolute(10.0));
+
+        let percent = |percent: f64| Ok((percent, SeekOptions::AbsolutePercent));
+        assert_eq!(parse_timestamp("10%"), percent(10.0));
+        assert_eq!(parse_timestamp("12.5%"), percent(12.5));
+        assert_eq!(parse_timestamp("100%"), percent(100.0));
+    }
+
+    #[test]
+    fn test_parse_invalid_timestamp() {
+        for timestamp in [
+            "", "abc", "1:2:3:4", "01:60", "01:60:00", "1::30", ":30", "1:30.", "-5", "+5", "1e3",
+            "1:-30", "101%", "-10%", "%", "abc%", "1,5",
+        ] {
+            assert!(
+                matches!(parse_timestamp(timestamp), Err(MpvError::Other(_))),
+                "{:?} should be rejected",
+                timestamp
+            );
+        }
+    }
+}
@@ -3,7 +3,9 @@ use std::time::Duration;
 use test_log::test;
 use tokio::time::sleep;
 
-use mpvipc_async::{MpvError, MpvExt, Property};
+use mpvipc_async::{
+    MpvError, MpvExt, Playlist, PlaylistAddOptions, PlaylistAddTypeOptions, Property, Switch,
+};
 
 use super::*;
 
@@ -34,6 +36,18 @@ async fn test_set_property() -> Result<(), MpvError> {
     Ok(())
 }
 
+#[tokio::test]
+#[cfg(target_family = "unix")]
+async fn test_is_idle() -> Result<(), MpvError> {
+    let (mut proc, mpv) = spawn_headless_mpv().await.unwrap();
+    assert!(mpv.is_idle().await?);
+
+    mpv.kill().await.unwrap();
+    proc.kill().await.unwrap();
+
+    Ok(())
+}
+
 #[tokio::test]
 #[cfg(target_family = "unix")]
 async fn test_get_unavailable_property() -> Result<(), MpvError> {
@@ -107,3 +121,81 @@ async fn test_unobserve_property() -> Result<(), MpvError> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[cfg(all(target_family = "unix", feature = "process"))]
+async fn test_spawn() -> Result<(), MpvError> {
+    let (proc, mpv) =
+        mpvipc_async::Mpv::spawn(&["--no-config", "--idle", "--no-video", "--no-audio"]).await?;
+    let version: String = mpv.get_property("mpv-version").await?.unwrap();
+    assert!(version.starts_with("mpv"));
+
+    mpv.quit_and_wait(proc, std::time::Duration::from_secs(5))
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(target_family = "unix")]
+async fn test_set_playlist_round_trip() -> Result<(), MpvError> {
+    let (mut proc, mpv) = spawn_headless_mpv().await.unwrap();
+    let file = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_assets/black-background-30s-480p.mp4"
+    );
+    mpv.set_property("pause", true).await?;
+    mpv.playlist_add(
+        file,
+        PlaylistAddTypeOptions::File,
+        PlaylistAddOptions::Replace,
+    )
+    .await?;
+    mpv.playlist_add(
+        file,
+        PlaylistAddTypeOptions::File,
+        PlaylistAddOptions::Append,
+    )
+    .await?;
+
+    let playlist = mpv.get_playlist().await?;
+    mpv.set_playlist(&playlist).await?;
+    let restored = mpv.get_playlist().await?;
+
+    let filenames = |playlist: &Playlist| -> Vec<String> {
+        playlist
+            .0
+            .iter()
+            .map(|entry| entry.filename.clone())
+            .collect()
+    };
+    assert_eq!(filenames(&restored), vec![file.to_owned(), file.to_owned()]);
+    assert_eq!(filenames(&restored), filenames(&playlist));
+
+    mpv.kill().await.unwrap();
+    proc.kill().await.unwrap();
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(target_family = "unix")]
+async fn test_toggle_sub_visibility() -> Result<(), MpvError> {
+    let (mut proc, mpv) = spawn_headless_mpv().await.unwrap();
+    mpv.set_sub_visibility(Switch::On).await?;
+    assert!(mpv.is_sub_visible().await?);
+
+    mpv.set_sub_visibility(Switch::Toggle).await?;
+    assert!(!mpv.is_sub_visible().await?);
+
+    mpv.set_sub_visibility(Switch::Toggle).await?;
+    assert!(mpv.is_sub_visible().await?);
+
+    mpv.set_sub_visibility(Switch::Off).await?;
+    assert!(!mpv.is_sub_visible().await?);
+
+    mpv.kill().await.unwrap();
+    proc.kill().await.unwrap();
+
+    Ok(())
+}
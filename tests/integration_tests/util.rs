@@ -3,7 +3,7 @@ use std::{path::Path, time::Duration};
 use thiserror::Error;
 use tokio::{
     process::{Child, Command},
-    time::{sleep, timeout},
+    time::timeout,
 };
 use tokio_stream::StreamExt;
 
@@ -28,20 +28,9 @@ pub async fn spawn_headless_mpv() -> Result<(Child, Mpv), MpvError> {
         .spawn()
         .expect("Failed to start mpv");
 
-    timeout(Duration::from_millis(1000), async {
-        while !&socket_path.exists() {
-            sleep(Duration::from_millis(10)).await;
-        }
-    })
-    .await
-    .map_err(|_| {
-        MpvError::MpvSocketConnectionError(format!(
-            "Failed to create mpv socket at {:?}, timed out waiting for socket file to be created",
-            &socket_path
-        ))
-    })?;
-
-    let mpv = Mpv::connect(socket_path.to_str().unwrap()).await?;
+    let mpv =
+        Mpv::connect_when_available(socket_path.to_str().unwrap(), Duration::from_millis(1000))
+            .await?;
     Ok((process_handle, mpv))
 }
 
@@ -0,0 +1,1877 @@
+use futures::{SinkExt, StreamExt};
+use std::{collections::HashMap, time::Duration};
+
+use mpvipc_async::{
+    CycleDirection, DefineSectionFlags, EnableSectionFlags, Event, EventLogMessageLevel,
+    FileOptions, LoopProperty, MetadataDiff, Mpv, MpvCommand, MpvDataType, MpvError, MpvExt,
+    NowPlaying, NumberChangeOptions, OptionInfo, OsdOverlayFormat, PauseReason, PlaybackSnapshot,
+    PlaybackStatus, Playlist, PlaylistAddOptions, PlaylistEntry, PlaylistJumpMode, ProfileMode,
+    ScreenshotFlag, SeekMode, SeekOptions, SeekPrecision, SeekReference, SeekUnit, VolumePreset,
+};
+use serde_json::{Value, json};
+use test_log::test;
+use tokio::{net::UnixStream, task::JoinHandle};
+use tokio_util::codec::{Framed, LinesCodec, LinesCodecError};
+
+/// Create a mock socket that asserts that the commands it receives match
+/// the expected commands in order, and answers each with the given reply.
+///
+/// If the reply is a JSON array, each of its elements is sent as a separate line.
+/// This can be used to send unsolicited events after the reply.
+fn test_socket(
+    exchanges: Vec<(Value, Value)>,
+) -> (UnixStream, JoinHandle<Result<(), LinesCodecError>>) {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        for (expected_command, reply) in exchanges {
+            let request = framed.next().await.unwrap()?;
+            let json = serde_json::from_str::<Value>(&request).unwrap();
+            assert_eq!(json["command"], expected_command);
+            match reply {
+                Value::Array(lines) => {
+                    for line in lines {
+                        framed.send(line.to_string()).await?;
+                    }
+                }
+                reply => framed.send(reply.to_string()).await?,
+            }
+        }
+        Ok(())
+    });
+
+    (server, join_handle)
+}
+
+fn success() -> Value {
+    json!({ "request_id": 0, "error": "success" })
+}
+
+#[test(tokio::test)]
+async fn test_cycle() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["cycle", "pause", "up"]), success()),
+        (json!(["cycle", "video-rotate", "down"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.run_command(MpvCommand::Cycle {
+        property: "pause".to_string(),
+        direction: CycleDirection::default(),
+    })
+    .await?;
+    mpv.run_command(MpvCommand::Cycle {
+        property: "video-rotate".to_string(),
+        direction: CycleDirection::Down,
+    })
+    .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_cycle_values() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["cycle-values", "video-rotate", "0", "90", "180"]),
+            success(),
+        ),
+        (
+            json!(["cycle-values", "!reverse", "video-rotate", "0", "90", "180"]),
+            success(),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let values = vec!["0".to_string(), "90".to_string(), "180".to_string()];
+    mpv.run_command(MpvCommand::CycleValues {
+        property: "video-rotate".to_string(),
+        values: values.clone(),
+        reverse: false,
+    })
+    .await?;
+    mpv.run_command(MpvCommand::CycleValues {
+        property: "video-rotate".to_string(),
+        values,
+        reverse: true,
+    })
+    .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_load_file_with_options() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![(
+        json!([
+            "loadfile",
+            "file.mkv",
+            "append",
+            "-1",
+            "start=30,title=%6%a,b=cd"
+        ]),
+        success(),
+    )]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.run_command(MpvCommand::LoadFileWithOptions {
+        file: "file.mkv".to_string(),
+        option: PlaylistAddOptions::Append,
+        options: HashMap::from([
+            ("title".to_string(), "a,b=cd".to_string()),
+            ("start".to_string(), "30".to_string()),
+        ]),
+    })
+    .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_playlist_add_with_options() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![(
+        json!([
+            "loadfile",
+            "file.mkv",
+            "append",
+            "-1",
+            "aid=2,force-media-title=Intro,start=30,stream-record=%7%50%.mkv"
+        ]),
+        success(),
+    )]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let options = FileOptions::new()
+        .start(30.0)
+        .aid(2)
+        .title("Intro")
+        .set("stream-record", "50%.mkv");
+    mpv.playlist_add_with_options("file.mkv", options).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_reload_current() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["get_property", "path"]),
+            json!({ "data": "file.mkv", "request_id": 0, "error": "success" }),
+        ),
+        (
+            json!(["get_property", "time-pos"]),
+            json!({ "data": 12.5, "request_id": 0, "error": "success" }),
+        ),
+        (
+            json!(["loadfile", "file.mkv", "replace", "-1", "start=12.5"]),
+            json!([
+                success(),
+                { "event": "end-file", "reason": "stop", "playlist_entry_id": 1 },
+                { "event": "file-loaded" },
+                { "event": "playback-restart" },
+            ]),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.reload_current().await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_reload_current_ignores_replayed_events() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["get_property", "path"]),
+            json!([
+                { "data": "file.mkv", "request_id": 0, "error": "success" },
+                // Left over from loading the file the first time
+                { "event": "playback-restart" },
+            ]),
+        ),
+        (
+            json!(["get_property", "time-pos"]),
+            json!({ "data": 12.5, "request_id": 0, "error": "success" }),
+        ),
+        (
+            json!(["loadfile", "file.mkv", "replace", "-1", "start=12.5"]),
+            json!([
+                success(),
+                {
+                    "event": "end-file",
+                    "reason": "error",
+                    "playlist_entry_id": 1,
+                    "file_error": "loading failed",
+                },
+            ]),
+        ),
+    ]);
+
+    let mpv = Mpv::builder()
+        .event_replay_depth(8)
+        .connect_socket(server)
+        .await?;
+    let result = mpv.reload_current().await;
+    assert!(matches!(result, Err(MpvError::Other(_))));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_reload_current_idle() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![(
+        json!(["get_property", "path"]),
+        json!({ "request_id": 0, "error": "property unavailable" }),
+    )]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let result = mpv.reload_current().await;
+    assert!(matches!(result, Err(MpvError::Other(_))));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_ab_loop_count() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["set_property", "ab-loop-count", "inf"]), success()),
+        (json!(["set_property", "ab-loop-count", 3]), success()),
+        (
+            json!(["get_property", "ab-loop-count"]),
+            json!({ "data": "inf", "request_id": 0, "error": "success" }),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.set_ab_loop_count(LoopProperty::Inf).await?;
+    mpv.set_ab_loop_count(LoopProperty::N(3)).await?;
+    assert_eq!(mpv.get_ab_loop_count().await?, LoopProperty::Inf);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+fn command_list(commands: &[&str]) -> Value {
+    let commands: Vec<_> = commands
+        .iter()
+        .map(|name| json!({ "name": name, "args": [], "vararg": false }))
+        .collect();
+    json!({ "data": commands, "request_id": 0, "error": "success" })
+}
+
+#[test(tokio::test)]
+async fn test_overlay_add_and_remove() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["get_property", "command-list"]),
+            command_list(&["overlay-add", "overlay-remove"]),
+        ),
+        (
+            json!({
+                "name": "overlay-add",
+                "id": 0,
+                "x": 10,
+                "y": -20,
+                "file": "/dev/shm/overlay",
+                "offset": 0,
+                "fmt": "bgra",
+                "w": 64,
+                "h": 32,
+                "stride": 256,
+            }),
+            success(),
+        ),
+        (json!({ "name": "overlay-remove", "id": 0 }), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.run_command(MpvCommand::OverlayAdd {
+        id: 0,
+        x: 10,
+        y: -20,
+        file: "/dev/shm/overlay".to_string(),
+        offset: 0,
+        fmt: "bgra".to_string(),
+        w: 64,
+        h: 32,
+        stride: 256,
+    })
+    .await?;
+    mpv.run_command(MpvCommand::OverlayRemove { id: 0 }).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_overlay_unsupported() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![(
+        json!(["get_property", "command-list"]),
+        command_list(&["loadfile"]),
+    )]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let result = mpv.run_command(MpvCommand::OverlayRemove { id: 0 }).await;
+    assert!(matches!(result, Err(MpvError::Unsupported(_))));
+    assert!(mpv.supports_command("loadfile").await?);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_osd_overlay() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["get_property", "command-list"]),
+            command_list(&["osd-overlay"]),
+        ),
+        (
+            json!({
+                "name": "osd-overlay",
+                "id": 1,
+                "format": "ass-events",
+                "data": "{\\pos(10,20)}Hello",
+                "res_x": 1280,
+                "res_y": 720,
+                "z": 5,
+            }),
+            success(),
+        ),
+        (
+            json!({
+                "name": "osd-overlay",
+                "id": 1,
+                "format": "none",
+                "data": "",
+                "res_x": 0,
+                "res_y": 0,
+                "z": 0,
+            }),
+            success(),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.run_command(MpvCommand::OsdOverlay {
+        id: 1,
+        format: OsdOverlayFormat::AssEvents,
+        data: "{\\pos(10,20)}Hello".to_string(),
+        res_x: 1280,
+        res_y: 720,
+        z: 5,
+    })
+    .await?;
+    mpv.clear_osd_overlay(1).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_set_speed_clamps() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["get_property", "speed"]),
+            json!({ "data": 0.5, "request_id": 0, "error": "success" }),
+        ),
+        (json!(["set_property", "speed", 0.01]), success()),
+        (
+            json!(["get_property", "speed"]),
+            json!({ "data": 0.01, "request_id": 0, "error": "success" }),
+        ),
+        (json!(["set_property", "speed", 0.01]), success()),
+        (json!(["set_property", "speed", 2.0]), success()),
+        (json!(["set_property", "speed", 1.0]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let speed = mpv.set_speed(1.0, NumberChangeOptions::Decrease).await?;
+    assert_eq!(speed, 0.01);
+    let speed = mpv.set_speed(1.0, NumberChangeOptions::Decrease).await?;
+    assert_eq!(speed, 0.01);
+    let speed = mpv.set_speed(2.0, NumberChangeOptions::Absolute).await?;
+    assert_eq!(speed, 2.0);
+    mpv.reset_speed().await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_video_zoom_and_pan() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["set_property", "video-zoom", 1.0]), success()),
+        (
+            json!(["get_property", "video-zoom"]),
+            json!({ "data": 1.0, "request_id": 0, "error": "success" }),
+        ),
+        (json!(["set_property", "video-zoom", 0.5]), success()),
+        (
+            json!(["get_property", "video-pan-x"]),
+            json!({ "data": 0.25, "request_id": 0, "error": "success" }),
+        ),
+        (
+            json!(["get_property", "video-pan-y"]),
+            json!({ "data": -0.5, "request_id": 0, "error": "success" }),
+        ),
+        (json!(["set_property", "video-pan-x", 0.5]), success()),
+        (json!(["set_property", "video-pan-y", 0.0]), success()),
+        (
+            json!(["get_property", "video-pan-x"]),
+            json!({ "data": 0.5, "request_id": 0, "error": "success" }),
+        ),
+        (
+            json!(["get_property", "video-pan-y"]),
+            json!({ "data": 0.0, "request_id": 0, "error": "success" }),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.set_video_zoom(1.0, NumberChangeOptions::Absolute)
+        .await?;
+    mpv.set_video_zoom(0.5, NumberChangeOptions::Decrease)
+        .await?;
+    mpv.set_video_pan(0.25, 0.5, NumberChangeOptions::Increase)
+        .await?;
+    assert_eq!(mpv.get_video_pan().await?, (0.5, 0.0));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_chapter_stream() -> Result<(), MpvError> {
+    let id = 1u64 << 32;
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["observe_property", id, "chapter"]),
+            json!([
+                success(),
+                { "event": "property-change", "id": id, "name": "chapter", "data": -1 },
+                { "event": "property-change", "id": 7, "name": "chapter", "data": 5 },
+                { "event": "property-change", "id": id, "name": "chapter", "data": 2 },
+                { "event": "property-change", "id": id, "name": "chapter" },
+            ]),
+        ),
+        (json!(["unobserve_property", id]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let mut chapters = mpv.chapter_stream().await?;
+    assert_eq!(chapters.next().await, Some(Ok(None)));
+    assert_eq!(chapters.next().await, Some(Ok(Some(2))));
+    assert_eq!(chapters.next().await, Some(Ok(None)));
+    drop(chapters);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_state_stream() -> Result<(), MpvError> {
+    let id = 1u64 << 32;
+    let change = |name: &str, data: bool| json!({ "event": "property-change", "id": id, "name": name, "data": data });
+    let (server, join_handle) = test_socket(vec![
+        (json!(["observe_property", id, "pause"]), success()),
+        (json!(["observe_property", id, "idle-active"]), success()),
+        (
+            json!(["observe_property", id, "eof-reached"]),
+            json!([
+                success(),
+                change("pause", false),
+                change("idle-active", false),
+                change("eof-reached", false),
+                change("pause", true),
+                change("pause", true),
+                change("eof-reached", true),
+                change("pause", false),
+            ]),
+        ),
+        (json!(["unobserve_property", id]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let mut states = mpv.state_stream().await?;
+    assert_eq!(states.next().await, Some(Ok(PlaybackStatus::Playing)));
+    assert_eq!(states.next().await, Some(Ok(PlaybackStatus::Paused)));
+    assert_eq!(states.next().await, Some(Ok(PlaybackStatus::Stopped)));
+    drop(states);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_state_stream_observe_error() -> Result<(), MpvError> {
+    let id = 1u64 << 32;
+    let (server, join_handle) = test_socket(vec![
+        (json!(["observe_property", id, "pause"]), success()),
+        (
+            json!(["observe_property", id, "idle-active"]),
+            json!({ "request_id": 0, "error": "property not found" }),
+        ),
+        (json!(["unobserve_property", id]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert!(matches!(
+        mpv.state_stream().await,
+        Err(MpvError::MpvError { .. })
+    ));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_metadata_diff_stream() -> Result<(), MpvError> {
+    let id = 1u64 << 32;
+    let change = |data: Value| json!({ "event": "property-change", "id": id, "name": "metadata", "data": data });
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["observe_property", id, "metadata"]),
+            json!([
+                success(),
+                change(json!({ "icy-name": "Radio", "icy-title": "First song" })),
+                change(json!({ "icy-name": "Radio", "icy-title": "Second song" })),
+            ]),
+        ),
+        (json!(["unobserve_property", id]), success()),
+    ]);
+
+    let string = |s: &str| MpvDataType::String(s.to_owned());
+    let mpv = Mpv::connect_socket(server).await?;
+    let mut diffs = mpv.metadata_diff_stream().await?;
+    let first = diffs.next().await.unwrap()?;
+    assert_eq!(first.changed.len(), 2);
+    assert_eq!(first.changed["icy-title"], string("First song"));
+    let second = diffs.next().await.unwrap()?;
+    assert_eq!(
+        second,
+        MetadataDiff {
+            changed: HashMap::from([("icy-title".to_owned(), string("Second song"))]),
+            removed: Vec::new(),
+        }
+    );
+    drop(diffs);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_playlist_stream() -> Result<(), MpvError> {
+    let id = 1u64 << 32;
+    let change = |data: Value| json!({ "event": "property-change", "id": id, "name": "playlist", "data": data });
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["observe_property", id, "playlist"]),
+            json!([
+                success(),
+                change(json!([])),
+                change(json!([
+                    { "filename": "a.mkv", "current": true, "playing": true },
+                    { "filename": "b.mkv", "title": "B" },
+                ])),
+            ]),
+        ),
+        (json!(["unobserve_property", id]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let mut playlists = mpv.playlist_stream().await?;
+    assert_eq!(playlists.next().await, Some(Ok(Playlist(Vec::new()))));
+    let Playlist(entries) = playlists.next().await.unwrap()?;
+    assert_eq!(entries.len(), 2);
+    assert_eq!((entries[1].id, entries[1].filename.as_str()), (1, "b.mkv"));
+    assert_eq!(entries[1].title.as_deref(), Some("B"));
+    assert!(entries[0].current);
+    drop(playlists);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_apply_playback_state() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["seek", "90.5", "absolute+exact"]), success()),
+        (json!(["set_property", "speed", 1.5]), success()),
+        (json!(["set_property", "pause", false]), success()),
+        (json!(["set_property", "pause", true]), success()),
+        (
+            json!(["seek", "10", "absolute+exact"]),
+            json!({ "request_id": 0, "error": "error running command" }),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.apply_playback_state(Some(Duration::from_secs_f64(90.5)), Some(1.5), Some(false))
+        .await?;
+    mpv.apply_playback_state(None, None, Some(true)).await?;
+    let result = mpv
+        .apply_playback_state(Some(Duration::from_secs(10)), Some(2.0), None)
+        .await;
+    assert!(matches!(result, Err(MpvError::MpvError { .. })));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_playlist_jump_modes() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["playlist-next", "weak"]), success()),
+        (json!(["playlist-next", "force"]), success()),
+        (json!(["playlist-prev", "weak"]), success()),
+        (json!(["playlist-prev", "force"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.next().await?;
+    mpv.next_with_mode(PlaylistJumpMode::Force).await?;
+    mpv.prev().await?;
+    mpv.run_command(MpvCommand::PlaylistPrev(PlaylistJumpMode::Force))
+        .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_next_property_change() -> Result<(), MpvError> {
+    let id = 1u64 << 32;
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["observe_property", id, "time-pos"]),
+            json!([
+                success(),
+                { "event": "property-change", "id": id, "name": "time-pos", "data": 1.0 },
+                { "event": "property-change", "id": 1, "name": "time-pos", "data": 1.5 },
+                { "event": "property-change", "id": id, "name": "time-pos", "data": 2.0 },
+            ]),
+        ),
+        (json!(["unobserve_property", id]), success()),
+        (
+            json!(["observe_property", id + 1, "pause"]),
+            json!([
+                success(),
+                { "event": "property-change", "id": id + 1, "name": "pause", "data": false },
+            ]),
+        ),
+        (json!(["unobserve_property", id + 1]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let time_pos: Option<f64> = mpv
+        .next_property_change("time-pos", Duration::from_secs(1))
+        .await?;
+    assert_eq!(time_pos, Some(2.0));
+
+    let pause = mpv
+        .next_property_change::<bool>("pause", Duration::from_millis(50))
+        .await;
+    assert!(matches!(pause, Err(MpvError::Timeout(_))));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_apply_profile() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["apply-profile", "low-power"]), success()),
+        (json!(["apply-profile", "low-power", "restore"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.apply_profile("low-power", ProfileMode::Apply).await?;
+    mpv.run_command(MpvCommand::ApplyProfile {
+        name: "low-power".to_string(),
+        mode: ProfileMode::Restore,
+    })
+    .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_reset_playback_params() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["set_property", "speed", 1.0]), success()),
+        (json!(["set_property", "audio-delay", 0]), success()),
+        (json!(["set_property", "sub-delay", 0]), success()),
+        (json!(["set_property", "video-zoom", 0]), success()),
+        (json!(["set_property", "video-pan-x", 0]), success()),
+        (json!(["set_property", "video-pan-y", 0]), success()),
+        (json!(["set_property", "volume", 100.0]), success()),
+        (json!(["set_property", "speed", 1.0]), success()),
+        (
+            json!(["set_property", "audio-delay", 0]),
+            json!({ "request_id": 0, "error": "property not found" }),
+        ),
+        (json!(["set_property", "sub-delay", 0]), success()),
+        (json!(["set_property", "video-zoom", 0]), success()),
+        (json!(["set_property", "video-pan-x", 0]), success()),
+        (json!(["set_property", "video-pan-y", 0]), success()),
+        (json!(["set_property", "volume", 50.0]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.reset_playback_params(None).await?;
+    match mpv.reset_playback_params(Some(50.0)).await {
+        Err(MpvError::MpvError { command, .. }) => {
+            assert_eq!(
+                command,
+                vec![json!("set_property"), json!("audio-delay"), json!(0)]
+            );
+        }
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_observe_property_notify_only() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["observe_property", 5, "playlist"]),
+            json!([
+                success(),
+                { "event": "property-change", "id": 5, "name": "playlist", "data": [{ "filename": "a.mkv" }] },
+                { "event": "property-change", "id": 6, "name": "volume", "data": 50.0 },
+            ]),
+        ),
+        (json!(["unobserve_property", 5]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let mut events = mpv.get_event_stream().await;
+    mpv.observe_property_notify_only(5, "playlist").await?;
+
+    assert_eq!(
+        events.next().await.unwrap()?,
+        Event::PropertyChange {
+            id: Some(5),
+            name: "playlist".to_string(),
+            data: None,
+        }
+    );
+    assert_eq!(
+        events.next().await.unwrap()?,
+        Event::PropertyChange {
+            id: Some(6),
+            name: "volume".to_string(),
+            data: Some(MpvDataType::Double(50.0)),
+        }
+    );
+    mpv.unobserve_property(5).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_default_osd() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["osd-msg", "seek", "10", "relative+exact"]),
+            success(),
+        ),
+        (json!(["osd-msg", "add", "volume", "5"]), success()),
+        (json!(["osd-msg", "set", "speed", "1.5"]), success()),
+        (json!(["osd-msg", "set", "volume", "50"]), success()),
+        (json!(["set_property", "pause", true]), success()),
+    ]);
+
+    let mpv = Mpv::builder()
+        .default_osd(true)
+        .connect_socket(server)
+        .await?;
+    mpv.seek_with_precision(10.0, SeekOptions::Relative, SeekPrecision::Exact)
+        .await?;
+    mpv.set_volume(5.0, NumberChangeOptions::Increase).await?;
+    mpv.set_speed(1.5, NumberChangeOptions::Absolute).await?;
+    mpv.set_volume_preset(VolumePreset::Half).await?;
+    mpv.set_property("pause", true).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_default_osd_command_timeout() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        framed.next().await.unwrap()?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok::<(), LinesCodecError>(())
+    });
+
+    let mpv = Mpv::builder()
+        .default_osd(true)
+        .command_timeout(Duration::from_millis(20))
+        .connect_socket(server)
+        .await?;
+    let result = mpv.set_volume(5.0, NumberChangeOptions::Increase).await;
+    assert!(matches!(result, Err(MpvError::Timeout(_))));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_sub_seek() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["sub-seek", "-1"]), success()),
+        (json!(["sub-seek", "2"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.sub_seek(-1).await?;
+    mpv.run_command(MpvCommand::SubSeek { skip: 2 }).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_set_volume_preset() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["set_property", "volume", 0.0]), success()),
+        (json!(["set_property", "volume", 25.0]), success()),
+        (json!(["set_property", "volume", 50.0]), success()),
+        (json!(["set_property", "volume", 100.0]), success()),
+        (
+            json!(["get_property", "volume-max"]),
+            json!({ "data": 150.0, "request_id": 0, "error": "success" }),
+        ),
+        (json!(["set_property", "volume", 150.0]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let presets = [
+        (VolumePreset::Mute, 0.0),
+        (VolumePreset::Quarter, 25.0),
+        (VolumePreset::Half, 50.0),
+        (VolumePreset::Full, 100.0),
+        (VolumePreset::Max, 150.0),
+    ];
+    for (preset, expected) in presets {
+        assert_eq!(mpv.set_volume_preset(preset).await?, expected);
+    }
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_load_script() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![(
+        json!(["load-script", "/usr/share/app/companion.lua"]),
+        success(),
+    )]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.load_script("/usr/share/app/companion.lua").await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_rate_limit_coalesces_set_property() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["set_property", "volume", 10.0]), success()),
+        (json!(["set_property", "volume", 30.0]), success()),
+        (json!(["set_property", "speed", 2.0]), success()),
+        (json!(["playlist-next", "weak"]), success()),
+        (json!(["set_property", "volume", 40.0]), success()),
+    ]);
+
+    let mpv = Mpv::builder()
+        .rate_limit(1, Duration::from_millis(100))
+        .connect_socket(server)
+        .await?;
+    let (first, replaced, speed, kept, next, after_next) = tokio::join!(
+        mpv.set_property("volume", 10.0),
+        mpv.set_property("volume", 20.0),
+        mpv.set_property("speed", 2.0),
+        mpv.set_property("volume", 30.0),
+        mpv.run_command(MpvCommand::PlaylistNext(PlaylistJumpMode::Weak)),
+        mpv.set_property("volume", 40.0),
+    );
+    for result in [first, replaced, speed, kept, next, after_next] {
+        result?;
+    }
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+/// Shuffle a playlist with the given seed against a mock mpv,
+/// which applies `playlist-move` commands to its playlist, and return the final order.
+async fn shuffle_seeded(seed: u64) -> Result<Vec<String>, MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        let mut playlist: Vec<String> = (0..8).map(|i| format!("file{}.mkv", i)).collect();
+        while let Some(request) = framed.next().await {
+            let json = serde_json::from_str::<Value>(&request.unwrap()).unwrap();
+            let reply = match json["command"][0].as_str().unwrap() {
+                "get_property" => {
+                    let entries: Vec<_> = playlist
+                        .iter()
+                        .map(|filename| json!({ "filename": filename }))
+                        .collect();
+                    json!({ "data": entries, "request_id": json["request_id"], "error": "success" })
+                }
+                "playlist-move" => {
+                    let from: usize = json["command"][1].as_str().unwrap().parse().unwrap();
+                    let to: usize = json["command"][2].as_str().unwrap().parse().unwrap();
+                    assert!(from > to, "entries are only moved towards the start");
+                    let entry = playlist.remove(from);
+                    playlist.insert(to, entry);
+                    json!({ "request_id": json["request_id"], "error": "success" })
+                }
+                command => panic!("unexpected command: {}", command),
+            };
+            framed.send(reply.to_string()).await.unwrap();
+        }
+        playlist
+    });
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.playlist_shuffle_seeded(seed).await?;
+    mpv.disconnect().await?;
+
+    Ok(join_handle.await.unwrap())
+}
+
+#[test(tokio::test)]
+async fn test_playlist_shuffle_seeded() -> Result<(), MpvError> {
+    let first = shuffle_seeded(42).await?;
+    let second = shuffle_seeded(42).await?;
+    assert_eq!(first, second);
+
+    let mut sorted = first.clone();
+    sorted.sort();
+    assert_eq!(
+        sorted,
+        (0..8).map(|i| format!("file{}.mkv", i)).collect::<Vec<_>>()
+    );
+    assert_ne!(first, sorted);
+    assert_ne!(first, shuffle_seeded(7).await?);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_ab_loop_around() -> Result<(), MpvError> {
+    let data = |value: Value| json!({ "data": value, "request_id": 0, "error": "success" });
+    let (server, join_handle) = test_socket(vec![
+        // Near the start of the file
+        (json!(["get_property", "time-pos"]), data(json!(2.0))),
+        (json!(["get_property", "duration"]), data(json!(60.0))),
+        (json!(["set_property", "ab-loop-a", 0.0]), success()),
+        (json!(["set_property", "ab-loop-b", 7.0]), success()),
+        // Near the end of the file
+        (json!(["get_property", "time-pos"]), data(json!(58.0))),
+        (json!(["get_property", "duration"]), data(json!(60.0))),
+        (json!(["set_property", "ab-loop-a", 53.0]), success()),
+        (json!(["set_property", "ab-loop-b", 60.0]), success()),
+        // Nothing is playing
+        (
+            json!(["get_property", "time-pos"]),
+            json!({ "request_id": 0, "error": "property unavailable" }),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let before = Duration::from_secs(5);
+    let after = Duration::from_secs(5);
+    assert_eq!(mpv.ab_loop_around(before, after).await?, (0.0, 7.0));
+    assert_eq!(mpv.ab_loop_around(before, after).await?, (53.0, 60.0));
+    assert_eq!(
+        mpv.ab_loop_around(before, after).await,
+        Err(MpvError::Other(
+            "Can not set an A-B loop, nothing is playing".to_owned()
+        ))
+    );
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_wait_until_loaded() -> Result<(), MpvError> {
+    let unavailable = || json!({ "request_id": 0, "error": "property unavailable" });
+    let (server, join_handle) = test_socket(vec![
+        // A file is already loaded
+        (
+            json!(["get_property", "file-format"]),
+            json!({ "data": "matroska,webm", "request_id": 0, "error": "success" }),
+        ),
+        // A file is loaded after waiting
+        (
+            json!(["get_property", "file-format"]),
+            json!([
+                unavailable(),
+                { "event": "start-file", "playlist_entry_id": 1 },
+                { "event": "file-loaded" },
+            ]),
+        ),
+        // No file is loaded in time
+        (json!(["get_property", "file-format"]), unavailable()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let timeout = Duration::from_millis(200);
+    mpv.wait_until_loaded(timeout).await?;
+    mpv.wait_until_loaded(timeout).await?;
+    assert!(matches!(
+        mpv.wait_until_loaded(timeout).await,
+        Err(MpvError::Timeout(_))
+    ));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_wait_until_loaded_ignores_replayed_events() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        // Left over from an earlier file
+        (
+            json!(["stop"]),
+            json!([{ "event": "file-loaded" }, success()]),
+        ),
+        (
+            json!(["get_property", "file-format"]),
+            json!({ "request_id": 0, "error": "property unavailable" }),
+        ),
+    ]);
+
+    let mpv = Mpv::builder()
+        .event_replay_depth(8)
+        .connect_socket(server)
+        .await?;
+    mpv.stop().await?;
+    assert!(matches!(
+        mpv.wait_until_loaded(Duration::from_millis(200)).await,
+        Err(MpvError::Timeout(_))
+    ));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_seek_mode() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["seek", "50", "absolute-percent"]), success()),
+        (json!(["seek", "-5", "relative"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let mode = SeekMode::new(SeekReference::Absolute, SeekUnit::Percent);
+    assert_eq!(mode, SeekMode::from(SeekOptions::AbsolutePercent));
+    mpv.seek(50.0, mode).await?;
+    mpv.seek(-5.0, SeekMode::default()).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_input_sections() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!([
+                "define-section",
+                "remote",
+                "SPACE cycle pause\nq quit",
+                "force"
+            ]),
+            success(),
+        ),
+        (json!(["enable-section", "remote", "exclusive"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.run_command(MpvCommand::DefineSection {
+        name: "remote".to_owned(),
+        contents: "SPACE cycle pause\nq quit".to_owned(),
+        flags: DefineSectionFlags::Force,
+    })
+    .await?;
+    mpv.run_command(MpvCommand::EnableSection {
+        name: "remote".to_owned(),
+        flags: EnableSectionFlags::Exclusive,
+    })
+    .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_toggle_speed() -> Result<(), MpvError> {
+    let speed = |speed: f64| json!({ "data": speed, "request_id": 0, "error": "success" });
+    let (server, join_handle) = test_socket(vec![
+        (json!(["get_property", "speed"]), speed(1.0)),
+        (json!(["set_property", "speed", 2.0]), success()),
+        (json!(["get_property", "speed"]), speed(2.0)),
+        (json!(["set_property", "speed", 1.0]), success()),
+        // Neither of the two speeds
+        (json!(["get_property", "speed"]), speed(1.5)),
+        (json!(["set_property", "speed", 1.0]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert_eq!(mpv.toggle_speed(1.0, 2.0).await?, 2.0);
+    assert_eq!(mpv.toggle_speed(1.0, 2.0).await?, 1.0);
+    assert_eq!(mpv.toggle_speed(1.0, 2.0).await?, 1.0);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_progress() -> Result<(), MpvError> {
+    let unavailable = || json!({ "request_id": 0, "error": "property unavailable" });
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["get_property", "time-pos"]),
+            json!({ "data": 30.5, "request_id": 0, "error": "success" }),
+        ),
+        (
+            json!(["get_property", "duration"]),
+            json!({ "data": 120.0, "request_id": 0, "error": "success" }),
+        ),
+        // Idle
+        (json!(["get_property", "time-pos"]), unavailable()),
+        (json!(["get_property", "duration"]), unavailable()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert_eq!(
+        mpv.get_progress().await?,
+        Some((Duration::from_secs_f64(30.5), Duration::from_secs(120)))
+    );
+    assert_eq!(mpv.get_progress().await?, None);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_pause_reason() -> Result<(), MpvError> {
+    let flag = |value: bool| json!({ "data": value, "request_id": 0, "error": "success" });
+    let unavailable = || json!({ "request_id": 0, "error": "property unavailable" });
+    let flags = |pause: Value, paused_for_cache: Value, core_idle: Value| {
+        vec![
+            (json!(["get_property", "pause"]), pause),
+            (
+                json!(["get_property", "paused-for-cache"]),
+                paused_for_cache,
+            ),
+            (json!(["get_property", "core-idle"]), core_idle),
+        ]
+    };
+    let (server, join_handle) = test_socket(
+        [
+            flags(flag(true), flag(false), flag(true)),
+            // Paused by the user while the cache is filling up
+            flags(flag(true), flag(true), flag(true)),
+            flags(flag(false), flag(true), flag(true)),
+            // Seeking
+            flags(flag(false), flag(false), flag(true)),
+            flags(flag(false), flag(false), flag(false)),
+            // Idle, without a file
+            flags(flag(false), unavailable(), flag(true)),
+        ]
+        .concat(),
+    );
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert_eq!(mpv.pause_reason().await?, PauseReason::UserPaused);
+    assert_eq!(mpv.pause_reason().await?, PauseReason::UserPaused);
+    assert_eq!(mpv.pause_reason().await?, PauseReason::Buffering);
+    assert_eq!(mpv.pause_reason().await?, PauseReason::Buffering);
+    assert_eq!(mpv.pause_reason().await?, PauseReason::Playing);
+    assert_eq!(mpv.pause_reason().await?, PauseReason::Buffering);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_playlist_move_up_and_down() -> Result<(), MpvError> {
+    let count = || json!({ "data": 3, "request_id": 0, "error": "success" });
+    let (server, join_handle) = test_socket(vec![
+        (json!(["playlist-move", "2", "1"]), success()),
+        (json!(["get_property", "playlist-count"]), count()),
+        // Moving down has to skip past the next entry
+        (json!(["playlist-move", "0", "2"]), success()),
+        (json!(["get_property", "playlist-count"]), count()),
+        (json!(["playlist-move", "1", "3"]), success()),
+        // The last entry can not be moved down
+        (json!(["get_property", "playlist-count"]), count()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.playlist_move_up(2).await?;
+    assert!(matches!(
+        mpv.playlist_move_up(0).await,
+        Err(MpvError::Other(_))
+    ));
+    mpv.playlist_move_down(0).await?;
+    mpv.playlist_move_down(1).await?;
+    assert!(matches!(
+        mpv.playlist_move_down(2).await,
+        Err(MpvError::Other(_))
+    ));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_idle_behavior() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["set_property", "idle", "no"]), success()),
+        (json!(["set_property", "idle", "yes"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.enable_quit_when_idle().await?;
+    mpv.keep_alive_when_idle().await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_multiply() -> Result<(), MpvError> {
+    let data = |value: f64| json!({ "data": value, "request_id": 0, "error": "success" });
+    let (server, join_handle) = test_socket(vec![
+        (json!(["multiply", "speed", "1.5"]), success()),
+        (json!(["get_property", "speed"]), data(1.5)),
+        (json!(["multiply", "volume", "0.5"]), success()),
+        (json!(["get_property", "volume"]), data(50.0)),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert_eq!(mpv.multiply_speed(1.5).await?, 1.5);
+    assert_eq!(mpv.multiply_volume(0.5).await?, 50.0);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_add() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["add", "volume", "5"]), success()),
+        (json!(["add", "volume", "-2.5"]), success()),
+        (json!(["add", "sub-delay", "0.1"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.set_volume(5.0, NumberChangeOptions::Increase).await?;
+    mpv.set_volume(2.5, NumberChangeOptions::Decrease).await?;
+    mpv.run_command(MpvCommand::Add {
+        property: "sub-delay".to_string(),
+        value: 0.1,
+    })
+    .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_set_playlist() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["loadfile", "a.mkv", "replace"]), success()),
+        (json!(["loadfile", "b.mkv", "append"]), success()),
+        (json!(["stop"]), success()),
+    ]);
+
+    let entry = |id: usize, filename: &str| PlaylistEntry {
+        id,
+        filename: filename.to_owned(),
+        title: Some("ignored".to_owned()),
+        current: false,
+    };
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.set_playlist(&Playlist(vec![entry(4, "a.mkv"), entry(7, "b.mkv")]))
+        .await?;
+    mpv.set_playlist(&Playlist::default()).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_bad_command_does_not_stop_ipc() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+
+        // An oversized command with odd characters, which mpv rejects
+        let request = framed.next().await.unwrap()?;
+        let json = serde_json::from_str::<Value>(&request).unwrap();
+        assert_eq!(json["command"][0], "no-such-command");
+        framed
+            .send(json!({ "request_id": 0, "error": "invalid parameter" }).to_string())
+            .await?;
+
+        // A command whose caller gives up before the reply arrives
+        framed.next().await.unwrap()?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        framed.send(success().to_string()).await?;
+
+        let request = framed.next().await.unwrap()?;
+        let json = serde_json::from_str::<Value>(&request).unwrap();
+        assert_eq!(json["command"], json!(["stop"]));
+        framed.send(success().to_string()).await?;
+
+        Ok::<(), LinesCodecError>(())
+    });
+
+    let mpv = Mpv::connect_socket(server).await?;
+
+    let argument = "\"quoted\"\nnewline\0".repeat(100_000);
+    let result = mpv.run_command_raw("no-such-command", &[&argument]).await;
+    assert!(matches!(result, Err(MpvError::MpvError { .. })));
+
+    let cancelled =
+        tokio::time::timeout(Duration::from_millis(10), mpv.run_command(MpvCommand::Stop)).await;
+    assert!(cancelled.is_err());
+
+    mpv.run_command(MpvCommand::Stop).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_command_timeout_override() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        let replies = [
+            success(),
+            json!({ "data": 50.0, "request_id": 0, "error": "success" }),
+            success(),
+        ];
+        for reply in replies {
+            framed.next().await.unwrap()?;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            framed.send(reply.to_string()).await?;
+        }
+        Ok::<(), LinesCodecError>(())
+    });
+
+    let mpv = Mpv::builder()
+        .command_timeout(Duration::from_millis(20))
+        .connect_socket(server)
+        .await?;
+
+    mpv.run_command_timeout(MpvCommand::Stop, Duration::from_secs(1))
+        .await?;
+    let volume: Option<f64> = mpv
+        .get_property_timeout("volume", Duration::from_secs(1))
+        .await?;
+    assert_eq!(volume, Some(50.0));
+
+    let result = mpv.run_command(MpvCommand::Stop).await;
+    assert!(matches!(result, Err(MpvError::Timeout(_))));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_non_object_reply() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["playlist-shuffle"]), json!("hello")),
+        (json!(["stop"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let error = mpv
+        .run_command(MpvCommand::PlaylistShuffle)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        MpvError::UnexpectedReply {
+            command: vec![json!("playlist-shuffle")],
+            received: json!("hello"),
+        }
+    );
+    let message = error.to_string();
+    assert!(message.contains("hello") && message.contains("playlist-shuffle"));
+
+    mpv.run_command(MpvCommand::Stop).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_log_stream() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["request_log_messages", "warn"]),
+            json!([
+                success(),
+                { "event": "file-loaded" },
+                {
+                    "event": "log-message",
+                    "prefix": "ffmpeg",
+                    "level": "warn",
+                    "text": "Something happened\n",
+                },
+            ]),
+        ),
+        (json!(["request_log_messages", "no"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let mut logs = mpv.log_stream(EventLogMessageLevel::Warn).await?;
+    assert_eq!(
+        logs.next().await,
+        Some(Ok((
+            EventLogMessageLevel::Warn,
+            "ffmpeg".to_string(),
+            "Something happened\n".to_string()
+        )))
+    );
+    drop(logs);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_log_stream_ignores_replayed_events() -> Result<(), MpvError> {
+    let log_message = |text: &str| json!({ "event": "log-message", "prefix": "cplayer", "level": "debug", "text": text });
+    let (server, join_handle) = test_socket(vec![
+        // Forwarded for an earlier log stream
+        (json!(["stop"]), json!([log_message("old"), success()])),
+        (
+            json!(["request_log_messages", "debug"]),
+            json!([success(), log_message("new")]),
+        ),
+        (json!(["request_log_messages", "no"]), success()),
+    ]);
+
+    let mpv = Mpv::builder()
+        .event_replay_depth(8)
+        .connect_socket(server)
+        .await?;
+    mpv.stop().await?;
+    let mut logs = mpv.log_stream(EventLogMessageLevel::Debug).await?;
+    assert_eq!(
+        logs.next().await,
+        Some(Ok((
+            EventLogMessageLevel::Debug,
+            "cplayer".to_string(),
+            "new".to_string()
+        )))
+    );
+    drop(logs);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_snapshot_and_restore() -> Result<(), MpvError> {
+    let data = |data: Value| json!({ "data": data, "request_id": 0, "error": "success" });
+    let (server, join_handle) = test_socket(vec![
+        (json!(["get_property", "path"]), data(json!("video.mkv"))),
+        (json!(["get_property", "time-pos"]), data(json!(12.5))),
+        (json!(["get_property", "pause"]), data(json!(true))),
+        (json!(["get_property", "volume"]), data(json!(80.0))),
+        (json!(["get_property", "speed"]), data(json!(1.5))),
+        (json!(["get_property", "mute"]), data(json!(false))),
+        (json!(["get_property", "loop-file"]), data(json!(false))),
+        (json!(["get_property", "loop-playlist"]), data(json!("inf"))),
+        (json!(["get_property", "playlist-pos"]), data(json!(0))),
+        (json!(["set_property", "pause", true]), success()),
+        (json!(["set_property", "volume", 80.0]), success()),
+        (json!(["set_property", "speed", 1.5]), success()),
+        (json!(["set_property", "mute", false]), success()),
+        (json!(["set_property", "loop-file", "no"]), success()),
+        (json!(["set_property", "loop-playlist", "inf"]), success()),
+        (
+            json!(["loadfile", "video.mkv", "replace", "-1", "start=12.5"]),
+            success(),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let snapshot = mpv.snapshot().await?;
+    assert_eq!(
+        snapshot,
+        PlaybackSnapshot {
+            now_playing: Some(NowPlaying {
+                path: "video.mkv".to_string(),
+                time_pos: Some(12.5),
+            }),
+            pause: true,
+            volume: 80.0,
+            speed: 1.5,
+            mute: false,
+            loop_file: LoopProperty::No,
+            loop_playlist: LoopProperty::Inf,
+            playlist_pos: Some(0),
+        }
+    );
+
+    let serialized = serde_json::to_string(&snapshot).unwrap();
+    let deserialized: PlaybackSnapshot = serde_json::from_str(&serialized).unwrap();
+    mpv.restore(&deserialized).await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_snapshot_idle() -> Result<(), MpvError> {
+    let data = |data: Value| json!({ "data": data, "request_id": 0, "error": "success" });
+    let unavailable = || json!({ "request_id": 0, "error": "property unavailable" });
+    let (server, join_handle) = test_socket(vec![
+        (json!(["get_property", "path"]), unavailable()),
+        (json!(["get_property", "time-pos"]), unavailable()),
+        (json!(["get_property", "pause"]), data(json!(false))),
+        (json!(["get_property", "volume"]), data(json!(100.0))),
+        (json!(["get_property", "speed"]), data(json!(1.0))),
+        (json!(["get_property", "mute"]), data(json!(false))),
+        (json!(["get_property", "loop-file"]), data(json!(false))),
+        (json!(["get_property", "loop-playlist"]), data(json!(false))),
+        (json!(["get_property", "playlist-pos"]), data(json!(-1))),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert_eq!(
+        mpv.snapshot().await?,
+        PlaybackSnapshot {
+            now_playing: None,
+            pause: false,
+            volume: 100.0,
+            speed: 1.0,
+            mute: false,
+            loop_file: LoopProperty::No,
+            loop_playlist: LoopProperty::No,
+            playlist_pos: None,
+        }
+    );
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_drop_buffers() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["drop-buffers"]), success()),
+        (json!(["drop-buffers"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.run_command(MpvCommand::DropBuffers).await?;
+    mpv.drop_buffers().await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_frame_step() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["frame-step"]), success()),
+        (json!(["frame-back-step"]), success()),
+        (json!(["frame-step"]), success()),
+        (json!(["frame-back-step"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.run_command(MpvCommand::FrameStep).await?;
+    mpv.run_command(MpvCommand::FrameBackStep).await?;
+    mpv.frame_step().await?;
+    mpv.frame_back_step().await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_screenshot() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        (json!(["screenshot", "subtitles"]), success()),
+        (json!(["screenshot", "window"]), success()),
+        (
+            json!(["screenshot-to-file", "/tmp/thumbnail.png", "video"]),
+            success(),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.run_command(MpvCommand::Screenshot {
+        flag: ScreenshotFlag::default(),
+    })
+    .await?;
+    mpv.run_command(MpvCommand::Screenshot {
+        flag: ScreenshotFlag::Window,
+    })
+    .await?;
+    mpv.run_command(MpvCommand::ScreenshotToFile {
+        filename: "/tmp/thumbnail.png".to_string(),
+        flag: ScreenshotFlag::Video,
+    })
+    .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_seek_precision() -> Result<(), MpvError> {
+    let options = [
+        (SeekOptions::Relative, "relative"),
+        (SeekOptions::Absolute, "absolute"),
+        (SeekOptions::RelativePercent, "relative-percent"),
+        (SeekOptions::AbsolutePercent, "absolute-percent"),
+    ];
+    let precisions = [
+        (SeekPrecision::Default, None),
+        (SeekPrecision::Keyframes, Some("keyframes")),
+        (SeekPrecision::Exact, Some("exact")),
+    ];
+
+    let mut exchanges = Vec::new();
+    for (_, option) in options {
+        for (_, precision) in precisions {
+            let flags = match precision {
+                Some(precision) => format!("{}+{}", option, precision),
+                None => option.to_string(),
+            };
+            exchanges.push((json!(["seek", "10", flags]), success()));
+        }
+    }
+    let (server, join_handle) = test_socket(exchanges);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    for (option, _) in options {
+        for (precision, _) in precisions {
+            mpv.seek_with_precision(10.0, option, precision).await?;
+        }
+    }
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_indexed() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![(
+        json!(["get_property", "track-list/1/lang"]),
+        json!({ "data": "eng", "request_id": 0, "error": "success" }),
+    )]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let lang: Option<String> = mpv.get_indexed("track-list", 1, "lang").await?;
+    assert_eq!(lang, Some("eng".to_string()));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_option_info() -> Result<(), MpvError> {
+    let data = |data: Value| json!({ "data": data, "request_id": 0, "error": "success" });
+    let (server, join_handle) = test_socket(vec![
+        (
+            json!(["get_property", "option-info/volume"]),
+            data(json!({
+                "name": "volume",
+                "type": "Float",
+                "set-from-commandline": false,
+                "set-locally": false,
+                "expects-file": false,
+                "default-value": 100.0,
+                "min": -1.0,
+                "max": 1000.0,
+            })),
+        ),
+        (
+            json!(["get_property", "option-info/hwdec"]),
+            data(json!({
+                "name": "hwdec",
+                "type": "String",
+                "default-value": "no",
+            })),
+        ),
+        (
+            json!(["get_property", "option-info/fullscreen"]),
+            data(json!({
+                "name": "fullscreen",
+                "type": "Flag",
+                "default-value": false,
+                "choices": ["no", "yes"],
+            })),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert_eq!(
+        mpv.get_option_info("volume").await?,
+        OptionInfo {
+            default: Some("100.0".to_string()),
+            min: Some(-1.0),
+            max: Some(1000.0),
+            choices: vec![],
+            type_name: "Float".to_string(),
+        }
+    );
+    assert_eq!(
+        mpv.get_option_info("hwdec").await?,
+        OptionInfo {
+            default: Some("no".to_string()),
+            min: None,
+            max: None,
+            choices: vec![],
+            type_name: "String".to_string(),
+        }
+    );
+    assert_eq!(
+        mpv.get_option_info("fullscreen").await?,
+        OptionInfo {
+            default: Some("no".to_string()),
+            min: None,
+            max: None,
+            choices: vec!["no".to_string(), "yes".to_string()],
+            type_name: "Flag".to_string(),
+        }
+    );
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_with_paused() -> Result<(), MpvError> {
+    let data = |data: Value| json!({ "data": data, "request_id": 0, "error": "success" });
+    let (server, join_handle) = test_socket(vec![
+        (json!(["get_property", "pause"]), data(json!(false))),
+        (json!(["set_property", "pause", true]), success()),
+        (json!(["frame-step"]), success()),
+        (json!(["set_property", "pause", false]), success()),
+        (json!(["get_property", "pause"]), data(json!(false))),
+        (json!(["set_property", "pause", true]), success()),
+        (
+            json!(["frame-step"]),
+            json!({ "request_id": 0, "error": "error running command" }),
+        ),
+        (json!(["set_property", "pause", false]), success()),
+        (json!(["get_property", "pause"]), data(json!(true))),
+        (json!(["frame-step"]), success()),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let value = mpv
+        .with_paused(|| async {
+            mpv.run_command_raw("frame-step", &[]).await?;
+            Ok(42)
+        })
+        .await?;
+    assert_eq!(value, 42);
+
+    let result = mpv
+        .with_paused(|| async { mpv.run_command_raw("frame-step", &[]).await })
+        .await;
+    assert!(matches!(result, Err(MpvError::MpvError { .. })));
+
+    // Already paused, so the pause state is left alone
+    mpv.with_paused(|| async { mpv.run_command_raw("frame-step", &[]).await })
+        .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
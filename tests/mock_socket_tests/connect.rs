@@ -0,0 +1,298 @@
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use mpvipc_async::{Event, Mpv, MpvError, MpvExt};
+use serde_json::{Value, json};
+use test_log::test;
+use tokio::net::{UnixListener, UnixStream};
+use tokio_util::{
+    bytes::{Bytes, BytesMut},
+    codec::{Decoder, Encoder, Framed, LengthDelimitedCodec, LinesCodec},
+};
+
+/// Frames each message with a length prefix instead of a trailing newline.
+struct LengthPrefixedStrings(LengthDelimitedCodec);
+
+impl Decoder for LengthPrefixedStrings {
+    type Item = String;
+    type Error = std::io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<String>, Self::Error> {
+        self.0
+            .decode(src)?
+            .map(|frame| String::from_utf8(frame.to_vec()).map_err(std::io::Error::other))
+            .transpose()
+    }
+}
+
+impl Encoder<String> for LengthPrefixedStrings {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: String, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.0.encode(Bytes::from(item), dst)
+    }
+}
+
+fn temp_socket_path() -> String {
+    std::env::temp_dir()
+        .join(format!("mpvipc-async-test-{}", uuid::Uuid::new_v4()))
+        .to_str()
+        .unwrap()
+        .to_owned()
+}
+
+#[test(tokio::test)]
+async fn test_connect_when_available() -> Result<(), MpvError> {
+    let socket_path = temp_socket_path();
+
+    let socket_path_clone = socket_path.clone();
+    let listener_handle = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let listener = UnixListener::bind(&socket_path_clone).unwrap();
+        listener.accept().await.unwrap()
+    });
+
+    let mpv = Mpv::connect_when_available(&socket_path, Duration::from_millis(1000)).await;
+    assert!(mpv.is_ok());
+
+    listener_handle.await.unwrap();
+    std::fs::remove_file(&socket_path).unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_diagnostics() -> Result<(), MpvError> {
+    let socket_path = temp_socket_path();
+    let listener = UnixListener::bind(&socket_path).unwrap();
+    let server_handle = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        while let Some(Ok(line)) = framed.next().await {
+            let command: Value = serde_json::from_str(&line).unwrap();
+            let reply = match command["command"][1].as_str() {
+                Some("mpv-version") => json!({ "data": "mpv 0.39.0", "error": "success" }),
+                Some("ffmpeg-version") => json!({ "data": "7.1", "error": "success" }),
+                _ => json!({ "error": "success" }),
+            };
+            let mut reply = reply.as_object().unwrap().clone();
+            reply.insert("request_id".to_owned(), command["request_id"].clone());
+            framed.send(Value::Object(reply).to_string()).await.unwrap();
+            framed
+                .send(json!({ "event": "playback-restart" }).to_string())
+                .await
+                .unwrap();
+        }
+    });
+
+    let mpv = Mpv::connect(&socket_path).await?;
+    mpv.observe_property(1, "volume").await?;
+    let diagnostics = mpv.diagnostics().await?;
+    assert!(diagnostics.is_connected);
+    assert_eq!(
+        diagnostics.socket_path.as_deref(),
+        Some(socket_path.as_str())
+    );
+    assert_eq!(diagnostics.mpv_version.as_deref(), Some("mpv 0.39.0"));
+    assert_eq!(diagnostics.ffmpeg_version.as_deref(), Some("7.1"));
+    assert_eq!(diagnostics.observed_properties, 1);
+    assert!(diagnostics.since_last_event.is_some());
+
+    mpv.disconnect().await?;
+    let diagnostics = mpv.diagnostics().await?;
+    assert!(!diagnostics.is_connected);
+    assert_eq!(diagnostics.mpv_version, None);
+
+    server_handle.abort();
+    std::fs::remove_file(&socket_path).unwrap();
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test(tokio::test)]
+async fn test_connect_abstract() -> Result<(), MpvError> {
+    use std::os::linux::net::SocketAddrExt;
+
+    let name = format!("mpvipc-async-test-{}", uuid::Uuid::new_v4());
+    let address = std::os::unix::net::SocketAddr::from_abstract_name(&name).unwrap();
+    let listener = std::os::unix::net::UnixListener::bind_addr(&address).unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let listener = UnixListener::from_std(listener).unwrap();
+
+    let server_handle = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        framed.next().await.unwrap().unwrap();
+        let reply = json!({ "data": 64.0, "request_id": 0, "error": "success" });
+        framed.send(reply.to_string()).await.unwrap();
+    });
+
+    let mpv = Mpv::connect_abstract(&name).await?;
+    assert_eq!(mpv.get_property::<f64>("volume").await?, Some(64.0));
+
+    server_handle.await.unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_connect_when_available_timeout() -> Result<(), MpvError> {
+    let socket_path = temp_socket_path();
+
+    let mpv = Mpv::connect_when_available(&socket_path, Duration::from_millis(50)).await;
+
+    match mpv {
+        Err(MpvError::Timeout(_)) => {}
+        _ => panic!("Unexpected result: {:?}", mpv),
+    }
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_connect_with_retry() -> Result<(), MpvError> {
+    let socket_path = temp_socket_path();
+
+    // The socket file exists, but nothing is listening on it yet
+    drop(std::os::unix::net::UnixListener::bind(&socket_path).unwrap());
+
+    let socket_path_clone = socket_path.clone();
+    let listener_handle = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        std::fs::remove_file(&socket_path_clone).unwrap();
+        let listener = UnixListener::bind(&socket_path_clone).unwrap();
+        listener.accept().await.unwrap()
+    });
+
+    let mpv = Mpv::connect_with_retry(&socket_path, 100, Duration::from_millis(10)).await;
+    assert!(mpv.is_ok());
+
+    listener_handle.await.unwrap();
+    std::fs::remove_file(&socket_path).unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_connect_with_retry_gives_up() -> Result<(), MpvError> {
+    let socket_path = temp_socket_path();
+
+    let mpv = Mpv::connect_with_retry(&socket_path, 3, Duration::from_millis(10)).await;
+
+    match mpv {
+        Err(MpvError::MpvSocketConnectionError(_)) => {}
+        _ => panic!("Unexpected result: {:?}", mpv),
+    }
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_connect_socket_with_codec() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LengthDelimitedCodec::new());
+        let request = framed.next().await.unwrap().unwrap();
+        let json = serde_json::from_slice::<Value>(&request).unwrap();
+        assert_eq!(json["command"], json!(["get_property", "volume"]));
+        let reply = json!({ "data": 50.0, "request_id": json["request_id"], "error": "success" });
+        framed.send(Bytes::from(reply.to_string())).await.unwrap();
+    });
+
+    let mpv = Mpv::builder()
+        .connect_socket_with_codec(server, LengthPrefixedStrings(LengthDelimitedCodec::new()))
+        .await?;
+    let volume: Option<f64> = mpv.get_property("volume").await?;
+    assert_eq!(volume, Some(50.0));
+
+    join_handle.await.unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_heartbeat() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let server_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+
+        // Answer the first heartbeat, and then stop answering without closing the socket
+        let request = framed.next().await.unwrap().unwrap();
+        let json = serde_json::from_str::<Value>(&request).unwrap();
+        assert_eq!(json["command"], json!(["get_property", "mpv-version"]));
+        let reply =
+            json!({ "data": "mpv 0.38.0", "request_id": json["request_id"], "error": "success" });
+        framed.send(reply.to_string()).await.unwrap();
+
+        framed.next().await.unwrap().unwrap();
+        std::future::pending::<()>().await;
+    });
+
+    let mpv = Mpv::builder()
+        .heartbeat(Duration::from_millis(20), Duration::from_millis(50))
+        .connect_socket(server)
+        .await?;
+    assert!(mpv.is_connected());
+    assert_eq!(mpv.last_heartbeat(), None);
+
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while mpv.last_heartbeat().is_none() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        while mpv.is_connected() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("heartbeat did not close the connection");
+
+    assert!(mpv.last_heartbeat().is_some());
+    assert!(mpv.get_property::<f64>("volume").await.is_err());
+
+    server_handle.abort();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_commands_after_disconnect() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let server_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        framed
+            .send(json!({ "event": "file-loaded" }).to_string())
+            .await
+            .unwrap();
+        std::future::pending::<()>().await;
+    });
+
+    let mpv = Mpv::builder()
+        .event_replay_depth(1)
+        .connect_socket(server)
+        .await?;
+    let other = mpv.clone();
+
+    tokio::time::timeout(
+        Duration::from_secs(1),
+        other.get_event_stream().await.next(),
+    )
+    .await
+    .expect("the event was not received");
+    mpv.disconnect().await?;
+
+    // Events buffered before the disconnect are still available on every clone,
+    // but commands fail with a dedicated error instead of an internal one.
+    let event = other.get_event_stream().await.next().await.unwrap()?;
+    assert_eq!(event, Event::FileLoaded);
+    assert!(!other.is_connected());
+    assert_eq!(
+        other.get_property::<f64>("volume").await,
+        Err(MpvError::Disconnected)
+    );
+    assert_eq!(other.disconnect().await, Err(MpvError::Disconnected));
+
+    server_handle.abort();
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use futures::{SinkExt, stream::StreamExt};
-use mpvipc_async::{Event, Mpv, MpvDataType, MpvExt};
+use mpvipc_async::{Event, Mpv, MpvDataType, MpvError, MpvExt, Property, merge_event_streams};
 use serde_json::json;
+use std::collections::HashMap;
 use test_log::test;
 use tokio::{net::UnixStream, task::JoinHandle};
 use tokio_util::codec::{Framed, LinesCodec, LinesCodecError};
@@ -63,3 +64,247 @@ async fn test_observe_event_successful() {
 
     join_handle.await.unwrap().unwrap();
 }
+
+#[test(tokio::test)]
+async fn test_event_replay_buffer() {
+    let (server, join_handle) = test_socket(vec![
+        (true, json!({ "event": "file-loaded" }).to_string()),
+        (true, json!({ "event": "playback-restart" }).to_string()),
+        (true, json!({ "event": "seek" }).to_string()),
+        (
+            false,
+            json!({ "data": 64.0, "request_id": 0, "error": "success" }).to_string(),
+        ),
+    ]);
+
+    let mpv = Mpv::builder()
+        .event_replay_depth(2)
+        .connect_socket(server)
+        .await
+        .unwrap();
+
+    // By the time the reply has arrived, all of the events have been processed,
+    // and no subscriber existed while they were broadcast.
+    let _: Option<f64> = mpv.get_property("volume").await.unwrap();
+
+    let mut events = mpv.get_event_stream().await;
+    assert_eq!(
+        events.next().await.unwrap().unwrap(),
+        Event::PlaybackRestart
+    );
+    assert_eq!(events.next().await.unwrap().unwrap(), Event::Seek);
+
+    join_handle.await.unwrap().unwrap();
+}
+
+#[test(tokio::test)]
+async fn test_event_max_value_depth() {
+    let nested = |depth: usize| (0..depth).fold(json!("leaf"), |value, _| json!([value]));
+    let change = |depth: usize| {
+        json!({ "event": "property-change", "id": 1, "name": "metadata", "data": nested(depth) })
+            .to_string()
+    };
+    let (server, join_handle) = test_socket(vec![
+        (true, change(4)),
+        (true, change(3)),
+        (
+            false,
+            json!({ "data": 64.0, "request_id": 0, "error": "success" }).to_string(),
+        ),
+    ]);
+
+    let mpv = Mpv::builder()
+        .event_replay_depth(2)
+        .max_value_depth(3)
+        .connect_socket(server)
+        .await
+        .unwrap();
+    let _: Option<f64> = mpv.get_property("volume").await.unwrap();
+
+    let mut events = mpv.get_event_stream().await;
+    assert!(matches!(
+        events.next().await.unwrap(),
+        Err(MpvError::Other(_))
+    ));
+    assert!(matches!(
+        events.next().await.unwrap(),
+        Ok(Event::PropertyChange { id: Some(1), .. })
+    ));
+
+    join_handle.await.unwrap().unwrap();
+}
+
+#[test(tokio::test)]
+async fn test_event_stream_with_raw() {
+    let raw_event = json!({
+        "event": "end-file",
+        "reason": "eof",
+        "playlist_entry_id": 1,
+        "not-modeled": [1, 2, 3],
+    });
+    let (server, join_handle) = test_socket(vec![
+        (true, raw_event.to_string()),
+        (
+            false,
+            json!({ "data": 64.0, "request_id": 0, "error": "success" }).to_string(),
+        ),
+    ]);
+
+    let mpv = Mpv::builder()
+        .event_replay_depth(1)
+        .connect_socket(server)
+        .await
+        .unwrap();
+    let _: Option<f64> = mpv.get_property("volume").await.unwrap();
+
+    let (event, raw) = mpv
+        .get_event_stream_with_raw()
+        .await
+        .next()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.kind(), "end-file");
+    assert_eq!(raw, raw_event);
+
+    join_handle.await.unwrap().unwrap();
+}
+
+#[test(tokio::test)]
+async fn test_event_channel_is_lossless() {
+    // This is more than the capacity of the broadcast channel behind `get_event_stream`
+    let mut answers: Vec<_> = (0..500)
+        .map(|_| (true, json!({ "event": "seek" }).to_string()))
+        .collect();
+    answers.push((
+        false,
+        json!({ "data": 64.0, "request_id": 0, "error": "success" }).to_string(),
+    ));
+    let (server, join_handle) = test_socket(answers);
+
+    let mpv = Mpv::connect_socket(server).await.unwrap();
+    let mut events = mpv.get_event_channel();
+
+    let _: Option<f64> = mpv.get_property("volume").await.unwrap();
+
+    for _ in 0..500 {
+        assert_eq!(events.recv().await.unwrap(), Event::Seek);
+    }
+    assert!(events.try_recv().is_err());
+
+    join_handle.await.unwrap().unwrap();
+}
+
+#[test(tokio::test)]
+async fn test_malformed_message_is_ignored() {
+    let (server, join_handle) = test_socket(vec![
+        (true, json!({ "foo": 1 }).to_string()),
+        (true, json!({ "event": "seek" }).to_string()),
+        (false, json!({ "foo": 2 }).to_string()),
+        (
+            true,
+            json!({ "data": 64.0, "request_id": 0, "error": "success" }).to_string(),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await.unwrap();
+    let mut events = mpv.get_event_stream().await;
+
+    let volume: Option<f64> = mpv.get_property("volume").await.unwrap();
+    assert_eq!(volume, Some(64.0));
+
+    assert_eq!(events.next().await.unwrap().unwrap(), Event::Seek);
+
+    join_handle.await.unwrap().unwrap();
+}
+
+#[test(tokio::test)]
+async fn test_dump_observed() {
+    let (server, join_handle) = test_socket(vec![
+        (
+            false,
+            json!({ "request_id": 0, "error": "success" }).to_string(),
+        ),
+        (
+            true,
+            json!({ "data": 64.0, "event": "property-change", "id": 1, "name": "volume" })
+                .to_string(),
+        ),
+        (
+            true,
+            json!({ "data": 1.0, "event": "property-change", "id": 2, "name": "speed" })
+                .to_string(),
+        ),
+        (
+            false,
+            json!({ "data": false, "request_id": 0, "error": "success" }).to_string(),
+        ),
+        (
+            false,
+            json!({ "request_id": 0, "error": "success" }).to_string(),
+        ),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await.unwrap();
+    assert!(mpv.dump_observed().is_empty());
+
+    mpv.observe_property(1, "volume").await.unwrap();
+
+    // By the time the reply has arrived, the events have been processed.
+    // The speed change is ignored, since it was never observed.
+    let _: Option<bool> = mpv.get_property("pause").await.unwrap();
+    assert_eq!(
+        mpv.dump_observed(),
+        HashMap::from([("volume".to_string(), Property::Volume(64.0))])
+    );
+
+    mpv.unobserve_property(1).await.unwrap();
+    assert!(mpv.dump_observed().is_empty());
+
+    join_handle.await.unwrap().unwrap();
+}
+
+#[test(tokio::test)]
+async fn test_merge_event_streams() {
+    let (left_server, left_join_handle) = test_socket(vec![
+        (
+            false,
+            json!({ "request_id": 0, "error": "success" }).to_string(),
+        ),
+        (true, json!({ "event": "file-loaded" }).to_string()),
+    ]);
+    let (right_server, right_join_handle) = test_socket(vec![
+        (
+            false,
+            json!({ "request_id": 0, "error": "success" }).to_string(),
+        ),
+        (true, json!({ "event": "seek" }).to_string()),
+        (true, json!({ "event": "playback-restart" }).to_string()),
+    ]);
+
+    let left = Mpv::connect_socket(left_server).await.unwrap();
+    let right = Mpv::connect_socket(right_server).await.unwrap();
+    let events = merge_event_streams(&[&left, &right]).await;
+
+    // Make the mock sockets send their events
+    left.set_property("pause", true).await.unwrap();
+    right.set_property("pause", true).await.unwrap();
+
+    let mut events = events
+        .take(3)
+        .map(|(index, event)| (index, event.unwrap()))
+        .collect::<Vec<_>>()
+        .await;
+    events.sort_by_key(|(index, _)| *index);
+    assert_eq!(
+        events,
+        vec![
+            (0, Event::FileLoaded),
+            (1, Event::Seek),
+            (1, Event::PlaybackRestart),
+        ]
+    );
+
+    left_join_handle.await.unwrap().unwrap();
+    right_join_handle.await.unwrap().unwrap();
+}
@@ -1,7 +1,14 @@
-use std::{panic, time::Duration};
+use std::{
+    collections::HashMap,
+    panic,
+    time::{Duration, Instant},
+};
 
 use futures::{SinkExt, StreamExt, stream::FuturesUnordered};
-use mpvipc_async::{Mpv, MpvError, MpvExt, Playlist, PlaylistEntry};
+use mpvipc_async::{
+    CurrentTracks, MediaKind, Mpv, MpvDataType, MpvError, MpvExt, Playlist, PlaylistEntry, Track,
+    TrackType,
+};
 use serde_json::{Value, json};
 use test_log::test;
 use tokio::{net::UnixStream, task::JoinHandle};
@@ -36,6 +43,45 @@ async fn test_get_property_successful() -> Result<(), MpvError> {
     Ok(())
 }
 
+#[test(tokio::test)]
+async fn test_get_property_timed() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": 100.0, "request_id": 0, "error": "success" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let before = Instant::now();
+    let (volume, received) = mpv.get_property_timed::<f64>("volume").await?;
+
+    assert_eq!(volume, Some(100.0));
+    assert!(before <= received && received <= Instant::now());
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_property_timed_command_timeout() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        framed.next().await.unwrap()?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok::<(), LinesCodecError>(())
+    });
+
+    let mpv = Mpv::builder()
+        .command_timeout(Duration::from_millis(20))
+        .connect_socket(server)
+        .await?;
+    let result = mpv.get_property_timed::<f64>("volume").await;
+
+    assert!(matches!(result, Err(MpvError::Timeout(_))));
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
 #[test(tokio::test)]
 async fn test_get_property_broken_pipe() -> Result<(), MpvError> {
     let (server, join_handle) = test_socket(vec![]);
@@ -234,3 +280,502 @@ async fn test_get_playlist_empty() -> Result<(), MpvError> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_get_properties_pipelined() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+
+        // All requests should arrive before any reply is sent
+        let mut properties = Vec::new();
+        for _ in 0..3 {
+            let request = framed.next().await.unwrap().unwrap();
+            let json = serde_json::from_str::<Value>(&request).unwrap();
+            properties.push(json["command"][1].as_str().unwrap().to_owned());
+        }
+        assert_eq!(properties, vec!["volume", "duration", "pause"]);
+
+        for reply in [
+            json!({ "data": 50.0, "request_id": 0, "error": "success" }),
+            json!({ "request_id": 0, "error": "property unavailable" }),
+            json!({ "data": true, "request_id": 0, "error": "success" }),
+        ] {
+            framed.send(reply.to_string()).await.unwrap();
+        }
+    });
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let values = mpv.get_properties(&["volume", "duration", "pause"]).await?;
+
+    assert_eq!(values, vec![Some(json!(50.0)), None, Some(json!(true))]);
+    join_handle.await.unwrap();
+
+    Ok(())
+}
+
+#[cfg(feature = "test-util")]
+#[test(tokio::test)]
+async fn test_get_property_value_with_meta() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        let mut request_ids = Vec::new();
+        for _ in 0..2 {
+            let request = framed.next().await.unwrap().unwrap();
+            let json = serde_json::from_str::<Value>(&request).unwrap();
+            let request_id = json["request_id"].as_u64().unwrap();
+            request_ids.push(request_id);
+            framed
+                .send(
+                    json!({ "data": 100.0, "request_id": request_id, "error": "success" })
+                        .to_string(),
+                )
+                .await
+                .unwrap();
+        }
+        request_ids
+    });
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let first = mpv.get_property_value_with_meta("volume").await?;
+    let second = mpv.get_property_value_with_meta("volume").await?;
+
+    let request_ids = join_handle.await.unwrap();
+    assert_eq!(first, (Some(json!(100.0)), request_ids[0]));
+    assert_eq!(second, (Some(json!(100.0)), request_ids[1]));
+    assert_ne!(request_ids[0], request_ids[1]);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_tracks_by_type() -> Result<(), MpvError> {
+    let track_list = json!([
+        { "id": 1, "type": "video", "codec": "h264", "selected": true, "image": false, "albumart": false },
+        { "id": 1, "type": "audio", "lang": "eng", "codec": "aac", "selected": true, "default": true },
+        { "id": 2, "type": "audio", "lang": "jpn", "title": "Commentary", "codec": "opus", "selected": false },
+        { "id": 1, "type": "sub", "lang": "eng", "codec": "subrip", "external": true, "external-filename": "/tmp/video.srt" },
+    ]);
+    let reply = json!({ "data": track_list, "request_id": 0, "error": "success" }).to_string();
+    let (server, join_handle) = test_socket(vec![reply.clone(), reply]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let audio_tracks = mpv.get_tracks_by_type(TrackType::Audio).await?;
+    let subtitle_tracks = mpv.get_tracks_by_type(TrackType::Sub).await?;
+
+    assert_eq!(
+        audio_tracks
+            .iter()
+            .map(|track| (track.id, track.lang.as_deref(), track.selected))
+            .collect::<Vec<_>>(),
+        vec![(1, Some("eng"), true), (2, Some("jpn"), false)]
+    );
+    assert_eq!(audio_tracks[1].title.as_deref(), Some("Commentary"));
+    assert!(audio_tracks[0].default);
+
+    assert_eq!(
+        subtitle_tracks,
+        vec![Track {
+            id: 1,
+            track_type: TrackType::Sub,
+            title: None,
+            lang: Some("eng".to_string()),
+            codec: Some("subrip".to_string()),
+            selected: false,
+            default: false,
+            forced: false,
+            external: true,
+            external_filename: Some("/tmp/video.srt".to_string()),
+            image: false,
+            albumart: false,
+        }]
+    );
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_current_tracks() -> Result<(), MpvError> {
+    let video = json!({
+        "id": 1, "type": "video", "src-id": 0, "image": false, "albumart": false,
+        "default": true, "forced": false, "external": false, "selected": true,
+        "codec": "h264", "demux-w": 1920, "demux-h": 1080, "demux-fps": 23.976,
+    });
+    let audio = json!({
+        "id": 2, "type": "audio", "src-id": 2, "title": "Commentary", "lang": "jpn",
+        "default": false, "forced": false, "external": false, "selected": true,
+        "codec": "opus", "audio-channels": 2, "demux-samplerate": 48000,
+    });
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": video, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": audio, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "request_id": 0, "error": "property unavailable" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let CurrentTracks { video, audio, sub } = mpv.get_current_tracks().await?;
+
+    let video = video.unwrap();
+    assert_eq!(video.track_type, TrackType::Video);
+    assert_eq!(video.codec.as_deref(), Some("h264"));
+    let audio = audio.unwrap();
+    assert_eq!((audio.id, audio.lang.as_deref()), (2, Some("jpn")));
+    assert_eq!(audio.title.as_deref(), Some("Commentary"));
+    assert_eq!(sub, None);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_metadata_value() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": "Some Artist", "request_id": 0, "error": "success" }).to_string(),
+        json!({ "request_id": 0, "error": "property unavailable" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let artist = mpv.get_metadata_value("artist").await?;
+    let album = mpv.get_metadata_value("album").await?;
+
+    assert_eq!(artist, Some("Some Artist".to_string()));
+    assert_eq!(album, None);
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_is_looping() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": "inf", "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": 3, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": false, "request_id": 0, "error": "success" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert!(mpv.is_looping_file().await?);
+    assert!(mpv.is_looping_playlist().await?);
+    assert!(!mpv.is_looping_file().await?);
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_chapter_and_filtered_metadata() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": { "title": "Chapter 1" }, "request_id": 0, "error": "success" })
+            .to_string(),
+        json!({ "request_id": 0, "error": "property unavailable" }).to_string(),
+        json!({ "data": { "Artist": "Someone", "Title": "Something" }, "request_id": 0, "error": "success" })
+            .to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+
+    assert_eq!(
+        mpv.get_chapter_metadata().await?,
+        HashMap::from([(
+            "title".to_string(),
+            MpvDataType::String("Chapter 1".to_string())
+        )])
+    );
+    assert!(mpv.get_chapter_metadata().await?.is_empty());
+    assert_eq!(
+        mpv.get_filtered_metadata().await?,
+        HashMap::from([
+            (
+                "Artist".to_string(),
+                MpvDataType::String("Someone".to_string())
+            ),
+            (
+                "Title".to_string(),
+                MpvDataType::String("Something".to_string())
+            ),
+        ])
+    );
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_property_null() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": null, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": null, "request_id": 0, "error": "success" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let volume = mpv.get_property::<f64>("volume").await;
+    assert_eq!(
+        volume,
+        Err(MpvError::UnexpectedNull {
+            expected_type: "f64".to_string()
+        })
+    );
+
+    let volume = mpv.get_volume().await;
+    assert_eq!(
+        volume,
+        Err(MpvError::UnexpectedNull {
+            expected_type: "f64".to_string()
+        })
+    );
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_media_kind() -> Result<(), MpvError> {
+    let fixtures = [
+        (json!([]), MediaKind::None),
+        (
+            json!([{ "id": 1, "type": "sub", "selected": true }]),
+            MediaKind::None,
+        ),
+        (
+            json!([{ "id": 1, "type": "audio", "selected": true }]),
+            MediaKind::AudioOnly,
+        ),
+        (
+            json!([
+                { "id": 1, "type": "video", "selected": true, "image": true, "albumart": true },
+                { "id": 1, "type": "audio", "selected": true },
+            ]),
+            MediaKind::AudioOnly,
+        ),
+        (
+            json!([{ "id": 1, "type": "video", "selected": true, "image": true, "albumart": false }]),
+            MediaKind::Image,
+        ),
+        (
+            json!([
+                { "id": 1, "type": "video", "selected": false, "image": true, "albumart": false },
+                { "id": 2, "type": "video", "selected": true, "image": false, "albumart": false },
+                { "id": 1, "type": "audio", "selected": true },
+            ]),
+            MediaKind::Video,
+        ),
+        (
+            json!([{ "id": 1, "type": "video", "selected": false, "image": false, "albumart": false }]),
+            MediaKind::Video,
+        ),
+    ];
+
+    for (track_list, expected) in fixtures {
+        let reply = json!({ "data": track_list, "request_id": 0, "error": "success" }).to_string();
+        let (server, join_handle) = test_socket(vec![reply]);
+
+        let mpv = Mpv::connect_socket(server).await?;
+        assert_eq!(mpv.media_kind().await?, expected, "{}", track_list);
+
+        join_handle.await.unwrap().unwrap();
+    }
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_negative_times_are_unavailable() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": -0.04, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": -1.5, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": -3.0, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": 12.5, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": 0.0, "request_id": 0, "error": "success" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert_eq!(mpv.get_time_pos().await?, None);
+    assert_eq!(mpv.get_time_remaining().await?, None);
+    assert_eq!(mpv.get_duration().await?, None);
+    assert_eq!(mpv.get_time_pos().await?, Some(12.5));
+    assert_eq!(mpv.get_duration().await?, Some(0.0));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_stream_open_filename() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": "https://example.com/watch?v=1", "request_id": 0, "error": "success" })
+            .to_string(),
+        json!({ "data": "https://cdn.example.com/1.webm", "request_id": 0, "error": "success" })
+            .to_string(),
+        json!({ "request_id": 0, "error": "property unavailable" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert_eq!(mpv.get_file_path().await?, "https://example.com/watch?v=1");
+    assert_eq!(
+        mpv.get_stream_open_filename().await?,
+        Some("https://cdn.example.com/1.webm".to_string())
+    );
+    assert_eq!(mpv.get_stream_open_filename().await?, None);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_is_network_stream() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": true, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": false, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "request_id": 0, "error": "property unavailable" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert!(mpv.is_network_stream().await?);
+    assert!(!mpv.is_network_stream().await?);
+    assert!(!mpv.is_network_stream().await?);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_has_output() -> Result<(), MpvError> {
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": true, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "request_id": 0, "error": "property unavailable" }).to_string(),
+        json!({ "data": true, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": false, "request_id": 0, "error": "success" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert!(mpv.has_video_output().await?);
+    assert!(!mpv.has_video_output().await?);
+    assert!(mpv.has_audio_output().await?);
+    assert!(!mpv.has_audio_output().await?);
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_property_retrying() -> Result<(), MpvError> {
+    let unavailable = || json!({ "request_id": 0, "error": "property unavailable" }).to_string();
+    let (server, join_handle) = test_socket(vec![
+        unavailable(),
+        unavailable(),
+        json!({ "data": 0.5, "request_id": 0, "error": "success" }).to_string(),
+        // Gives up after the last attempt
+        unavailable(),
+        unavailable(),
+        // Real errors are not retried
+        json!({ "request_id": 0, "error": "property not found" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let delay = Duration::from_millis(10);
+    let time_pos: Option<f64> = mpv.get_property_retrying("time-pos", 5, delay).await?;
+    assert_eq!(time_pos, Some(0.5));
+    let time_pos: Option<f64> = mpv.get_property_retrying("time-pos", 2, delay).await?;
+    assert_eq!(time_pos, None);
+    let result: Result<Option<f64>, _> = mpv.get_property_retrying("time-pos", 5, delay).await;
+    assert!(result.is_err());
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_render_stats() -> Result<(), MpvError> {
+    let vo_passes = json!({
+        "fresh": [
+            { "desc": "scaling (luma)", "last": 120000, "avg": 110000, "peak": 250000, "count": 2,
+              "samples": [100000, 120000] },
+        ],
+        "redraw": [],
+    });
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": ["pause", "vo-passes"], "request_id": 0, "error": "success" }).to_string(),
+        json!({ "data": vo_passes, "request_id": 0, "error": "success" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let render_stats = mpv.get_render_stats().await?.unwrap();
+    assert_eq!(render_stats.fresh.len(), 1);
+    assert_eq!(render_stats.fresh[0].desc, "scaling (luma)");
+    assert_eq!(render_stats.fresh[0].peak, Duration::from_micros(250));
+    assert!(render_stats.redraw.is_empty());
+
+    join_handle.await.unwrap().unwrap();
+
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": ["pause"], "request_id": 0, "error": "success" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    assert!(matches!(
+        mpv.get_render_stats().await,
+        Err(MpvError::Unsupported(_))
+    ));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_get_property_as() -> Result<(), MpvError> {
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct MyTrack {
+        id: usize,
+        #[serde(rename = "type")]
+        kind: String,
+        lang: Option<String>,
+    }
+
+    let tracks = json!([
+        { "id": 1, "type": "video", "selected": true },
+        { "id": 1, "type": "audio", "lang": "eng", "selected": true },
+    ]);
+    let (server, join_handle) = test_socket(vec![
+        json!({ "data": tracks, "request_id": 0, "error": "success" }).to_string(),
+        json!({ "request_id": 0, "error": "property unavailable" }).to_string(),
+        json!({ "data": "yes", "request_id": 0, "error": "success" }).to_string(),
+    ]);
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let tracks: Vec<MyTrack> = mpv.get_property_as("track-list").await?;
+    assert_eq!(
+        tracks,
+        vec![
+            MyTrack {
+                id: 1,
+                kind: "video".to_owned(),
+                lang: None,
+            },
+            MyTrack {
+                id: 1,
+                kind: "audio".to_owned(),
+                lang: Some("eng".to_owned()),
+            },
+        ]
+    );
+    let params: Option<HashMap<String, Value>> = mpv.get_property_as("video-params").await?;
+    assert_eq!(params, None);
+    let result: Result<usize, _> = mpv.get_property_as("fullscreen").await;
+    assert!(matches!(result, Err(MpvError::JsonParseError(_))));
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
@@ -1,3 +1,7 @@
+mod commands;
+mod connect;
 mod events;
 mod get_property;
+#[cfg(feature = "process")]
+mod process;
 mod set_property;
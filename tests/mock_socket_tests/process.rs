@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use mpvipc_async::{Mpv, MpvError};
+use serde_json::{Value, json};
+use test_log::test;
+use tokio::{net::UnixStream, process::Command};
+use tokio_util::codec::{Framed, LinesCodec};
+
+/// Create a mock socket that answers `quit`, and optionally sends the `shutdown` event afterwards.
+fn quit_socket(send_shutdown: bool) -> (UnixStream, tokio::task::JoinHandle<()>) {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        let request = framed.next().await.unwrap().unwrap();
+        let json = serde_json::from_str::<Value>(&request).unwrap();
+        assert_eq!(json["command"], json!(["quit"]));
+
+        framed
+            .send(json!({ "request_id": 0, "error": "success" }).to_string())
+            .await
+            .unwrap();
+        if send_shutdown {
+            framed
+                .send(json!({ "event": "shutdown" }).to_string())
+                .await
+                .unwrap();
+        }
+    });
+
+    (server, join_handle)
+}
+
+#[test(tokio::test)]
+async fn test_quit_and_wait() -> Result<(), MpvError> {
+    let (server, join_handle) = quit_socket(true);
+    let child = Command::new("true").kill_on_drop(true).spawn().unwrap();
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.quit_and_wait(child, Duration::from_secs(5)).await?;
+
+    join_handle.await.unwrap();
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_quit_and_wait_kills_on_timeout() -> Result<(), MpvError> {
+    let (server, join_handle) = quit_socket(false);
+    let child = Command::new("sleep")
+        .arg("30")
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+
+    let mpv = Mpv::connect_socket(server).await?;
+    let result = mpv.quit_and_wait(child, Duration::from_millis(100)).await;
+    assert!(matches!(result, Err(MpvError::Timeout(_))));
+
+    join_handle.await.unwrap();
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use std::{panic, time::Duration};
 
 use futures::{SinkExt, StreamExt, stream::FuturesUnordered};
-use mpvipc_async::{Mpv, MpvError};
+use mpvipc_async::{Mpv, MpvDataType, MpvError};
 use serde_json::{Value, json};
 use test_log::test;
 use tokio::{net::UnixStream, task::JoinHandle};
@@ -96,6 +96,40 @@ async fn test_get_property_error() -> Result<(), MpvError> {
     Ok(())
 }
 
+#[test(tokio::test)]
+async fn test_set_property_data() -> Result<(), MpvError> {
+    let (socket, server) = UnixStream::pair().unwrap();
+    let join_handle: JoinHandle<Result<(), LinesCodecError>> = tokio::spawn(async move {
+        let mut framed = Framed::new(socket, LinesCodec::new());
+        let request = framed.next().await.unwrap()?;
+        let json = serde_json::from_str::<Value>(&request).unwrap();
+        assert_eq!(
+            json["command"],
+            json!(["set_property", "metadata", { "title": "Song", "track": 3 }])
+        );
+        framed
+            .send(json!({ "request_id": 0, "error": "success" }).to_string())
+            .await
+    });
+
+    let mpv = Mpv::connect_socket(server).await?;
+    mpv.set_property_data(
+        "metadata",
+        MpvDataType::HashMap(
+            [
+                ("title".to_string(), MpvDataType::String("Song".to_string())),
+                ("track".to_string(), MpvDataType::Usize(3)),
+            ]
+            .into(),
+        ),
+    )
+    .await?;
+
+    join_handle.await.unwrap().unwrap();
+
+    Ok(())
+}
+
 #[test(tokio::test)]
 async fn test_set_property_simultaneous_requests() {
     let (socket, server) = UnixStream::pair().unwrap();